@@ -21,8 +21,11 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 // Path is for working with file system paths
 
-use walkdir::WalkDir;
-// For recursively walking through directories
+use tauri::State;
+// State<T> gives commands access to shared app state (like our DbPool)
+
+use crate::db::DbPool;
+use crate::tags::TagBackends;
 
 // ============================================================================
 // DATA STRUCTURES (Types)
@@ -52,6 +55,21 @@ pub struct FileInfo {
     pub thumbnail_path: Option<String>,  // Might not have generated thumbnail
     pub finder_tags: Vec<String>,  // Vec<T> is like Array<T> in TypeScript
     pub finder_colors: Vec<i32>,   // Finder color codes
+
+    // Emotional metadata the user entered (None if they never added any)
+    // #[serde(default)] lets the frontend omit this field when sending a FileInfo
+    #[serde(default)]
+    pub metadata: Option<FileMetadata>,
+
+    // (device, inode) - stays the same when the file is renamed or moved.
+    // Only used by the backend to detect moves, so it's never sent to JS.
+    #[serde(skip)]
+    pub inode: Option<(u64, u64)>,
+
+    // When the file's inode last changed (ctime, in ns): bumps on tag edits too,
+    // which don't touch modified_at. Backend-only, like inode.
+    #[serde(skip)]
+    pub changed_at: Option<i64>,
 }
 
 /// Finder tag with color information
@@ -62,7 +80,7 @@ pub struct FinderTag {
 }
 
 /// Metadata for emotional tagging
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileMetadata {
     pub file_id: i64,
     pub mood: Option<String>,
@@ -70,6 +88,7 @@ pub struct FileMetadata {
     pub vibe_color: Option<String>,
     pub location: Option<String>,
     pub energy_level: Option<String>,
+    pub notes: Option<String>,  // Free-form "why this file matters" text
 }
 
 /// Read-only event data from Apple Calendar (macOS).
@@ -275,266 +294,40 @@ fn run_applescript(source: &str) -> Result<String, String> {
 }
 
 // ============================================================================
-// FILE SCANNING COMMANDS
+// MACOS FINDER TAGS
 // ============================================================================
 
 #[tauri::command]
-pub async fn scan_directories(app_handle: tauri::AppHandle, directories: Vec<String>) -> Result<Vec<FileInfo>, String> {
-    // RUST RESULT TYPE:
-    // Result<T, E> represents either:
-    // - Ok(T): Success with value of type T
-    // - Err(E): Error with value of type E
-    //
-    // This is Rust's way of handling errors (no exceptions!)
-    // It forces you to handle both success and error cases
-    //
-    // Result<Vec<FileInfo>, String> means:
-    // - On success: return a vector of FileInfo
-    // - On error: return a String error message
-
-    println!("🔍 Scanning directories: {:?}", directories);
-    // {:?} uses Debug formatting (prints the whole vector)
-
-    let mut all_files = Vec::new();
-    // "let" declares a variable
-    // "mut" makes it mutable (can be changed)
-    // In Rust, variables are immutable by default!
-    // Vec::new() creates an empty vector (like [] in JS)
-
-    for directory in directories {
-        // Loop through each directory path
-        // "for item in collection" is like "for (const item of collection)" in JS
-
-        let directory_path = Path::new(&directory);
-        if !directory_path.exists() || !directory_path.is_dir() {
-            eprintln!("⚠️  Skipping non-directory path: {}", directory);
-            continue;
-        }
-
-        match scan_directory(&directory).await {
-            // "&directory" is a reference (borrow) to directory
-            // We're not giving ownership, just letting scan_directory "look at" it
-            //
-            // match is like switch/case but mandatory to handle all cases
-
-            Ok(files) => {
-                // If scan succeeded, add files to our collection
-                all_files.extend(files);
-                // extend() adds all items from one Vec to another
-                // Like array.push(...otherArray) in JS
-            }
-
-            Err(e) => {
-                eprintln!("❌ Error scanning {}: {}", directory, e);
-                // eprintln! prints to stderr
-                // We'll continue scanning other directories even if one fails
-            }
-        }
-    }
-
-    println!("✅ Found {} files total", all_files.len());
-
-    // PHASE 2: Save files to database!
-    // Get database connection and save all scanned files
-    match crate::db::get_connection(&app_handle) {
-        Ok(mut conn) => {
-            match crate::db::upsert_files(&mut conn, &all_files) {
-                Ok(saved_count) => println!("💾 Saved {} files to database", saved_count),
-                Err(e) => eprintln!("❌ Failed to save scan results: {}", e),
-            }
-        }
-        Err(e) => {
-            eprintln!("❌ Failed to open database: {}", e);
-            // Continue anyway - we can still return the files
-        }
-    }
-
-    Ok(all_files)
-    // Ok() wraps the value in a Result::Ok
-    // This gets converted to a resolved Promise in JavaScript
-}
-
-/// Scan a single directory recursively
-async fn scan_directory(directory: &str) -> Result<Vec<FileInfo>, String> {
-    // "&str" is a string reference (borrowed string)
-    // It's more efficient than String because it doesn't allocate new memory
-
-    let mut files = Vec::new();
-
-    // WalkDir recursively walks through a directory
-    // It's like a breadth-first or depth-first search of the file system
-    for entry in WalkDir::new(directory)
-        .follow_links(false)  // Don't follow symbolic links (prevents infinite loops)
-        .max_depth(20)        // Don't go deeper than 20 levels (safety limit)
-        .into_iter()          // Convert to iterator
-        .filter_map(|e| e.ok())  // Filter out errors, keep only successful entries
-    {
-        // Check if this entry is a file (not a directory)
-        if entry.file_type().is_file() {
-            // Try to convert this entry to FileInfo
-            match entry_to_file_info(entry) {
-                Ok(file_info) => files.push(file_info),
-                Err(e) => {
-                    // Skip files we can't read (permissions, etc.)
-                    eprintln!("⚠️  Skipping file: {}", e);
-                }
-            }
-        }
-    }
-
-    Ok(files)
-}
-
-/// Convert a directory entry to FileInfo
-fn entry_to_file_info(entry: walkdir::DirEntry) -> Result<FileInfo, String> {
-    // Get file metadata (size, timestamps, etc.)
-    let metadata = entry.metadata()
-        .map_err(|e| format!("Failed to read metadata: {}", e))?;
-    // map_err() converts the error type
-    // ? propagates the error up if it occurs
-    // Think of it as: if (error) { return Err(error) }
-
-    let path = entry.path();
-    let path_str = path.to_string_lossy().to_string();
-    // to_string_lossy() converts Path to String
-    // "lossy" means: replace invalid UTF-8 with � character
-
-    // Get file name
-    let name = path
-        .file_name()  // Get the file name part
-        .and_then(|n| n.to_str())  // Convert to &str
-        .unwrap_or("Unknown")  // If None, use "Unknown"
-        .to_string();
-
-    // Get file extension
-    let file_type = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_string();
-
-    // Get timestamps
-    // system_time_to_unix() is a helper we'll define below
-    let created_at = metadata.created()
-        .ok()
-        .map(system_time_to_unix)
-        .unwrap_or(0);
-
-    let modified_at = metadata.modified()
-        .ok()
-        .map(system_time_to_unix)
-        .unwrap_or(0);
-
-    Ok(FileInfo {
-        id: None,  // Will be set by database
-        path: path_str,
-        name,
-        file_type,
-        size: metadata.len(),
-        created_at,
-        modified_at,
-        last_opened_at: None,
-        thumbnail_path: None,
-        finder_tags: Vec::new(),  // Will be populated by get_finder_tags
-        finder_colors: Vec::new(),
-    })
+pub fn get_finder_tags(pool: State<'_, DbPool>, file_path: String) -> Result<Vec<FinderTag>, String> {
+    // The actual xattr work lives in tags.rs - this reads Finder tags on
+    // macOS and freedesktop (user.xdg.tags) tags everywhere
+    TagBackends::load(&pool.reader()).read(Path::new(&file_path))
 }
 
 // ============================================================================
-// MACOS FINDER TAGS
+// EMOTIONAL METADATA
 // ============================================================================
+//
+// Mood, season, vibe color, location, energy level and notes are things
+// only the user knows - they live in the file_metadata table (keyed by the
+// file's database id), never on disk next to the file.
 
 #[tauri::command]
-pub fn get_finder_tags(file_path: String) -> Result<Vec<FinderTag>, String> {
-    // Read Finder tags from extended attributes
-    // Finder stores tags in: com.apple.metadata:_kMDItemUserTags
-
-    use xattr;  // Import xattr crate
-
-    // Extended attribute key for Finder tags
-    let tag_key = "com.apple.metadata:_kMDItemUserTags";
-
-    match xattr::get(&file_path, tag_key) {
-        Ok(Some(data)) => {
-            // We got tag data! It's in plist (property list) format
-            // Parse it to extract tag names and colors
-            parse_finder_tags(data)
-        }
-        Ok(None) => {
-            // File has no tags
-            Ok(Vec::new())
-        }
-        Err(e) => {
-            // Error reading tags
-            Err(format!("Failed to read Finder tags: {}", e))
-        }
-    }
+pub async fn update_file_metadata(pool: State<'_, DbPool>, metadata: FileMetadata) -> Result<(), String> {
+    crate::db::upsert_file_metadata(&pool.writer(), &metadata)
+        .map_err(|e| format!("Failed to save metadata: {}", e))
 }
 
-/// Parse Finder tags from plist data
-fn parse_finder_tags(data: Vec<u8>) -> Result<Vec<FinderTag>, String> {
-    // Finder tags are stored as a plist (XML or binary format)
-    // For MVP, we'll use plist crate to parse it
-
-    use plist::Value;
-
-    match plist::from_bytes::<Value>(&data) {
-        Ok(Value::Array(items)) => {
-            // Tags are stored as an array of strings with color info
-            let tags: Vec<FinderTag> = items
-                .iter()
-                .filter_map(|item| {
-                    if let Value::String(tag_str) = item {
-                        // Tag format: "Name\n6" where 6 is the color code
-                        let parts: Vec<&str> = tag_str.split('\n').collect();
-                        if let Some(name) = parts.first() {
-                            let color = parts.get(1)
-                                .and_then(|c| c.parse::<i32>().ok())
-                                .unwrap_or(0);
-
-                            return Some(FinderTag {
-                                name: name.to_string(),
-                                color,
-                            });
-                        }
-                    }
-                    None
-                })
-                .collect();
-
-            Ok(tags)
-        }
-        Ok(_) => Ok(Vec::new()),
-        Err(e) => Err(format!("Failed to parse tags: {}", e)),
-    }
+#[tauri::command]
+pub async fn get_file_metadata(pool: State<'_, DbPool>, file_id: i64) -> Result<Option<FileMetadata>, String> {
+    crate::db::get_file_metadata(&pool.reader(), file_id)
+        .map_err(|e| format!("Failed to load metadata: {}", e))
 }
 
-// ============================================================================
-// THUMBNAIL GENERATION
-// ============================================================================
-
 #[tauri::command]
-pub async fn generate_thumbnail(file_path: String, output_dir: String) -> Result<String, String> {
-    // Use macOS qlmanage to generate thumbnail
-    // qlmanage is macOS's Quick Look thumbnail generator
-
-    // Create output file path
-    let file_name = Path::new(&file_path)
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("thumbnail");
-
-    let output_path = format!("{}/{}.png", output_dir, file_name);
-
-    // Run: qlmanage -t "file_path" -o "output_dir" -s 256
-    // -t = thumbnail mode
-    // -o = output directory
-    // -s = size (256x256 pixels)
-
-    // For now, we'll return a placeholder
-    // We'll implement this fully when we integrate shell plugin in Phase 5
-
-    Ok(output_path)
+pub async fn clear_file_metadata(pool: State<'_, DbPool>, file_id: i64) -> Result<(), String> {
+    crate::db::clear_file_metadata(&pool.writer(), file_id)
+        .map_err(|e| format!("Failed to clear metadata: {}", e))
 }
 
 // ============================================================================
@@ -542,42 +335,26 @@ pub async fn generate_thumbnail(file_path: String, output_dir: String) -> Result
 // ============================================================================
 
 #[tauri::command]
-pub async fn get_all_files(app_handle: tauri::AppHandle) -> Result<Vec<FileInfo>, String> {
+pub async fn get_all_files(pool: State<'_, DbPool>) -> Result<Vec<FileInfo>, String> {
     // PHASE 2: Load files from database!
-    match crate::db::get_connection(&app_handle) {
-        Ok(conn) => {
-            match crate::db::get_all_files(&conn) {
-                Ok(files) => {
-                    println!("📂 Loaded {} files from database", files.len());
-                    Ok(files)
-                }
-                Err(e) => Err(format!("Failed to load files: {}", e)),
-            }
+    match crate::db::get_all_files(&pool.reader()) {
+        Ok(files) => {
+            println!("📂 Loaded {} files from database", files.len());
+            Ok(files)
         }
-        Err(e) => Err(format!("Failed to connect to database: {}", e)),
+        Err(e) => Err(format!("Failed to load files: {}", e)),
     }
 }
 
 #[tauri::command]
-pub async fn update_file_metadata(_metadata: FileMetadata) -> Result<(), String> {
-    // Will update emotional metadata in database (Phase 7)
-    Ok(())
-}
-
-#[tauri::command]
-pub async fn search_files(app_handle: tauri::AppHandle, query: String) -> Result<Vec<FileInfo>, String> {
+pub async fn search_files(pool: State<'_, DbPool>, query: String) -> Result<Vec<FileInfo>, String> {
     // PHASE 2: Use FTS5 for lightning-fast full-text search!
-    match crate::db::get_connection(&app_handle) {
-        Ok(conn) => {
-            match crate::db::search_files(&conn, &query) {
-                Ok(files) => {
-                    println!("🔎 Found {} files matching '{}'", files.len(), query);
-                    Ok(files)
-                }
-                Err(e) => Err(format!("Search failed: {}", e)),
-            }
+    match crate::db::search_files(&pool.reader(), &query) {
+        Ok(files) => {
+            println!("🔎 Found {} files matching '{}'", files.len(), query);
+            Ok(files)
         }
-        Err(e) => Err(format!("Failed to connect to database: {}", e)),
+        Err(e) => Err(format!("Search failed: {}", e)),
     }
 }
 
@@ -585,7 +362,7 @@ pub async fn search_files(app_handle: tauri::AppHandle, query: String) -> Result
 // HELPER FUNCTIONS
 // ============================================================================
 
-fn system_time_to_unix(time: std::time::SystemTime) -> i64 {
+pub(crate) fn system_time_to_unix(time: std::time::SystemTime) -> i64 {
     // Convert SystemTime to Unix timestamp (seconds since 1970)
     time.duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
@@ -0,0 +1,637 @@
+// ============================================================================
+// CONTENT MODULE - Text inside files, for content search
+// ============================================================================
+//
+// files_fts knows names and paths. To find "that note that mentions the
+// Lisbon trip" we also need what's INSIDE the files. This module reads the
+// text out of the formats people actually search in:
+//
+//   text      .txt .md .rs .py .json ...   read as UTF-8 (binary files skipped)
+//   html      .html .htm                   tags, <script> and <style> stripped
+//   office    .docx .xlsx .pptx            zip files full of XML: keep the <t> text
+//   pdf       .pdf                         the text layer (scans have none)
+//
+// The text goes into contents_fts (rowid = files.id), which search_files
+// queries alongside files_fts.
+//
+// Like hashing.rs, it all runs on one background thread, woken after scans
+// and watcher batches. Files have a size limit per kind, and the text kept
+// per file is capped, so one giant log file can't bloat the database.
+// content_indexed_at is cleared when a file changes (see upsert_files), so
+// the next pass reads it again.
+//
+// ============================================================================
+
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::commands::system_time_to_unix;
+use crate::db::{DbPool, HashCandidate};
+
+/// Largest plain text / HTML file we read
+const MAX_TEXT_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Largest office document / PDF we read (they're mostly images and fonts)
+const MAX_DOCUMENT_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Text kept per file. Enough for any note; a novel gets its first chapters.
+const MAX_INDEXED_TEXT: usize = 1024 * 1024;
+
+/// Largest XML part we'll decompress from an office file (zip bombs)
+const MAX_XML_PART: u64 = 32 * 1024 * 1024;
+
+/// A NUL byte in the first few KB means "binary file, not text"
+const BINARY_SNIFF: usize = 8 * 1024;
+
+/// Files read (and saved in one transaction) per round
+const CONTENT_BATCH: usize = 50;
+
+/// After a wake-up, wait this long for more (same as hashing)
+const SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Text,
+    Html,
+    Office,
+    Pdf,
+}
+
+impl Kind {
+    /// Parsed by zip/lopdf, which can panic on a malformed file - and with
+    /// panic = "abort" (Cargo.toml) a panic takes the whole app down
+    fn needs_crash_guard(self) -> bool {
+        matches!(self, Kind::Office | Kind::Pdf)
+    }
+}
+
+/// Which extensions (file_type) each kind covers, and its size limit
+const KINDS: &[(Kind, u64, &[&str])] = &[
+    (Kind::Text, MAX_TEXT_FILE_SIZE, &[
+        "txt", "text", "md", "markdown", "rst", "org", "log", "csv", "tsv",
+        "json", "yaml", "yml", "toml", "ini", "xml", "tex",
+        "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "swift",
+        "c", "h", "cpp", "hpp", "cc", "cs", "rb", "php", "sh", "zsh", "bash",
+        "lua", "sql", "css", "scss", "vue", "svelte",
+    ]),
+    (Kind::Html, MAX_TEXT_FILE_SIZE, &["html", "htm"]),
+    (Kind::Office, MAX_DOCUMENT_SIZE, &["docx", "xlsx", "pptx"]),
+    (Kind::Pdf, MAX_DOCUMENT_SIZE, &["pdf"]),
+];
+
+// ============================================================================
+// EXTRACTION
+// ============================================================================
+
+/// Plain text, or None for a binary file
+fn text_from_bytes(bytes: &[u8]) -> Option<String> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF)];
+    if sniff.contains(&0) {
+        return None;
+    }
+    // Not every text file is valid UTF-8 (old Latin-1 notes): keep what we can
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Visible text of an HTML page. Not a real HTML parser - just enough to
+/// drop tags, comments, scripts and styles, and decode common entities.
+fn text_from_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len() / 2);
+    let lower = html.to_ascii_lowercase();  // Same byte offsets as `html`
+    let mut i = 0;
+
+    while i < html.len() {
+        let rest = &html[i..];
+        if rest.starts_with("<!--") {
+            i += lower[i..].find("-->").map(|end| end + 3).unwrap_or(rest.len());
+        } else if let Some(tag) = ["<script", "<style"].iter().find(|tag| lower[i..].starts_with(**tag)) {
+            // Skip to the matching closing tag
+            let close = format!("</{}", &tag[1..]);
+            i += lower[i..].find(&close).unwrap_or(rest.len());
+            i += lower[i..].find('>').map(|end| end + 1).unwrap_or(html.len() - i);
+        } else if rest.starts_with('<') {
+            i += rest.find('>').map(|end| end + 1).unwrap_or(rest.len());
+            text.push(' ');  // <p>one</p><p>two</p> shouldn't become "onetwo"
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            text.push_str(&decode_entities(&rest[..end]));
+            i += end;
+        }
+    }
+    text
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Text of one Office Open XML part: the contents of every <w:t>, <a:t> or
+/// <t> element, with a line break after each paragraph / row
+fn text_from_office_xml(xml: &[u8], text: &mut String) -> Result<(), quick_xml::Error> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    let mut buf = Vec::new();
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_text = true,
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" | b"si" | b"row" => text.push('\n'),
+                _ => {}
+            },
+            Event::Text(t) if in_text => {
+                let unescaped = t.unescape()?;
+                text.push_str(&unescaped);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(())
+}
+
+/// The XML parts holding a document's text, in reading order
+fn office_text_parts(names: &[String]) -> Vec<String> {
+    let mut slides: Vec<(u32, String)> = names
+        .iter()
+        .filter_map(|name| {
+            let number = name.strip_prefix("ppt/slides/slide")?.strip_suffix(".xml")?;
+            Some((number.parse().ok()?, name.clone()))
+        })
+        .collect();
+    // slide10.xml sorts before slide2.xml as a string
+    slides.sort();
+
+    let mut parts: Vec<String> = ["word/document.xml", "xl/sharedStrings.xml"]
+        .iter()
+        .filter(|part| names.iter().any(|name| name == *part))
+        .map(|part| part.to_string())
+        .collect();
+    parts.extend(slides.into_iter().map(|(_, name)| name));
+    parts
+}
+
+fn text_from_office(file: File) -> Result<String, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(file)?;
+    let names: Vec<String> = archive.file_names().map(|name| name.to_string()).collect();
+
+    let mut text = String::new();
+    for part in office_text_parts(&names) {
+        let mut xml = Vec::new();
+        // take() stops a tiny zip entry from inflating into gigabytes
+        archive.by_name(&part)?.take(MAX_XML_PART).read_to_end(&mut xml)?;
+        text_from_office_xml(&xml, &mut text)?;
+        if text.len() >= MAX_INDEXED_TEXT {
+            break;
+        }
+    }
+    Ok(text)
+}
+
+fn text_from_pdf(file: File) -> Result<String, Box<dyn std::error::Error>> {
+    let document = lopdf::Document::load_from(file)?;
+    if document.is_encrypted() {
+        return Ok(String::new());  // Needs a password - nothing we can read
+    }
+
+    let mut text = String::new();
+    for page in document.get_pages().into_keys() {
+        // One page with a broken font shouldn't lose the rest of the document
+        if let Ok(page_text) = document.extract_text(&[page]) {
+            text.push_str(&page_text);
+        }
+        if text.len() >= MAX_INDEXED_TEXT {
+            break;
+        }
+    }
+    Ok(text)
+}
+
+/// Cut `text` down to MAX_INDEXED_TEXT bytes (on a character boundary)
+fn truncate_text(mut text: String) -> String {
+    if text.len() > MAX_INDEXED_TEXT {
+        let mut end = MAX_INDEXED_TEXT;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+/// The searchable text of one file: Ok(None) if it changed since it was
+/// indexed (the watcher will update the row), Ok(Some(None)) if it has no
+/// text worth indexing - or it couldn't be parsed. Err only if the file
+/// couldn't be opened right now (gone, permissions): worth another try.
+fn extract_file(file: &HashCandidate, kind: Kind) -> io::Result<Option<Option<String>>> {
+    let path = Path::new(&file.path);
+    let metadata = std::fs::metadata(path)?;
+    let modified_at = metadata.modified().map(system_time_to_unix).unwrap_or(0);
+    if metadata.len() != file.size || modified_at != file.modified_at {
+        return Ok(None);
+    }
+
+    // Everything after opening is about the contents: a broken document
+    // stays broken, so retrying it would only parse it again and again
+    let opened = File::open(path)?;
+    let text = parse_file(opened, kind).unwrap_or_else(|e| {
+        eprintln!("⚠️  Failed to extract text from {}: {}", file.path, e);
+        None
+    });
+    Ok(Some(
+        text.filter(|text| !text.trim().is_empty())
+            .map(truncate_text),
+    ))
+}
+
+/// The text of an opened file, or None for a binary file
+fn parse_file(mut file: File, kind: Kind) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    Ok(match kind {
+        Kind::Text | Kind::Html => {
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            let text = text_from_bytes(&bytes);
+            match kind {
+                Kind::Html => text.map(|html| text_from_html(&html)),
+                _ => text,
+            }
+        }
+        Kind::Office => Some(text_from_office(file)?),
+        Kind::Pdf => Some(text_from_pdf(file)?),
+    })
+}
+
+/// Mark `file` as read before parsing it (see index_kind). False if it
+/// shouldn't be read now: its row changed, or the mark couldn't be saved.
+fn mark_attempted(pool: &DbPool, file: &HashCandidate) -> bool {
+    match crate::db::mark_content_attempted(&pool.writer(), file) {
+        Ok(marked) => marked,
+        Err(e) => {
+            eprintln!("⚠️  Failed to mark {} as read: {}", file.path, e);
+            false
+        }
+    }
+}
+
+/// Read every file of one kind that needs it. Returns how many were saved.
+fn index_kind(pool: &DbPool, kind: Kind, max_size: u64, file_types: &[&str]) -> usize {
+    let mut after_id = 0i64;
+    let mut saved = 0usize;
+
+    loop {
+        let candidates = match crate::db::files_needing_content(&pool.reader(), file_types, max_size, after_id, CONTENT_BATCH) {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                eprintln!("⚠️  Failed to list files to read: {}", e);
+                break;
+            }
+        };
+        let Some(last) = candidates.last() else {
+            break;  // Nothing (more) to do
+        };
+        after_id = last.id;
+
+        // Read the files WITHOUT holding the database writer.
+        // A file we can't parse is saved with no text, so it isn't retried
+        // on every pass (it's read again once it changes).
+        //
+        // Documents are marked as read BEFORE they're parsed: if the parser
+        // crashes the app, the file is skipped from then on instead of
+        // crashing every launch.
+        let contents: Vec<(HashCandidate, Option<String>)> = candidates
+            .into_iter()
+            .filter(|file| !kind.needs_crash_guard() || mark_attempted(pool, file))
+            .filter_map(|file| match extract_file(&file, kind) {
+                Ok(Some(text)) => Some((file, text)),
+                Ok(None) => None,
+                Err(e) => {
+                    // Gone or unreadable right now - try again next pass
+                    eprintln!("⚠️  Failed to read {}: {}", file.path, e);
+                    if kind.needs_crash_guard() {
+                        if let Err(e) = crate::db::clear_content_attempted(&pool.writer(), file.id) {
+                            eprintln!("⚠️  Failed to unmark {}: {}", file.path, e);
+                        }
+                    }
+                    None
+                }
+            })
+            .collect();
+
+        let mut conn = pool.writer();
+        let result = conn
+            .transaction()
+            .map_err(|e| e.into())
+            .and_then(|tx| {
+                let count = crate::db::save_file_contents(&tx, &contents)?;
+                tx.commit()?;
+                Ok::<_, Box<dyn std::error::Error>>(count)
+            });
+        match result {
+            Ok(count) => saved += count,
+            Err(e) => eprintln!("⚠️  Failed to save file contents: {}", e),
+        }
+    }
+
+    saved
+}
+
+// ============================================================================
+// BACKGROUND WORKER (Tauri managed state)
+// ============================================================================
+
+/// Payload of the "content-indexed" event
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentIndexSummary {
+    pub files_read: usize,
+}
+
+pub struct ContentQueue {
+    wake_tx: Mutex<mpsc::Sender<()>>,
+}
+
+impl ContentQueue {
+    /// Spawn the extraction thread (it does a first pass right away).
+    /// Call once from main.rs setup().
+    pub fn start(app_handle: AppHandle) -> Self {
+        let (wake_tx, wake_rx) = mpsc::channel::<()>();
+
+        std::thread::spawn(move || loop {
+            let pool = app_handle.state::<DbPool>();
+            let files_read: usize = KINDS
+                .iter()
+                .map(|(kind, max_size, file_types)| index_kind(&pool, *kind, *max_size, file_types))
+                .sum();
+            if files_read > 0 {
+                println!("📖 Indexed the text of {} files", files_read);
+                app_handle.emit("content-indexed", ContentIndexSummary { files_read }).ok();
+            }
+
+            // Sleep until something new was indexed
+            if wake_rx.recv().is_err() {
+                break;  // Sender dropped: the app is shutting down
+            }
+            std::thread::sleep(SETTLE_DELAY);
+            while wake_rx.try_recv().is_ok() {}
+        });
+
+        Self { wake_tx: Mutex::new(wake_tx) }
+    }
+
+    /// Files were added or changed: read whatever now needs it
+    pub fn wake(&self) {
+        if let Ok(tx) = self.wake_tx.lock() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentIndexStatus {
+    pub indexed: usize,  // Files whose text is searchable
+    pub pending: usize,  // Files still waiting to be read
+}
+
+/// How far content indexing has got (for a "still indexing..." hint)
+#[tauri::command]
+pub async fn content_index_status(
+    pool: State<'_, DbPool>,
+    queue: State<'_, ContentQueue>,
+) -> Result<ContentIndexStatus, String> {
+    let conn = pool.reader();
+    let indexed = crate::db::count_indexed_contents(&conn)
+        .map_err(|e| format!("Failed to count indexed files: {}", e))?;
+
+    let mut pending = 0usize;
+    for (_, max_size, file_types) in KINDS {
+        pending += crate::db::count_files_needing_content(&conn, file_types, *max_size)
+            .map_err(|e| format!("Failed to count unread files: {}", e))?;
+    }
+    if pending > 0 {
+        queue.wake();
+    }
+
+    Ok(ContentIndexStatus { indexed, pending })
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. Read::take:
+//    - Wraps a reader so it stops after n bytes - a cheap guard against
+//      zip entries that decompress to far more than the file size
+//
+// 2. Nested Option:
+//    - Option<Option<String>> says two different things: outer None is
+//      "skip this file for now", inner None is "read it, found no text"
+//
+// 3. Two kinds of failure, two return types:
+//    - extract_file returns io::Result (the file couldn't be opened: retry
+//      later), parse_file a Box<dyn Error> (a bad document: give up) - so
+//      the caller can't mix them up
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::io::Write;
+
+    /// A .docx holding `parts` (name, contents), deflated like the real thing
+    fn docx(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (name, contents) in parts {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// The candidate the indexer would hand over for `path`, as it is now
+    fn candidate(path: &str) -> HashCandidate {
+        let metadata = std::fs::metadata(path).unwrap();
+        HashCandidate {
+            id: 1,
+            path: path.to_string(),
+            size: metadata.len(),
+            modified_at: metadata.modified().map(system_time_to_unix).unwrap(),
+        }
+    }
+
+    #[test]
+    fn html_keeps_only_the_visible_text() {
+        let html = "<html><head><title>Trip</title>\
+                    <STYLE>body { color: red }</STYLE>\
+                    <script type=\"x\">if (a < b) alert('hidden')</script></head>\
+                    <body><!-- <p>commented out</p> --><p>Lisbon&nbsp;&amp;&nbsp;Porto</p>\
+                    <p>&lt;3 &quot;tram&quot; &#39;28&apos;</p></body></html>";
+        let text = text_from_html(html);
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        assert_eq!(words, ["Trip", "Lisbon", "&", "Porto", "<3", "\"tram\"", "'28'"]);
+    }
+
+    #[test]
+    fn html_entities_are_decoded_once() {
+        // "&amp;lt;" is the text "&lt;", not "<"
+        assert_eq!(text_from_html("a &amp;lt; b"), "a &lt; b");
+        assert_eq!(text_from_html("<p>one</p><p>two</p>").split_whitespace().count(), 2);
+        // An unclosed comment or script hides the rest, rather than panicking
+        assert_eq!(text_from_html("shown<!-- never closed").trim(), "shown");
+        assert_eq!(text_from_html("shown<script>never closed").trim(), "shown");
+    }
+
+    #[test]
+    fn office_xml_keeps_text_elements_with_paragraph_breaks() {
+        let xml = br#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:r><w:t>Hello</w:t></w:r><w:r><w:t xml:space="preserve"> world</w:t></w:r></w:p>
+            <w:p><w:r><w:instrText>not text</w:instrText><w:t>Fish &amp; chips</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+        let mut text = String::new();
+        text_from_office_xml(xml, &mut text).unwrap();
+
+        assert_eq!(text, "Hello world\nFish & chips\n");
+    }
+
+    #[test]
+    fn office_parts_come_in_reading_order() {
+        let names: Vec<String> = [
+            "ppt/slides/slide10.xml",
+            "ppt/slides/_rels/slide1.xml.rels",
+            "ppt/slides/slide2.xml",
+            "xl/sharedStrings.xml",
+            "ppt/slides/slide1.xml",
+            "word/document.xml",
+            "word/styles.xml",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+
+        assert_eq!(office_text_parts(&names), [
+            "word/document.xml",
+            "xl/sharedStrings.xml",
+            "ppt/slides/slide1.xml",
+            "ppt/slides/slide2.xml",
+            "ppt/slides/slide10.xml",
+        ]);
+    }
+
+    #[test]
+    fn binary_files_are_sniffed_from_the_start() {
+        assert_eq!(text_from_bytes(b"plain text").as_deref(), Some("plain text"));
+        assert_eq!(text_from_bytes(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), None);
+        // Latin-1 isn't UTF-8, but it's still text
+        assert_eq!(text_from_bytes(b"caf\xe9").as_deref(), Some("caf\u{fffd}"));
+
+        // A NUL past the sniffed prefix doesn't make a file binary
+        let mut late_nul = vec![b'a'; BINARY_SNIFF];
+        late_nul.push(0);
+        assert!(text_from_bytes(&late_nul).is_some());
+        late_nul[BINARY_SNIFF - 1] = 0;
+        assert_eq!(text_from_bytes(&late_nul), None);
+    }
+
+    #[test]
+    fn truncation_stops_on_a_character_boundary() {
+        let short = "short".to_string();
+        assert_eq!(truncate_text(short.clone()), short);
+
+        // 'é' is two bytes: the limit lands in the middle of the last one
+        let text = format!("{}{}", "a".repeat(MAX_INDEXED_TEXT - 1), "é".repeat(4));
+        let truncated = truncate_text(text);
+        assert_eq!(truncated.len(), MAX_INDEXED_TEXT - 1);
+        assert!(truncated.ends_with('a'));
+
+        let exact = "b".repeat(MAX_INDEXED_TEXT);
+        assert_eq!(truncate_text(exact.clone()), exact);
+    }
+
+    #[test]
+    fn documents_are_extracted_from_disk() {
+        let dir = TempDir::new("content-extract");
+        let report = dir.write("report.docx", &docx(&[(
+            "word/document.xml",
+            "<w:document><w:p><w:t>Quarterly numbers</w:t></w:p></w:document>",
+        )]));
+        let page = dir.write("page.html", b"<h1>Hi &amp; bye</h1>");
+        let image = dir.write("image.txt", b"GIF89a\0\0");
+
+        assert_eq!(
+            extract_file(&candidate(&report), Kind::Office).unwrap(),
+            Some(Some("Quarterly numbers\n".to_string()))
+        );
+        assert_eq!(
+            extract_file(&candidate(&page), Kind::Html).unwrap(),
+            Some(Some(" Hi & bye ".to_string()))
+        );
+        assert_eq!(extract_file(&candidate(&image), Kind::Text).unwrap(), Some(None));
+
+        // Changed since it was indexed: leave it to the watcher
+        let mut stale = candidate(&page);
+        stale.size += 1;
+        assert_eq!(extract_file(&stale, Kind::Html).unwrap(), None);
+    }
+
+    #[test]
+    fn broken_documents_are_saved_as_no_text_not_retried() {
+        let dir = TempDir::new("content-broken");
+        let mut bytes = docx(&[("word/document.xml", &"<w:t>lorem ipsum dolor</w:t>".repeat(500))]);
+        // Scribble over the middle of the deflated stream (after the
+        // 30-byte local header and the 17-byte name)
+        let data_start = 30 + "word/document.xml".len();
+        for byte in &mut bytes[data_start + 20..data_start + 60] {
+            *byte = 0xff;
+        }
+        let broken = dir.write("broken.docx", &bytes);
+        let not_a_zip = dir.write("not-a-zip.docx", b"just text");
+        let not_a_pdf = dir.write("not-a-pdf.pdf", b"%PDF-1.4 and then nothing");
+
+        // The read fails with an io::Error from inside the zip reader - it
+        // must still count as a bad document, not a file to try again
+        let error = parse_file(File::open(&broken).unwrap(), Kind::Office).unwrap_err();
+        assert!(error.downcast_ref::<io::Error>().is_some());
+        assert_eq!(extract_file(&candidate(&broken), Kind::Office).unwrap(), Some(None));
+        assert_eq!(extract_file(&candidate(&not_a_zip), Kind::Office).unwrap(), Some(None));
+        assert_eq!(extract_file(&candidate(&not_a_pdf), Kind::Pdf).unwrap(), Some(None));
+    }
+
+    #[test]
+    fn missing_files_are_retried() {
+        let dir = TempDir::new("content-missing");
+        let path = dir.write("gone.txt", b"soon gone");
+        let file = candidate(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let error = extract_file(&file, Kind::Text).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -13,15 +13,28 @@
 //
 // ============================================================================
 
-use rusqlite::params;
-use serde::Serialize;
-use tauri::{AppHandle, Manager};
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
 use chrono::{DateTime, Datelike, Utc};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
 // Re-export our FileInfo type so other modules can use it
-pub use crate::commands::FileInfo;
+pub use crate::commands::{FileInfo, FileMetadata};
+use crate::scan_policy::{ScanPolicy, FOLDER_FILE_TYPE};
+use crate::scanner::SourceScanStats;
+use crate::highlight::SearchResult;
+use crate::ranking::SearchRanking;
+use crate::search_query::SearchQuery;
+use crate::sources::ScanSource;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ResurfacedFile {
@@ -34,7 +47,9 @@ pub struct ResurfacedFile {
 // DATABASE INITIALIZATION
 // ============================================================================
 
-pub async fn init_database(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+/// Open aurora.db, run migrations, and build the shared connection pool.
+/// Called once from main.rs setup(); the pool is then kept in Tauri managed state.
+pub fn init_database(app_handle: &AppHandle) -> Result<DbPool, Box<dyn std::error::Error>> {
     println!("📊 Initializing Aurora SQLite database...");
 
     // Get the app's data directory where we'll store the database
@@ -49,136 +64,135 @@ pub async fn init_database(app_handle: &AppHandle) -> Result<(), Box<dyn std::er
 
     // Database file path
     let db_path = app_dir.join("aurora.db");
-    let _db_url = format!("sqlite:{}", db_path.display());
 
     println!("📁 Database location: {}", db_path.display());
 
-    // Create the database connection using rusqlite directly
-    // (simpler than dealing with tauri-plugin-sql for now)
-    let conn = rusqlite::Connection::open(&db_path)?;
+    let pool = open_database(&db_path)?;
 
-    // Execute schema creation
-    create_schema(&conn)?;
+    println!("✅ Database initialized successfully!");
 
-    // Insert default data
-    insert_defaults(&conn)?;
+    Ok(pool)
+}
 
-    println!("✅ Database initialized successfully!");
+/// Open (or create) the database at `db_path`, bring its schema up to date
+/// and build the pool. init_database minus the app data folder, so tests
+/// can open a scratch database.
+pub(crate) fn open_database(db_path: &Path) -> Result<DbPool, Box<dyn std::error::Error>> {
+    // Open the writer connection first - migrations need write access,
+    // and WAL mode must be switched on before any reader attaches.
+    let mut writer = open_writer(db_path)?;
+
+    // Bring the schema up to date (refuses databases from a newer Aurora)
+    let applied = crate::migrations::run_migrations(&mut writer)?;
+    println!(
+        "✅ Database schema at version {} ({} migration(s) applied)",
+        crate::migrations::latest_version(),
+        applied
+    );
 
-    Ok(())
+    // Insert default data
+    insert_defaults(&writer)?;
+
+    Ok(DbPool::new(db_path, writer, READER_COUNT)?)
 }
 
 // ============================================================================
-// SCHEMA CREATION
+// CONNECTION POOL
+// ============================================================================
+//
+// WHY A POOL?
+// Opening a new SQLite connection for every command (and every watcher
+// event!) is slow, and lots of short-lived connections end up fighting over
+// the database lock with "database is locked" errors.
+//
+// Instead we open connections ONCE at startup and share them:
+// - One WRITER connection (SQLite only allows one writer at a time anyway)
+// - A few READ-ONLY connections for UI queries
+//
+// With WAL (Write-Ahead Logging) mode, readers don't block the writer and
+// the writer doesn't block readers - so a burst of watcher events can't
+// freeze a search the user is typing.
+//
 // ============================================================================
 
-fn create_schema(conn: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
-    // LEARNING NOTE:
-    // execute_batch() runs multiple SQL statements at once
-    // It's perfect for schema creation
+/// How many read-only connections to keep open
+const READER_COUNT: usize = 4;
+
+/// How long a connection waits for a lock before giving up with SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many prepared statements each connection keeps compiled
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Shared SQLite connections, stored in Tauri managed state
+pub struct DbPool {
+    writer: Mutex<Connection>,
+    readers: Vec<Mutex<Connection>>,
+    next_reader: AtomicUsize,
+}
+
+impl DbPool {
+    fn new(db_path: &Path, writer: Connection, reader_count: usize) -> Result<Self, rusqlite::Error> {
+        let readers = (0..reader_count.max(1))
+            .map(|_| open_reader(db_path).map(Mutex::new))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            writer: Mutex::new(writer),
+            readers,
+            next_reader: AtomicUsize::new(0),
+        })
+    }
+
+    /// Borrow the single writer connection (blocks until it's free)
+    pub fn writer(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock "poisons" the Mutex.
+        // The connection itself is still fine, so we just keep using it.
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
 
+    /// Borrow a read-only connection
+    pub fn reader(&self) -> MutexGuard<'_, Connection> {
+        // Start at a different reader each time (round-robin) and grab the
+        // first idle one. If they're all busy, wait on the starting reader.
+        let start = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+        for offset in 0..self.readers.len() {
+            let idx = (start + offset) % self.readers.len();
+            if let Ok(guard) = self.readers[idx].try_lock() {
+                return guard;
+            }
+        }
+
+        self.readers[start].lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn open_writer(db_path: &Path) -> Result<Connection, rusqlite::Error> {
+    let conn = Connection::open(db_path)?;
     conn.execute_batch(
         "
-        -- Files table: stores all indexed files
-        CREATE TABLE IF NOT EXISTS files (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            path TEXT UNIQUE NOT NULL,
-            name TEXT NOT NULL,
-            file_type TEXT,
-            size INTEGER,
-            created_at INTEGER,
-            modified_at INTEGER,
-            last_opened_at INTEGER,
-            open_count INTEGER DEFAULT 0,
-            thumbnail_path TEXT,
-
-            -- Spatial memory: tile positions
-            tile_x REAL,
-            tile_y REAL,
-            tile_cluster TEXT,
-
-            -- Timestamps
-            indexed_at INTEGER DEFAULT (strftime('%s', 'now'))
-        );
-
-        -- Finder tags table
-        CREATE TABLE IF NOT EXISTS finder_tags (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT UNIQUE NOT NULL,
-            color INTEGER DEFAULT 0
-        );
-
-        -- File-tag associations (many-to-many)
-        CREATE TABLE IF NOT EXISTS file_tags (
-            file_id INTEGER,
-            tag_id INTEGER,
-            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE,
-            FOREIGN KEY (tag_id) REFERENCES finder_tags(id) ON DELETE CASCADE,
-            PRIMARY KEY (file_id, tag_id)
-        );
-
-        -- Emotional metadata
-        CREATE TABLE IF NOT EXISTS file_metadata (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            file_id INTEGER UNIQUE,
-            mood TEXT,
-            season TEXT,
-            vibe_color TEXT,
-            location TEXT,
-            energy_level TEXT,
-            notes TEXT,
-            created_at INTEGER DEFAULT (strftime('%s', 'now')),
-            updated_at INTEGER DEFAULT (strftime('%s', 'now')),
-            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
-        );
-
-        -- Clusters for visual grouping
-        CREATE TABLE IF NOT EXISTS clusters (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT UNIQUE NOT NULL,
-            color TEXT,
-            sort_order INTEGER DEFAULT 0
-        );
-
-        -- Full-text search index (FTS5)
-        -- This makes search SUPER fast!
-        CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(
-            file_id UNINDEXED,
-            path,
-            name,
-            content=''
-        );
-
-        -- Indexes for faster queries
-        CREATE INDEX IF NOT EXISTS idx_files_path ON files(path);
-        CREATE INDEX IF NOT EXISTS idx_files_modified ON files(modified_at DESC);
-        CREATE INDEX IF NOT EXISTS idx_files_cluster ON files(tile_cluster);
-        CREATE INDEX IF NOT EXISTS idx_metadata_file ON file_metadata(file_id);
-
-        -- Triggers to keep FTS5 in sync
-        -- These automatically update the search index when files change
-
-        CREATE TRIGGER IF NOT EXISTS files_ai AFTER INSERT ON files
-        BEGIN
-            INSERT INTO files_fts(file_id, path, name)
-            VALUES (new.id, new.path, new.name);
-        END;
-
-        CREATE TRIGGER IF NOT EXISTS files_ad AFTER DELETE ON files
-        BEGIN
-            DELETE FROM files_fts WHERE file_id = old.id;
-        END;
-
-        CREATE TRIGGER IF NOT EXISTS files_au AFTER UPDATE ON files
-        BEGIN
-            UPDATE files_fts
-            SET path = new.path, name = new.name
-            WHERE file_id = old.id;
-        END;
+        PRAGMA journal_mode = WAL;
+        PRAGMA synchronous = NORMAL;
         ",
     )?;
+    configure_connection(&conn)?;
+    Ok(conn)
+}
+
+fn open_reader(db_path: &Path) -> Result<Connection, rusqlite::Error> {
+    let conn = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    configure_connection(&conn)?;
+    Ok(conn)
+}
 
-    println!("✅ Database schema created");
+/// Settings every pooled connection shares
+fn configure_connection(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
     Ok(())
 }
 
@@ -208,20 +222,6 @@ fn insert_defaults(conn: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
 // DATABASE OPERATIONS
 // ============================================================================
 
-/// Get database connection
-/// This is a helper to get a connection from the app handle
-pub fn get_connection(app_handle: &AppHandle) -> Result<rusqlite::Connection, Box<dyn std::error::Error>> {
-    let app_dir = app_handle
-        .path()
-        .app_data_dir()
-        .expect("Failed to get app data directory");
-
-    let db_path = app_dir.join("aurora.db");
-    let conn = rusqlite::Connection::open(&db_path)?;
-
-    Ok(conn)
-}
-
 /// Insert or update a file in the database
 /// "Upsert" = update if exists, insert if not
 #[allow(dead_code)]
@@ -258,6 +258,8 @@ pub fn upsert_file(
         |row| row.get(0),
     )?;
 
+    sync_file_tags(conn, file_id, &file.finder_tags, &file.finder_colors)?;
+
     Ok(file_id)
 }
 
@@ -270,106 +272,415 @@ pub fn upsert_files(
         return Ok(0);
     }
 
-    let mut saved_count = 0usize;
     let tx = conn.transaction()?;
+    let saved_count = upsert_files_in_tx(&tx, files)?;
+    tx.commit()?;
+    Ok(saved_count)
+}
+
+/// upsert_files inside a transaction the caller already started (and
+/// commits). A file that fails is logged and skipped.
+pub fn upsert_files_in_tx(
+    tx: &rusqlite::Transaction,
+    files: &[FileInfo],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut saved_count = 0usize;
     {
-        let mut stmt = tx.prepare(
-            "INSERT INTO files (path, name, file_type, size, created_at, modified_at, open_count)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO files (path, name, file_type, size, created_at, modified_at, open_count, device, inode, changed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?8, ?9)
              ON CONFLICT(path) DO UPDATE SET
                 name = excluded.name,
                 file_type = excluded.file_type,
+                -- Different content: the old hashes no longer apply
+                partial_hash = CASE WHEN size IS excluded.size AND modified_at IS excluded.modified_at
+                                    THEN partial_hash END,
+                content_hash = CASE WHEN size IS excluded.size AND modified_at IS excluded.modified_at
+                                    THEN content_hash END,
+                -- ...and its text has to be read again
+                content_indexed_at = CASE WHEN size IS excluded.size AND modified_at IS excluded.modified_at
+                                          THEN content_indexed_at END,
+                -- ...and its thumbnail redrawn (enqueue_missing picks it up)
+                thumbnail_path = CASE WHEN size IS excluded.size AND modified_at IS excluded.modified_at
+                                      THEN thumbnail_path END,
                 size = excluded.size,
-                modified_at = excluded.modified_at",
+                modified_at = excluded.modified_at,
+                device = excluded.device,
+                inode = excluded.inode,
+                changed_at = excluded.changed_at",
         )?;
 
+        let mut id_stmt = tx.prepare_cached("SELECT id FROM files WHERE path = ?1")?;
+
         for file in files {
-            match stmt.execute(params![
-                &file.path,
-                &file.name,
-                &file.file_type,
-                file.size as i64,
-                file.created_at,
-                file.modified_at,
-            ]) {
+            let result = stmt
+                .execute(params![
+                    &file.path,
+                    &file.name,
+                    &file.file_type,
+                    file.size as i64,
+                    file.created_at,
+                    file.modified_at,
+                    file.inode.map(|(device, _)| device as i64),
+                    file.inode.map(|(_, inode)| inode as i64),
+                    file.changed_at,
+                ])
+                .and_then(|_| id_stmt.query_row(params![&file.path], |row| row.get::<_, i64>(0)))
+                .and_then(|file_id| sync_file_tags(tx, file_id, &file.finder_tags, &file.finder_colors));
+
+            match result {
                 Ok(_) => saved_count += 1,
                 Err(e) => eprintln!("⚠️  Failed to upsert {}: {}", file.path, e),
             }
         }
     }
 
-    tx.commit()?;
     Ok(saved_count)
 }
 
+/// Make the file_tags rows for one file match `names`/`colors`.
+/// Tags are shared between files (finder_tags holds one row per tag name),
+/// and a tag's color is whatever it was most recently seen with.
+fn sync_file_tags(
+    conn: &rusqlite::Connection,
+    file_id: i64,
+    names: &[String],
+    colors: &[i32],
+) -> Result<(), rusqlite::Error> {
+    conn.prepare_cached("DELETE FROM file_tags WHERE file_id = ?1")?
+        .execute(params![file_id])?;
+
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    let mut tag_stmt = conn.prepare_cached(
+        "INSERT INTO finder_tags (name, color) VALUES (?1, ?2)
+         ON CONFLICT(name) DO UPDATE SET color = excluded.color",
+    )?;
+    let mut link_stmt = conn.prepare_cached(
+        "INSERT OR IGNORE INTO file_tags (file_id, tag_id)
+         SELECT ?1, id FROM finder_tags WHERE name = ?2",
+    )?;
+
+    for (idx, name) in names.iter().enumerate() {
+        // colors lines up with names (same index = same tag)
+        let color = colors.get(idx).copied().unwrap_or(0);
+        tag_stmt.execute(params![name, color])?;
+        link_stmt.execute(params![file_id, name])?;
+    }
+
+    Ok(())
+}
+
+/// Replace the stored tags of the file at `path`.
+/// Returns false if the file isn't indexed (there's no row to tag).
+pub fn set_file_tags(
+    conn: &rusqlite::Connection,
+    path: &str,
+    names: &[String],
+    colors: &[i32],
+) -> Result<bool, rusqlite::Error> {
+    let file_id: Option<i64> = conn
+        .prepare_cached("SELECT id FROM files WHERE path = ?1")?
+        .query_row(params![path], |row| row.get(0))
+        .optional()?;
+
+    match file_id {
+        Some(file_id) => {
+            sync_file_tags(conn, file_id, names, colors)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Fill in finder_tags/finder_colors for files loaded from the database
+fn attach_tags(conn: &rusqlite::Connection, files: &mut [FileInfo]) -> Result<(), Box<dyn std::error::Error>> {
+    let ids: Vec<i64> = files.iter().filter_map(|f| f.id).collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT ft.file_id, t.name, t.color
+         FROM file_tags ft
+         JOIN finder_tags t ON t.id = ft.tag_id
+         WHERE ft.file_id IN (SELECT value FROM json_each(?1))
+         ORDER BY t.name",
+    )?;
+
+    // Group the rows by file id: { file_id -> [(name, color), ...] }
+    let mut by_file: HashMap<i64, Vec<(String, i32)>> = HashMap::new();
+    let rows = stmt.query_map([serde_json::to_string(&ids)?], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<i32>>(2)?))
+    })?;
+    for row in rows {
+        let (file_id, name, color) = row?;
+        by_file.entry(file_id).or_default().push((name, color.unwrap_or(0)));
+    }
+
+    for file in files.iter_mut() {
+        if let Some(tags) = file.id.and_then(|id| by_file.remove(&id)) {
+            (file.finder_tags, file.finder_colors) = tags.into_iter().unzip();
+        }
+    }
+
+    Ok(())
+}
+
+// ----------------------------------------------------------------------------
+// Reading FileInfo rows
+// ----------------------------------------------------------------------------
+//
+// Every query that returns FileInfo selects the same columns in the same
+// order, so one row-mapping function (file_from_row) can serve them all.
+// The LEFT JOIN brings along emotional metadata when the user has saved some
+// (LEFT JOIN = "keep the file even if there's no matching metadata row").
+
+const FILE_SELECT: &str = "
+    SELECT f.id, f.path, f.name, f.file_type, f.size,
+           f.created_at, f.modified_at, f.last_opened_at, f.thumbnail_path,
+           m.file_id, m.mood, m.season, m.vibe_color, m.location, m.energy_level, m.notes
+    FROM files f
+    LEFT JOIN file_metadata m ON m.file_id = f.id";
+
+/// Map one row of FILE_SELECT into a FileInfo
+fn file_from_row(row: &rusqlite::Row) -> Result<FileInfo, rusqlite::Error> {
+    // m.file_id is NULL when the LEFT JOIN found no metadata row
+    let metadata = match row.get::<_, Option<i64>>(9)? {
+        Some(file_id) => Some(FileMetadata {
+            file_id,
+            mood: row.get(10)?,
+            season: row.get(11)?,
+            vibe_color: row.get(12)?,
+            location: row.get(13)?,
+            energy_level: row.get(14)?,
+            notes: row.get(15)?,
+        }),
+        None => None,
+    };
+
+    Ok(FileInfo {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        name: row.get(2)?,
+        file_type: row.get(3)?,
+        size: row.get::<_, i64>(4)? as u64,
+        created_at: row.get(5)?,
+        modified_at: row.get(6)?,
+        last_opened_at: row.get(7)?,
+        thumbnail_path: row.get(8)?,
+        finder_tags: Vec::new(),  // Filled in afterwards by attach_tags()
+        finder_colors: Vec::new(),
+        metadata,
+        inode: None,  // Only needed when writing
+        changed_at: None,
+    })
+}
+
 /// Get all files from the database
 pub fn get_all_files(conn: &rusqlite::Connection) -> Result<Vec<FileInfo>, Box<dyn std::error::Error>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, path, name, file_type, size, created_at, modified_at, last_opened_at, thumbnail_path
-         FROM files
-         ORDER BY modified_at DESC
-         LIMIT 1000"
-    )?;
+    let mut stmt = conn.prepare_cached(&format!(
+        "{}
+         ORDER BY f.modified_at DESC
+         LIMIT 1000",
+        FILE_SELECT
+    ))?;
+
+    let mut files = stmt
+        .query_map([], file_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    attach_tags(conn, &mut files)?;
 
-    let files = stmt.query_map([], |row| {
-        Ok(FileInfo {
-            id: row.get(0)?,
-            path: row.get(1)?,
-            name: row.get(2)?,
-            file_type: row.get(3)?,
-            size: row.get::<_, i64>(4)? as u64,
-            created_at: row.get(5)?,
-            modified_at: row.get(6)?,
-            last_opened_at: row.get(7)?,
-            thumbnail_path: row.get(8)?,
-            finder_tags: Vec::new(),  // TODO: Join with tags table
-            finder_colors: Vec::new(),
-        })
-    })?
-    .collect::<Result<Vec<_>, _>>()?;
+    Ok(files)
+}
+
+/// The rows for these paths (paths that aren't indexed are left out)
+pub fn get_files_by_paths(
+    conn: &rusqlite::Connection,
+    paths: &[String],
+) -> Result<Vec<FileInfo>, Box<dyn std::error::Error>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare_cached(&format!(
+        "{}
+         WHERE f.path IN (SELECT value FROM json_each(?1))",
+        FILE_SELECT
+    ))?;
+
+    let mut files = stmt
+        .query_map([serde_json::to_string(paths)?], file_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    attach_tags(conn, &mut files)?;
 
     Ok(files)
 }
 
-/// Search files using FTS5
-pub fn search_files(
+/// The rows for these ids, in the same order (missing ids are left out)
+pub fn get_files_by_ids(
     conn: &rusqlite::Connection,
-    query: &str,
+    ids: &[i64],
 ) -> Result<Vec<FileInfo>, Box<dyn std::error::Error>> {
-    let mut stmt = conn.prepare(
-        "SELECT f.id, f.path, f.name, f.file_type, f.size,
-                f.created_at, f.modified_at, f.last_opened_at, f.thumbnail_path
-         FROM files f
-         JOIN files_fts fts ON f.id = fts.file_id
-         WHERE files_fts MATCH ?1
-         ORDER BY rank
-         LIMIT 50"
-    )?;
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare_cached(&format!(
+        "{}
+         WHERE f.id IN (SELECT value FROM json_each(?1))",
+        FILE_SELECT
+    ))?;
+
+    let mut files = stmt
+        .query_map([serde_json::to_string(ids)?], file_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    attach_tags(conn, &mut files)?;
 
-    let files = stmt.query_map([query], |row| {
-        Ok(FileInfo {
-            id: row.get(0)?,
-            path: row.get(1)?,
-            name: row.get(2)?,
-            file_type: row.get(3)?,
-            size: row.get::<_, i64>(4)? as u64,
-            created_at: row.get(5)?,
-            modified_at: row.get(6)?,
-            last_opened_at: row.get(7)?,
-            thumbnail_path: row.get(8)?,
-            finder_tags: Vec::new(),
-            finder_colors: Vec::new(),
-        })
-    })?
-    .collect::<Result<Vec<_>, _>>()?;
+    // IN (...) returns rows in whatever order SQLite likes
+    let position: HashMap<i64, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    files.sort_by_key(|f| f.id.and_then(|id| position.get(&id).copied()).unwrap_or(usize::MAX));
 
     Ok(files)
 }
 
-/// Delete a file from the database
-pub fn delete_file(conn: &rusqlite::Connection, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    conn.execute("DELETE FROM files WHERE path = ?1", [path])?;
-    Ok(())
+/// Delete many files from the database in a single transaction.
+/// Returns how many rows were actually removed.
+pub fn delete_files(
+    conn: &mut rusqlite::Connection,
+    paths: &[String],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if paths.is_empty() {
+        return Ok(0);
+    }
+
+    let tx = conn.transaction()?;
+    let removed = delete_files_in_tx(&tx, paths)?;
+    tx.commit()?;
+    Ok(removed)
+}
+
+/// delete_files inside a transaction the caller already started
+pub fn delete_files_in_tx(
+    tx: &rusqlite::Transaction,
+    paths: &[String],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut removed = 0usize;
+    let mut stmt = tx.prepare_cached("DELETE FROM files WHERE path = ?1")?;
+    for path in paths {
+        removed += stmt.execute([path])?;
+    }
+    Ok(removed)
+}
+
+/// The indexed path of the file with this (device, inode), if any
+pub fn find_path_by_inode(
+    conn: &rusqlite::Connection,
+    inode: (u64, u64),
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let path = conn
+        .prepare_cached("SELECT path FROM files WHERE inode = ?1 AND device = ?2 LIMIT 1")?
+        .query_row(params![inode.1 as i64, inode.0 as i64], |row| row.get(0))
+        .optional()?;
+    Ok(path)
+}
+
+/// A file or folder moved from `from` to `to`: update the existing rows'
+/// paths instead of deleting and re-adding them, so the row ID - and with
+/// it open counts, tile positions, tags and metadata - survives.
+/// Everything indexed inside a moved folder moves along.
+/// Returns the (old, new) path of every moved row (empty = we never
+/// indexed `from`).
+///
+/// Runs inside the caller's transaction (the watcher writes a whole batch
+/// of events in one).
+pub fn rename_path(
+    tx: &mut rusqlite::Transaction,
+    from: &str,
+    to: &str,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let name = Path::new(to)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| to.to_string());
+    let from_prefix = format!("{}/", from);
+    let to_prefix = format!("{}/", to);
+
+    // RUST TRANSACTIONS:
+    // A savepoint is a transaction inside the transaction - if a statement
+    // below fails, dropping `sp` undoes just this move
+    let sp = tx.savepoint()?;
+    let moved: Vec<(String, String)> = {
+        let mut stmt = sp.prepare_cached(
+            "SELECT path FROM files WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2",
+        )?;
+        let old_paths = stmt
+            .query_map(params![from, from_prefix], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        old_paths
+            .into_iter()
+            .map(|old| {
+                let new = format!("{}{}", to, &old[from.len()..]);
+                (old, new)
+            })
+            .collect()
+    };
+    if moved.is_empty() {
+        // Nothing to move - and an editor saving through a temp file
+        // must not wipe the real file's row at `to`
+        return Ok(moved);
+    }
+
+    // Whatever was indexed at the destination was overwritten by the move
+    sp.execute(
+        "DELETE FROM files WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2",
+        params![to, to_prefix],
+    )?;
+    sp.execute("UPDATE files SET path = ?2, name = ?3 WHERE path = ?1", params![from, to, name])?;
+    sp.execute(
+        "UPDATE files SET path = ?2 || substr(path, length(?1) + 1)
+         WHERE substr(path, 1, length(?1)) = ?1",
+        params![from_prefix, to_prefix],
+    )?;
+    sp.commit()?;
+    Ok(moved)
+}
+
+/// What a scan remembers about a file: (modified_at, size, changed_at)
+pub type FileStamp = (i64, u64, Option<i64>);
+
+/// modified_at, size and changed_at for every indexed file inside `root`
+/// (at any depth).
+/// Incremental scans compare these with the disk to skip unchanged files.
+pub fn get_file_stamps_under(
+    conn: &rusqlite::Connection,
+    root: &str,
+) -> Result<HashMap<String, FileStamp>, Box<dyn std::error::Error>> {
+    // "/Users/me/Docs" must not match "/Users/me/Docs-old/..." - compare
+    // against the folder path WITH its trailing slash
+    let prefix = if root.ends_with('/') {
+        root.to_string()
+    } else {
+        format!("{}/", root)
+    };
+
+    // LEARNING NOTE:
+    // substr() instead of LIKE so '%' and '_' in folder names aren't wildcards
+    let mut stmt = conn.prepare_cached(
+        "SELECT path, COALESCE(modified_at, 0), COALESCE(size, 0), changed_at
+         FROM files
+         WHERE substr(path, 1, length(?1)) = ?1",
+    )?;
+    let stamps = stmt
+        .query_map([&prefix], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                (row.get::<_, i64>(1)?, row.get::<_, i64>(2)? as u64, row.get::<_, Option<i64>>(3)?),
+            ))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    Ok(stamps)
 }
 
 pub fn record_open(conn: &rusqlite::Connection, path: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -383,6 +694,111 @@ pub fn record_open(conn: &rusqlite::Connection, path: &str) -> Result<(), Box<dy
     Ok(())
 }
 
+/// Remember where a file's thumbnail was written
+pub fn set_thumbnail_path(
+    conn: &rusqlite::Connection,
+    path: &str,
+    thumbnail_path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute(
+        "UPDATE files SET thumbnail_path = ?2 WHERE path = ?1",
+        params![path, thumbnail_path],
+    )?;
+    Ok(())
+}
+
+/// Paths of indexed files with one of `extensions` that have no thumbnail yet
+pub fn files_missing_thumbnails(
+    conn: &rusqlite::Connection,
+    extensions: &[&str],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    // json_each() turns a JSON array into rows, so one parameter can hold
+    // the whole extension list (no need to build "?, ?, ?" placeholders)
+    let extensions = serde_json::to_string(extensions)?;
+    let mut stmt = conn.prepare_cached(
+        "SELECT path FROM files
+         WHERE thumbnail_path IS NULL
+           AND lower(file_type) IN (SELECT value FROM json_each(?1))
+         ORDER BY modified_at DESC",
+    )?;
+
+    let paths = stmt
+        .query_map([extensions], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(paths)
+}
+
+// ----------------------------------------------------------------------------
+// Emotional metadata (mood, season, vibe, location, energy, notes)
+// ----------------------------------------------------------------------------
+
+/// Save emotional metadata for a file, replacing whatever was there before.
+/// Empty strings are stored as NULL so "cleared" fields really are cleared.
+pub fn upsert_file_metadata(
+    conn: &rusqlite::Connection,
+    metadata: &FileMetadata,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fn clean(value: &Option<String>) -> Option<&str> {
+        value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+    }
+
+    conn.execute(
+        "INSERT INTO file_metadata (file_id, mood, season, vibe_color, location, energy_level, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(file_id) DO UPDATE SET
+            mood = excluded.mood,
+            season = excluded.season,
+            vibe_color = excluded.vibe_color,
+            location = excluded.location,
+            energy_level = excluded.energy_level,
+            notes = excluded.notes,
+            updated_at = strftime('%s', 'now')",
+        params![
+            metadata.file_id,
+            clean(&metadata.mood),
+            clean(&metadata.season),
+            clean(&metadata.vibe_color),
+            clean(&metadata.location),
+            clean(&metadata.energy_level),
+            clean(&metadata.notes),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Load emotional metadata for a file (None if nothing was ever saved)
+pub fn get_file_metadata(
+    conn: &rusqlite::Connection,
+    file_id: i64,
+) -> Result<Option<FileMetadata>, Box<dyn std::error::Error>> {
+    let metadata = conn
+        .query_row(
+            "SELECT file_id, mood, season, vibe_color, location, energy_level, notes
+             FROM file_metadata
+             WHERE file_id = ?1",
+            params![file_id],
+            |row| {
+                Ok(FileMetadata {
+                    file_id: row.get(0)?,
+                    mood: row.get(1)?,
+                    season: row.get(2)?,
+                    vibe_color: row.get(3)?,
+                    location: row.get(4)?,
+                    energy_level: row.get(5)?,
+                    notes: row.get(6)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(metadata)
+}
+
+/// Remove all emotional metadata for a file
+pub fn clear_file_metadata(conn: &rusqlite::Connection, file_id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute("DELETE FROM file_metadata WHERE file_id = ?1", params![file_id])?;
+    Ok(())
+}
+
 fn activity_timestamp(file: &FileInfo) -> i64 {
     file.last_opened_at.unwrap_or(file.modified_at)
 }
@@ -407,30 +823,17 @@ pub fn get_resurfaced_files(
 ) -> Result<Vec<ResurfacedFile>, Box<dyn std::error::Error>> {
     let count = count.clamp(1, 12);
 
-    let mut stmt = conn.prepare(
-        "SELECT id, path, name, file_type, size, created_at, modified_at, last_opened_at, thumbnail_path
-         FROM files
-         ORDER BY COALESCE(last_opened_at, modified_at) ASC
+    let mut stmt = conn.prepare_cached(&format!(
+        "{}
+         ORDER BY COALESCE(f.last_opened_at, f.modified_at) ASC
          LIMIT 5000",
-    )?;
+        FILE_SELECT
+    ))?;
 
     let mut files: Vec<FileInfo> = stmt
-        .query_map([], |row| {
-            Ok(FileInfo {
-                id: row.get(0)?,
-                path: row.get(1)?,
-                name: row.get(2)?,
-                file_type: row.get(3)?,
-                size: row.get::<_, i64>(4)? as u64,
-                created_at: row.get(5)?,
-                modified_at: row.get(6)?,
-                last_opened_at: row.get(7)?,
-                thumbnail_path: row.get(8)?,
-                finder_tags: Vec::new(),
-                finder_colors: Vec::new(),
-            })
-        })?
+        .query_map([], file_from_row)?
         .collect::<Result<Vec<_>, _>>()?;
+    attach_tags(conn, &mut files)?;
 
     if files.is_empty() {
         return Ok(Vec::new());
@@ -537,41 +940,763 @@ pub fn get_file_count(conn: &rusqlite::Connection) -> Result<usize, Box<dyn std:
     Ok(count as usize)
 }
 
+// ============================================================================
+// SETTINGS (key -> JSON value)
+// ============================================================================
+//
+// Backend preferences (tag colors, ignore rules, ...) live in the settings
+// table so the Rust side can read them without asking the frontend.
+
+/// Load a setting, or None if it was never saved
+pub fn get_setting<T: DeserializeOwned>(
+    conn: &rusqlite::Connection,
+    key: &str,
+) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    let value: Option<String> = conn
+        .prepare_cached("SELECT value FROM settings WHERE key = ?1")?
+        .query_row(params![key], |row| row.get(0))
+        .optional()?;
+
+    match value {
+        Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+        None => Ok(None),
+    }
+}
+
+/// Save a setting (replaces the previous value)
+pub fn set_setting<T: Serialize>(
+    conn: &rusqlite::Connection,
+    key: &str,
+    value: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    conn.prepare_cached(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET
+            value = excluded.value,
+            updated_at = strftime('%s', 'now')",
+    )?
+    .execute(params![key, serde_json::to_string(value)?])?;
+    Ok(())
+}
+
+// ============================================================================
+// SCAN POLICIES (one row per source folder)
+// ============================================================================
+
+const SCAN_POLICY_COLUMNS: &str = "root, max_depth, follow_links, include_hidden, min_size, max_size,
+     allowed_extensions, denied_extensions, index_directories";
+
+fn scan_policy_from_row(row: &rusqlite::Row) -> rusqlite::Result<(String, ScanPolicy)> {
+    // Extension lists are stored as JSON text
+    let list = |idx: usize| -> rusqlite::Result<Vec<String>> {
+        let json: String = row.get(idx)?;
+        Ok(serde_json::from_str(&json).unwrap_or_default())
+    };
+
+    Ok((
+        row.get(0)?,
+        ScanPolicy {
+            max_depth: row.get::<_, i64>(1)?.max(1) as usize,
+            follow_links: row.get(2)?,
+            include_hidden: row.get(3)?,
+            min_size: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+            max_size: row.get::<_, Option<i64>>(5)?.map(|v| v as u64),
+            allowed_extensions: list(6)?,
+            denied_extensions: list(7)?,
+            index_directories: row.get(8)?,
+        },
+    ))
+}
+
+pub fn get_scan_policies(
+    conn: &rusqlite::Connection,
+) -> Result<Vec<(String, ScanPolicy)>, Box<dyn std::error::Error>> {
+    let mut stmt = conn.prepare_cached(&format!("SELECT {} FROM scan_policies", SCAN_POLICY_COLUMNS))?;
+    let policies = stmt
+        .query_map([], scan_policy_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(policies)
+}
+
+pub fn get_scan_policy(
+    conn: &rusqlite::Connection,
+    root: &str,
+) -> Result<Option<ScanPolicy>, Box<dyn std::error::Error>> {
+    let policy = conn
+        .prepare_cached(&format!("SELECT {} FROM scan_policies WHERE root = ?1", SCAN_POLICY_COLUMNS))?
+        .query_row(params![root], scan_policy_from_row)
+        .optional()?;
+    Ok(policy.map(|(_, policy)| policy))
+}
+
+pub fn set_scan_policy(
+    conn: &rusqlite::Connection,
+    root: &str,
+    policy: &ScanPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    conn.prepare_cached(
+        "INSERT INTO scan_policies (root, max_depth, follow_links, include_hidden, min_size, max_size,
+                                    allowed_extensions, denied_extensions, index_directories)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(root) DO UPDATE SET
+            max_depth = excluded.max_depth,
+            follow_links = excluded.follow_links,
+            include_hidden = excluded.include_hidden,
+            min_size = excluded.min_size,
+            max_size = excluded.max_size,
+            allowed_extensions = excluded.allowed_extensions,
+            denied_extensions = excluded.denied_extensions,
+            index_directories = excluded.index_directories,
+            updated_at = strftime('%s', 'now')",
+    )?
+    .execute(params![
+        root,
+        policy.max_depth as i64,
+        policy.follow_links,
+        policy.include_hidden,
+        policy.min_size.map(|v| v as i64),
+        policy.max_size.map(|v| v as i64),
+        serde_json::to_string(&policy.allowed_extensions)?,
+        serde_json::to_string(&policy.denied_extensions)?,
+        policy.index_directories,
+    ])?;
+    Ok(())
+}
+
+pub fn delete_scan_policy(conn: &rusqlite::Connection, root: &str) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute("DELETE FROM scan_policies WHERE root = ?1", params![root])?;
+    Ok(())
+}
+
+// ============================================================================
+// SCAN SOURCES (the folders the user added)
+// ============================================================================
+
+const SCAN_SOURCE_COLUMNS: &str = "id, path, enabled, added_at, last_scan_at, last_scan_status,
+     last_scan_added, last_scan_updated, last_scan_removed, last_scan_unchanged,
+     last_scan_errors, last_error";
+
+fn scan_source_from_row(row: &rusqlite::Row) -> rusqlite::Result<ScanSource> {
+    Ok(ScanSource {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        enabled: row.get(2)?,
+        added_at: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+        last_scan_at: row.get(4)?,
+        last_scan_status: row.get(5)?,
+        last_scan_added: row.get::<_, i64>(6)? as usize,
+        last_scan_updated: row.get::<_, i64>(7)? as usize,
+        last_scan_removed: row.get::<_, i64>(8)? as usize,
+        last_scan_unchanged: row.get::<_, i64>(9)? as usize,
+        last_scan_errors: row.get::<_, i64>(10)? as usize,
+        last_error: row.get(11)?,
+    })
+}
+
+pub fn get_scan_sources(conn: &rusqlite::Connection) -> Result<Vec<ScanSource>, Box<dyn std::error::Error>> {
+    let mut stmt = conn.prepare_cached(&format!("SELECT {} FROM scan_sources ORDER BY path", SCAN_SOURCE_COLUMNS))?;
+    let sources = stmt
+        .query_map([], scan_source_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sources)
+}
+
+/// Paths of the sources that should be scanned and watched
+pub fn get_enabled_source_paths(conn: &rusqlite::Connection) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut stmt = conn.prepare_cached("SELECT path FROM scan_sources WHERE enabled = 1 ORDER BY path")?;
+    let paths = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(paths)
+}
+
+/// Add a source (enabled). Returns false if it was already registered.
+pub fn add_scan_source(conn: &rusqlite::Connection, path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let inserted = conn.execute(
+        "INSERT INTO scan_sources (path) VALUES (?1) ON CONFLICT(path) DO NOTHING",
+        params![path],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Returns false if there was no such source
+pub fn delete_scan_source(conn: &rusqlite::Connection, path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let deleted = conn.execute("DELETE FROM scan_sources WHERE path = ?1", params![path])?;
+    Ok(deleted > 0)
+}
+
+/// Returns false if there was no such source
+pub fn set_scan_source_enabled(
+    conn: &rusqlite::Connection,
+    path: &str,
+    enabled: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let updated = conn.execute(
+        "UPDATE scan_sources SET enabled = ?2 WHERE path = ?1",
+        params![path, enabled],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Remember how the last scan of a source went.
+/// Folders that aren't registered sources (e.g. dropped onto the window)
+/// are scanned too, but there's no row to update - that's fine.
+pub fn record_source_scan(
+    conn: &rusqlite::Connection,
+    stats: &SourceScanStats,
+) -> Result<(), Box<dyn std::error::Error>> {
+    conn.prepare_cached(
+        "UPDATE scan_sources SET
+            last_scan_at = strftime('%s', 'now'),
+            last_scan_status = ?2,
+            last_scan_added = ?3,
+            last_scan_updated = ?4,
+            last_scan_removed = ?5,
+            last_scan_unchanged = ?6,
+            last_scan_errors = ?7,
+            last_error = ?8
+         WHERE path = ?1",
+    )?
+    .execute(params![
+        stats.path,
+        stats.status,
+        stats.added as i64,
+        stats.updated as i64,
+        stats.removed as i64,
+        stats.unchanged as i64,
+        stats.errors as i64,
+        stats.last_error,
+    ])?;
+    Ok(())
+}
+
+// ============================================================================
+// SEARCH
+// ============================================================================
+//
+// Searching is three steps:
+// 1. search_candidates() finds up to SEARCH_CANDIDATES matching files and
+//    the signals ranking.rs scores them by (text match, opens, dates...)
+// 2. ranking.rs picks the best SEARCH_LIMIT, and only those are loaded
+//    as full FileInfo rows
+// 3. highlight.rs adds what matched in each one (for the UI to bold)
+
+/// Files considered per search before ranking
+const SEARCH_CANDIDATES: usize = 500;
+
+/// Results returned per search
+const SEARCH_LIMIT: usize = 50;
+
+/// One search hit, with what ranking.rs needs to score it
+#[derive(Debug, Clone)]
+pub struct SearchCandidate {
+    pub id: i64,
+    /// FTS5 bm25() score: MORE NEGATIVE = better match. None without words.
+    pub text_score: Option<f64>,
+    pub open_count: i64,
+    pub last_opened_at: Option<i64>,
+    pub modified_at: i64,
+    pub tagged: bool,
+    pub clustered: bool,
+}
+
+/// How search matches the words that were typed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Whole words first; fuzzy if that finds nothing
+    #[default]
+    Auto,
+    /// Whole words and word prefixes only (FTS5)
+    Text,
+    /// Letters in order with gaps allowed: "resme" finds "resume" (fuzzy.rs)
+    Fuzzy,
+}
+
+/// Search files with the search box syntax (words, "phrases", type:pdf,
+/// size:>10mb... - see search_query.rs), best results first. Errors in
+/// the query come back as a message the user can act on.
+pub fn search_files(
+    conn: &rusqlite::Connection,
+    query: &str,
+) -> Result<Vec<FileInfo>, Box<dyn std::error::Error>> {
+    let results = search_files_with_mode(conn, query, SearchMode::Auto)?;
+    Ok(results.into_iter().map(|r| r.file).collect())
+}
+
+/// Like search_files, with the matched parts of each result
+pub fn search_files_with_mode(
+    conn: &rusqlite::Connection,
+    query: &str,
+    mode: SearchMode,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let query = SearchQuery::parse(query)?;
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Only filters: nothing to match fuzzily
+    if mode == SearchMode::Fuzzy && !query.words.is_empty() {
+        return fuzzy_files(conn, &query);
+    }
+    let results = text_search(conn, &query)?;
+    if results.is_empty() && mode == SearchMode::Auto && !query.words.is_empty() {
+        return fuzzy_files(conn, &query);
+    }
+    Ok(results)
+}
+
+/// FTS5 search, ranked by ranking.rs
+fn text_search(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let candidates = search_candidates(conn, query, SEARCH_CANDIDATES)?;
+    let ranking = SearchRanking::load(conn);
+    let ids = crate::ranking::rank(&candidates, &ranking, Utc::now().timestamp(), SEARCH_LIMIT);
+    let files = get_files_by_ids(conn, &ids)?;
+    crate::highlight::text_results(conn, query, files)
+}
+
+fn fuzzy_files(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let matches = crate::fuzzy::fuzzy_search(conn, query, SEARCH_LIMIT)?;
+    crate::highlight::fuzzy_results(conn, query, matches)
+}
+
+/// Files matching `query`, best text matches first (or, with only filters,
+/// the most recently touched first)
+pub fn search_candidates(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    limit: usize,
+) -> Result<Vec<SearchCandidate>, Box<dyn std::error::Error>> {
+    let mut values = Vec::new();
+
+    // Word matches, from names/paths and from file contents (content.rs).
+    // A hit in the name counts 4x a hit in the folder names of the path,
+    // and a hit in the contents half as much as one in the path.
+    let hits = match &query.fts {
+        Some(fts) => {
+            values.push(Value::Text(fts.clone()));
+            let mut hits = "SELECT rowid AS id, bm25(files_fts, 4.0, 1.0) AS score
+                  FROM files_fts WHERE files_fts MATCH ?"
+                .to_string();
+            if let Some(words_fts) = &query.words_fts {
+                values.push(Value::Text(words_fts.clone()));
+                hits.push_str(
+                    "
+                  UNION ALL
+                  SELECT rowid, bm25(contents_fts) * 0.5
+                  FROM contents_fts WHERE contents_fts MATCH ?",
+                );
+                // name:/path: words still have to be in the name/path
+                if let Some(column_fts) = &query.column_fts {
+                    values.push(Value::Text(column_fts.clone()));
+                    hits.push_str(" AND rowid IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?)");
+                }
+            }
+            Some(hits)
+        }
+        None => None,
+    };
+
+    values.extend(query.params.iter().cloned());
+    values.push(Value::Integer(limit as i64));
+
+    let filter = if query.conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", query.conditions.join("\n           AND "))
+    };
+    let sql = match hits {
+        // MATERIALIZED: bm25() only works while reading the FTS table, so
+        // SQLite mustn't fold `hits` into the GROUP BY below
+        Some(hits) => format!(
+            "WITH hits AS MATERIALIZED (
+                  {}
+             )
+             SELECT f.id, MIN(h.score), IFNULL(f.open_count, 0), f.last_opened_at, IFNULL(f.modified_at, 0),
+                    EXISTS (SELECT 1 FROM file_tags ft WHERE ft.file_id = f.id),
+                    f.tile_cluster IS NOT NULL
+             FROM hits h
+             JOIN files f ON f.id = h.id
+             LEFT JOIN file_metadata m ON m.file_id = f.id
+             {}
+             GROUP BY f.id
+             ORDER BY MIN(h.score), f.id
+             LIMIT ?",
+            hits, filter
+        ),
+        // Only filters: the most recently touched first
+        None => format!(
+            "SELECT f.id, NULL, IFNULL(f.open_count, 0), f.last_opened_at, IFNULL(f.modified_at, 0),
+                    EXISTS (SELECT 1 FROM file_tags ft WHERE ft.file_id = f.id),
+                    f.tile_cluster IS NOT NULL
+             FROM files f
+             LEFT JOIN file_metadata m ON m.file_id = f.id
+             {}
+             ORDER BY MAX(IFNULL(f.last_opened_at, 0), IFNULL(f.modified_at, 0)) DESC, f.id
+             LIMIT ?",
+            filter
+        ),
+    };
+
+    // prepare, not prepare_cached: almost every search is different SQL
+    let mut stmt = conn.prepare(&sql)?;
+    let candidates = stmt
+        .query_map(rusqlite::params_from_iter(values), search_candidate_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(candidates)
+}
+
+fn search_candidate_from_row(row: &rusqlite::Row) -> rusqlite::Result<SearchCandidate> {
+    Ok(SearchCandidate {
+        id: row.get(0)?,
+        text_score: row.get(1)?,
+        open_count: row.get(2)?,
+        last_opened_at: row.get(3)?,
+        modified_at: row.get(4)?,
+        tagged: row.get(5)?,
+        clustered: row.get(6)?,
+    })
+}
+
+/// Ranking signals for files found some other way (text_score is None)
+pub fn search_signals(
+    conn: &rusqlite::Connection,
+    ids: &[i64],
+) -> Result<Vec<SearchCandidate>, Box<dyn std::error::Error>> {
+    let candidates = conn
+        .prepare_cached(
+            "SELECT f.id, NULL, IFNULL(f.open_count, 0), f.last_opened_at, IFNULL(f.modified_at, 0),
+                    EXISTS (SELECT 1 FROM file_tags ft WHERE ft.file_id = f.id),
+                    f.tile_cluster IS NOT NULL
+             FROM files f
+             WHERE f.id IN (SELECT value FROM json_each(?1))",
+        )?
+        .query_map([serde_json::to_string(ids)?], search_candidate_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(candidates)
+}
+
+/// Call `visit(id, name, path)` for every file that passes the query's
+/// filters and name:/path: words - but NOT its plain words, which the
+/// caller matches itself (fuzzy.rs). Rows are streamed, not collected.
+pub fn visit_search_rows(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    mut visit: impl FnMut(i64, &str, &str),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conditions = query.conditions.clone();
+    let mut values = query.params.clone();
+    if let Some(column_fts) = &query.column_fts {
+        conditions.push("f.id IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?)".to_string());
+        values.push(Value::Text(column_fts.clone()));
+    }
+    let filter = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join("\n           AND "))
+    };
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT f.id, f.name, f.path
+         FROM files f
+         LEFT JOIN file_metadata m ON m.file_id = f.id
+         {}",
+        filter
+    ))?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(values))?;
+    while let Some(row) = rows.next()? {
+        // get_ref borrows the text straight from SQLite - no String per row
+        visit(row.get(0)?, row.get_ref(1)?.as_str()?, row.get_ref(2)?.as_str()?);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// CONTENT HASHES (duplicate detection)
+// ============================================================================
+
+/// A file waiting for background work (hashing, text extraction),
+/// as the database knows it
+#[derive(Debug, Clone)]
+pub struct HashCandidate {
+    pub id: i64,
+    pub path: String,
+    pub size: u64,
+    pub modified_at: i64,
+}
+
+fn hash_candidate_from_row(row: &rusqlite::Row) -> rusqlite::Result<HashCandidate> {
+    Ok(HashCandidate {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        size: row.get::<_, i64>(2)? as u64,
+        modified_at: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+    })
+}
+
+/// Files without a partial hash that share their size with another file
+/// (a file with a unique size can't have a duplicate). Paged by id.
+/// Folder rows have a size too, but nothing to hash - they're skipped.
+pub fn files_needing_partial_hash(
+    conn: &rusqlite::Connection,
+    min_size: u64,
+    after_id: i64,
+    limit: usize,
+) -> Result<Vec<HashCandidate>, Box<dyn std::error::Error>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, path, size, modified_at FROM files
+         WHERE partial_hash IS NULL AND size >= ?1 AND id > ?2 AND file_type IS NOT ?4
+           AND size IN (SELECT size FROM files WHERE size >= ?1 AND file_type IS NOT ?4
+                        GROUP BY size HAVING COUNT(*) > 1)
+         ORDER BY id
+         LIMIT ?3",
+    )?;
+    let files = stmt
+        .query_map(params![min_size as i64, after_id, limit as i64, FOLDER_FILE_TYPE], hash_candidate_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(files)
+}
+
+/// Files without a full hash whose size AND partial hash match another file
+pub fn files_needing_content_hash(
+    conn: &rusqlite::Connection,
+    after_id: i64,
+    limit: usize,
+) -> Result<Vec<HashCandidate>, Box<dyn std::error::Error>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT f.id, f.path, f.size, f.modified_at FROM files f
+         WHERE f.content_hash IS NULL AND f.partial_hash IS NOT NULL AND f.id > ?1 AND f.file_type IS NOT ?3
+           AND EXISTS (SELECT 1 FROM files g
+                       WHERE g.size = f.size AND g.partial_hash = f.partial_hash AND g.id != f.id
+                         AND g.file_type IS NOT ?3)
+         ORDER BY f.id
+         LIMIT ?2",
+    )?;
+    let files = stmt
+        .query_map(params![after_id, limit as i64, FOLDER_FILE_TYPE], hash_candidate_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(files)
+}
+
+/// How many files still need a hash before duplicate results are complete
+pub fn count_files_needing_hash(conn: &rusqlite::Connection, min_size: u64) -> Result<usize, Box<dyn std::error::Error>> {
+    let partial: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM files
+         WHERE partial_hash IS NULL AND size >= ?1 AND file_type IS NOT ?2
+           AND size IN (SELECT size FROM files WHERE size >= ?1 AND file_type IS NOT ?2
+                        GROUP BY size HAVING COUNT(*) > 1)",
+        params![min_size as i64, FOLDER_FILE_TYPE],
+        |row| row.get(0),
+    )?;
+    let full: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM files f
+         WHERE f.content_hash IS NULL AND f.partial_hash IS NOT NULL AND f.file_type IS NOT ?1
+           AND EXISTS (SELECT 1 FROM files g
+                       WHERE g.size = f.size AND g.partial_hash = f.partial_hash AND g.id != f.id
+                         AND g.file_type IS NOT ?1)",
+        [FOLDER_FILE_TYPE],
+        |row| row.get(0),
+    )?;
+    Ok((partial + full) as usize)
+}
+
+/// Save hashes computed for `file`. A row that changed since it was read
+/// (different size or modified time) is left alone - its hash would be
+/// stale. `column` is "partial_hash" or "content_hash".
+pub fn set_file_hashes(
+    tx: &rusqlite::Transaction,
+    column: &str,
+    hashes: &[(HashCandidate, String)],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if column != "partial_hash" && column != "content_hash" {
+        return Err(format!("Not a hash column: {}", column).into());
+    }
+    let mut stmt = tx.prepare_cached(&format!(
+        "UPDATE files SET {} = ?2 WHERE id = ?1 AND size = ?3 AND COALESCE(modified_at, 0) = ?4",
+        column
+    ))?;
+    let mut saved = 0usize;
+    for (file, hash) in hashes {
+        saved += stmt.execute(params![file.id, hash, file.size as i64, file.modified_at])?;
+    }
+    Ok(saved)
+}
+
+/// Identical files: (content hash, size, the files)
+pub type DuplicateRows = (String, u64, Vec<FileInfo>);
+
+/// Groups of identical files, biggest waste (size x extra copies) first
+pub fn get_duplicate_groups(
+    conn: &rusqlite::Connection,
+    limit: usize,
+) -> Result<Vec<DuplicateRows>, Box<dyn std::error::Error>> {
+    let groups: Vec<(String, u64)> = conn
+        .prepare_cached(
+            "SELECT content_hash, size FROM files
+             WHERE content_hash IS NOT NULL
+             GROUP BY content_hash, size
+             HAVING COUNT(*) > 1
+             ORDER BY (COUNT(*) - 1) * size DESC, content_hash
+             LIMIT ?1",
+        )?
+        .query_map(params![limit as i64], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare_cached(&format!(
+        "{}
+         WHERE f.content_hash = ?1 AND f.size = ?2
+         ORDER BY f.modified_at ASC, f.path",
+        FILE_SELECT
+    ))?;
+    let mut result = Vec::with_capacity(groups.len());
+    for (hash, size) in groups {
+        let mut files = stmt
+            .query_map(params![hash, size as i64], file_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        attach_tags(conn, &mut files)?;
+        result.push((hash, size, files));
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// FILE CONTENTS (content search)
+// ============================================================================
+
+/// Files of these types whose text hasn't been read yet. Paged by id.
+pub fn files_needing_content(
+    conn: &rusqlite::Connection,
+    file_types: &[&str],
+    max_size: u64,
+    after_id: i64,
+    limit: usize,
+) -> Result<Vec<HashCandidate>, Box<dyn std::error::Error>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, path, size, modified_at FROM files
+         WHERE content_indexed_at IS NULL AND id > ?1 AND size <= ?2
+           AND lower(file_type) IN (SELECT value FROM json_each(?3))
+         ORDER BY id
+         LIMIT ?4",
+    )?;
+    let files = stmt
+        .query_map(
+            params![after_id, max_size as i64, serde_json::to_string(file_types)?, limit as i64],
+            hash_candidate_from_row,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(files)
+}
+
+/// How many files of these types are still waiting to be read
+pub fn count_files_needing_content(
+    conn: &rusqlite::Connection,
+    file_types: &[&str],
+    max_size: u64,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM files
+         WHERE content_indexed_at IS NULL AND size <= ?1
+           AND lower(file_type) IN (SELECT value FROM json_each(?2))",
+        params![max_size as i64, serde_json::to_string(file_types)?],
+        |row| row.get(0),
+    )?;
+    Ok(count as usize)
+}
+
+/// How many files have searchable text
+pub fn count_indexed_contents(conn: &rusqlite::Connection) -> Result<usize, Box<dyn std::error::Error>> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM contents_fts", [], |row| row.get(0))?;
+    Ok(count as usize)
+}
+
+/// Mark `file` as read BEFORE parsing it, so a parser that crashes the app
+/// doesn't get the same file again on the next launch (save_file_contents
+/// marks it again with the text). False if the row changed since it was
+/// listed - then it shouldn't be read at all.
+pub fn mark_content_attempted(conn: &rusqlite::Connection, file: &HashCandidate) -> Result<bool, Box<dyn std::error::Error>> {
+    let marked = conn.execute(
+        "UPDATE files SET content_indexed_at = strftime('%s', 'now')
+         WHERE id = ?1 AND size = ?2 AND COALESCE(modified_at, 0) = ?3",
+        params![file.id, file.size as i64, file.modified_at],
+    )?;
+    Ok(marked > 0)
+}
+
+/// Undo mark_content_attempted, so the next pass reads the file again
+pub fn clear_content_attempted(conn: &rusqlite::Connection, id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    conn.execute("UPDATE files SET content_indexed_at = NULL WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Save the text read from each file (None = nothing to index: no text,
+/// or it couldn't be read). Like set_file_hashes, a row that changed since
+/// it was read is left alone. Returns how many files were saved.
+pub fn save_file_contents(
+    tx: &rusqlite::Transaction,
+    contents: &[(HashCandidate, Option<String>)],
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut mark = tx.prepare_cached(
+        "UPDATE files SET content_indexed_at = strftime('%s', 'now')
+         WHERE id = ?1 AND size = ?2 AND COALESCE(modified_at, 0) = ?3",
+    )?;
+    let mut delete = tx.prepare_cached("DELETE FROM contents_fts WHERE rowid = ?1")?;
+    let mut insert = tx.prepare_cached("INSERT INTO contents_fts (rowid, content) VALUES (?1, ?2)")?;
+
+    let mut saved = 0usize;
+    for (file, text) in contents {
+        if mark.execute(params![file.id, file.size as i64, file.modified_at])? == 0 {
+            continue;
+        }
+        // Replace whatever an older version of the file said
+        delete.execute(params![file.id])?;
+        if let Some(text) = text {
+            insert.execute(params![file.id, text])?;
+        }
+        saved += 1;
+    }
+    Ok(saved)
+}
+
 // ============================================================================
 // TAURI COMMANDS (callable from JavaScript)
 // ============================================================================
 
 #[tauri::command]
-pub async fn db_get_all_files(app_handle: AppHandle) -> Result<Vec<FileInfo>, String> {
-    let conn = get_connection(&app_handle).map_err(|e| e.to_string())?;
-    get_all_files(&conn).map_err(|e| e.to_string())
+pub async fn db_get_all_files(pool: State<'_, DbPool>) -> Result<Vec<FileInfo>, String> {
+    get_all_files(&pool.reader()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn db_search_files(app_handle: AppHandle, query: String) -> Result<Vec<FileInfo>, String> {
-    let conn = get_connection(&app_handle).map_err(|e| e.to_string())?;
-    search_files(&conn, &query).map_err(|e| e.to_string())
+pub async fn db_search_files(
+    pool: State<'_, DbPool>,
+    query: String,
+    mode: Option<SearchMode>,
+) -> Result<Vec<SearchResult>, String> {
+    search_files_with_mode(&pool.reader(), &query, mode.unwrap_or_default()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn db_get_file_count(app_handle: AppHandle) -> Result<usize, String> {
-    let conn = get_connection(&app_handle).map_err(|e| e.to_string())?;
-    get_file_count(&conn).map_err(|e| e.to_string())
+pub async fn db_get_file_count(pool: State<'_, DbPool>) -> Result<usize, String> {
+    get_file_count(&pool.reader()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn db_record_open(app_handle: AppHandle, path: String) -> Result<(), String> {
-    let conn = get_connection(&app_handle).map_err(|e| e.to_string())?;
-    record_open(&conn, &path).map_err(|e| e.to_string())
+pub async fn db_record_open(pool: State<'_, DbPool>, path: String) -> Result<(), String> {
+    record_open(&pool.writer(), &path).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn db_get_resurfaced_files(
-    app_handle: AppHandle,
+    pool: State<'_, DbPool>,
     count: Option<usize>,
 ) -> Result<Vec<ResurfacedFile>, String> {
-    let conn = get_connection(&app_handle).map_err(|e| e.to_string())?;
-    get_resurfaced_files(&conn, count.unwrap_or(3)).map_err(|e| e.to_string())
+    get_resurfaced_files(&pool.reader(), count.unwrap_or(3)).map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -579,7 +1704,7 @@ pub async fn db_get_resurfaced_files(
 // ============================================================================
 //
 // 1. RUSQLITE: Direct SQLite access (simpler than plugin for now)
-//    - Connection::open() creates/opens database
+//    - Connection::open() creates/opens database (done once, in DbPool)
 //    - execute() runs SQL without returning rows
 //    - query_map() runs SQL and maps rows to Rust types
 //
@@ -591,6 +1716,7 @@ pub async fn db_get_resurfaced_files(
 // 3. PREPARED STATEMENTS: Prevent SQL injection
 //    ?1, ?2 = positional parameters
 //    :name = named parameters
+//    prepare_cached() reuses the compiled statement on later calls
 //
 // 4. FTS5: Full-Text Search
 //    - Virtual table that indexes text
@@ -598,3 +1724,104 @@ pub async fn db_get_resurfaced_files(
 //    - rank column shows relevance
 //
 // ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{file_id, file_info, memory_database};
+
+    fn metadata(file_id: i64) -> FileMetadata {
+        FileMetadata {
+            file_id,
+            mood: Some("hopeful".to_string()),
+            season: Some("summer".to_string()),
+            vibe_color: Some("#FFCC00".to_string()),
+            location: Some("Lisbon".to_string()),
+            energy_level: Some("high".to_string()),
+            notes: Some("The trip we kept talking about".to_string()),
+        }
+    }
+
+    #[test]
+    fn metadata_is_saved_and_comes_back_with_file_rows() {
+        let mut conn = memory_database();
+        upsert_files(&mut conn, &[file_info("/docs/trip.md", 10, 100), file_info("/docs/other.md", 10, 50)]).unwrap();
+        let id = file_id(&conn, "/docs/trip.md");
+
+        upsert_file_metadata(&conn, &metadata(id)).unwrap();
+        assert_eq!(get_file_metadata(&conn, id).unwrap(), Some(metadata(id)));
+
+        let files = get_all_files(&conn).unwrap();
+        let trip = files.iter().find(|f| f.path == "/docs/trip.md").unwrap();
+        assert_eq!(trip.metadata, Some(metadata(id)));
+        let other = files.iter().find(|f| f.path == "/docs/other.md").unwrap();
+        assert_eq!(other.metadata, None);
+    }
+
+    #[test]
+    fn saving_metadata_replaces_it_and_blank_fields_are_cleared() {
+        let mut conn = memory_database();
+        upsert_files(&mut conn, &[file_info("/docs/trip.md", 10, 100)]).unwrap();
+        let id = file_id(&conn, "/docs/trip.md");
+        upsert_file_metadata(&conn, &metadata(id)).unwrap();
+
+        let edited = FileMetadata {
+            mood: Some("  calm ".to_string()),
+            location: Some("   ".to_string()),
+            notes: None,
+            ..metadata(id)
+        };
+        upsert_file_metadata(&conn, &edited).unwrap();
+
+        let saved = get_file_metadata(&conn, id).unwrap().unwrap();
+        assert_eq!(saved.mood.as_deref(), Some("calm"));
+        assert_eq!(saved.location, None);
+        assert_eq!(saved.notes, None);
+        assert_eq!(saved.season.as_deref(), Some("summer"));
+    }
+
+    #[test]
+    fn metadata_survives_rescans_and_goes_with_its_file() {
+        let mut conn = memory_database();
+        upsert_files(&mut conn, &[file_info("/docs/trip.md", 10, 100)]).unwrap();
+        let id = file_id(&conn, "/docs/trip.md");
+        upsert_file_metadata(&conn, &metadata(id)).unwrap();
+
+        // A rescan of a changed file updates the same row
+        upsert_files(&mut conn, &[file_info("/docs/trip.md", 20, 200)]).unwrap();
+        assert_eq!(file_id(&conn, "/docs/trip.md"), id);
+        assert_eq!(get_file_metadata(&conn, id).unwrap(), Some(metadata(id)));
+
+        clear_file_metadata(&conn, id).unwrap();
+        assert_eq!(get_file_metadata(&conn, id).unwrap(), None);
+
+        // Deleting the file deletes its metadata (ON DELETE CASCADE)
+        upsert_file_metadata(&conn, &metadata(id)).unwrap();
+        conn.execute("DELETE FROM files WHERE id = ?1", [id]).unwrap();
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM file_metadata", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[test]
+    fn changed_images_lose_their_thumbnail() {
+        let mut conn = memory_database();
+        upsert_files(&mut conn, &[file_info("/photos/a.jpg", 10, 100), file_info("/photos/b.jpg", 10, 100)]).unwrap();
+        set_thumbnail_path(&conn, "/photos/a.jpg", "/cache/aa/a-256.png").unwrap();
+        set_thumbnail_path(&conn, "/photos/b.jpg", "/cache/bb/b-256.png").unwrap();
+
+        // a.jpg was edited while Aurora was closed; b.jpg wasn't
+        upsert_files(&mut conn, &[file_info("/photos/a.jpg", 12, 200), file_info("/photos/b.jpg", 10, 100)]).unwrap();
+
+        assert_eq!(files_missing_thumbnails(&conn, &["jpg"]).unwrap(), vec!["/photos/a.jpg".to_string()]);
+        let thumbnail = |path: &str| -> Option<String> {
+            conn.query_row("SELECT thumbnail_path FROM files WHERE path = ?1", [path], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(thumbnail("/photos/a.jpg"), None);
+        assert_eq!(thumbnail("/photos/b.jpg").as_deref(), Some("/cache/bb/b-256.png"));
+    }
+}
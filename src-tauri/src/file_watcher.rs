@@ -9,27 +9,118 @@
 //
 // The "notify" crate gives us cross-platform access to FSEvents
 //
+// Folders where native events can't be trusted (network shares, FUSE
+// mounts, inotify's watch limit) are polled instead - see watch_fallback.rs
+//
 // ============================================================================
 
-use notify::{RecursiveMode, Watcher};
-use notify_debouncer_full::{new_debouncer, DebouncedEvent};
+use notify::{PollWatcher, RecursiveMode, Watcher};
+use notify_debouncer_full::{new_debouncer, new_debouncer_opt, DebouncedEvent, Debouncer, FileIdMap};
 // Debouncer prevents event spam
 // When you save a file, your editor might trigger 10 events in 1 second
 // The debouncer waits a bit and only notifies us once
 
-use std::path::Path;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{mpsc, Mutex};
-use std::time::Duration;
-use tauri::{AppHandle, Emitter, State};
-use crate::commands::FileInfo;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::commands::{system_time_to_unix, FileInfo};
+use crate::content::ContentQueue;
+use crate::db::DbPool;
+use crate::hashing::HashQueue;
+use crate::scanner::{self, IndexRules};
+use crate::thumbnails::{self, ThumbnailQueue};
+use crate::watch_fallback::WatcherSettings;
 
 // ============================================================================
 // FILE WATCHER SETUP
 // ============================================================================
 
+/// Wait this long after the last event before handling a batch
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Debounced batches that may wait for the database at once
+const BATCH_QUEUE_CAPACITY: usize = 4;
+
+/// One folder the watcher was asked to watch
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchedPath {
+    pub path: String,
+    pub watching: bool,         // Events are actually coming in for it
+    pub polling: bool,          // Watched by the polling fallback
+    pub poll_reason: Option<String>,
+    pub error: Option<String>,  // Why it isn't watched (or the last problem)
+    pub starting: bool,         // Still being set up (a polled folder is read completely first)
+}
+
+impl WatchedPath {
+    fn native(path: String) -> Self {
+        Self { path, watching: true, polling: false, poll_reason: None, error: None, starting: false }
+    }
+
+    fn failed(path: String, error: String) -> Self {
+        Self { path, watching: false, polling: false, poll_reason: None, error: Some(error), starting: false }
+    }
+
+    fn starting(path: String) -> Self {
+        Self { path, watching: false, polling: false, poll_reason: None, error: None, starting: true }
+    }
+}
+
+/// Payload of watch_status and the "watcher-status" event
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WatcherStatus {
+    pub active: bool,
+    pub paths: Vec<WatchedPath>,
+    pub events_received: u64,       // Debounced file system events
+    pub changes_emitted: u64,       // Changes sent in "file-changes" events
+    pub errors: u64,                // Errors reported while watching
+    pub last_event_at: Option<i64>, // Unix timestamp
+    pub last_error: Option<String>,
+    pub polling_fallback: bool,     // Any path on the polling fallback?
+    pub starting: bool,             // Folders are still being set up
+}
+
 #[derive(Default)]
 pub struct WatcherState {
     stop_tx: Mutex<Option<mpsc::Sender<()>>>,
+    status: Mutex<WatcherStatus>,
+    /// Bumped every time the watched folders are replaced, so a watcher
+    /// thread that was replaced while it was still starting up can't
+    /// overwrite the status of the one that replaced it
+    generation: AtomicU64,
+}
+
+impl WatcherState {
+    fn status(&self) -> WatcherStatus {
+        self.status.lock().map(|status| status.clone()).unwrap_or_default()
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut WatcherStatus)) {
+        if let Ok(mut status) = self.status.lock() {
+            update(&mut status);
+        }
+    }
+}
+
+/// Tell the frontend how the watcher is doing
+fn emit_status(app_handle: &AppHandle) {
+    let status = app_handle.state::<WatcherState>().status();
+    app_handle.emit("watcher-status", status).ok();
+}
+
+/// Turn notify's errors into something a person can act on
+fn describe_watch_error(error: &notify::Error) -> String {
+    match error.kind {
+        notify::ErrorKind::MaxFilesWatch => {
+            "Too many folders to watch (the system's watch limit was reached - on Linux, raise fs.inotify.max_user_watches)".to_string()
+        }
+        notify::ErrorKind::PathNotFound => "Folder not found".to_string(),
+        _ => error.to_string(),
+    }
 }
 
 fn stop_watching_inner(state: &WatcherState) {
@@ -40,63 +131,203 @@ fn stop_watching_inner(state: &WatcherState) {
     }
 }
 
-fn start_watching_inner(app_handle: AppHandle, watch_paths: Vec<String>) -> mpsc::Sender<()> {
+/// Start the thread that writes debounced batches to the database, one
+/// at a time and in the order they arrived. Both watchers (native and
+/// polling) send to the returned queue; the thread ends once they're gone.
+fn spawn_batch_writer(app_handle: AppHandle) -> mpsc::SyncSender<Vec<DebouncedEvent>> {
+    // BACKPRESSURE:
+    // A sync_channel holds at most BATCH_QUEUE_CAPACITY batches. When the
+    // database falls behind, send() blocks the debouncer thread, and new
+    // events pile up (and merge per path) inside the debouncer instead of
+    // in an ever-growing queue.
+    let (queue_tx, queue_rx) = mpsc::sync_channel::<Vec<DebouncedEvent>>(BATCH_QUEUE_CAPACITY);
+
+    std::thread::spawn(move || {
+        let app_handle = &app_handle;
+        let state = app_handle.state::<WatcherState>();
+
+        for events in queue_rx {
+            // Tag formats, ignore rules and scan policies are read
+            // fresh for every batch, so edits apply right away
+            // (the scanner uses the very same IndexRules)
+            let rules = IndexRules::load(&app_handle.state::<DbPool>().reader());
+
+            // Process the events
+            let event_count = events.len() as u64;
+            let change_count = handle_file_events(app_handle, &rules, events) as u64;
+
+            state.update_status(|status| {
+                status.events_received += event_count;
+                status.changes_emitted += change_count;
+                status.last_event_at = Some(system_time_to_unix(SystemTime::now()));
+            });
+            emit_status(app_handle);
+        }
+    });
+
+    queue_tx
+}
+
+/// What both watchers (native and polling) do with a debounced batch
+fn event_handler(
+    app_handle: AppHandle,
+    queue: mpsc::SyncSender<Vec<DebouncedEvent>>,
+) -> impl FnMut(Result<Vec<DebouncedEvent>, Vec<notify::Error>>) + Send + 'static {
+    // CLOSURE (like arrow function in JS):
+    // This runs whenever file events happen
+    move |result| match result {
+        Ok(events) => {
+            // Waits here while the queue is full (see spawn_batch_writer)
+            if queue.send(events).is_err() {
+                eprintln!("⚠️  File watcher batch writer stopped, dropping events");
+            }
+        }
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("❌ File watch error: {:?}", error);
+            }
+            app_handle.state::<WatcherState>().update_status(|status| {
+                status.errors += errors.len() as u64;
+                status.last_error = errors.last().map(describe_watch_error);
+                // Errors about a specific folder show up on that folder
+                for error in &errors {
+                    for watched in status.paths.iter_mut() {
+                        if error.paths.iter().any(|p| p.starts_with(&watched.path)) {
+                            watched.error = Some(describe_watch_error(error));
+                        }
+                    }
+                }
+            });
+            emit_status(&app_handle);
+        }
+    }
+}
+
+/// Start the watcher thread. Once every path is set up it reports how that
+/// went through the status (see report_watched), then keeps watching until
+/// the returned sender is used.
+///
+/// Each folder gets native events if possible, and the polling fallback
+/// (see watch_fallback.rs) if it's on a network/FUSE mount, polling is
+/// forced for it, or the native watcher can't watch it.
+fn start_watching_inner(
+    app_handle: AppHandle,
+    watch_paths: Vec<String>,
+    settings: WatcherSettings,
+    generation: u64,
+) -> mpsc::Sender<()> {
     let (stop_tx, stop_rx) = mpsc::channel::<()>();
 
     // RUST ASYNC PATTERN:
     // Spawn this on a separate thread so it doesn't block
     // Like running a background worker in JavaScript
-    tokio::task::spawn_blocking(move || {
+    // (Tauri's runtime, not tokio's directly: this also runs from setup(),
+    // before any async command has entered the runtime)
+    tauri::async_runtime::spawn_blocking(move || {
         // "move" captures app_handle by value (takes ownership)
+        let queue = spawn_batch_writer(app_handle.clone());
 
         // Create a debouncer
         // Debounce for 2 seconds (wait 2s after last event before notifying)
-        let mut debouncer = match new_debouncer(
-            Duration::from_secs(2),  // Wait time
+        let mut native_error: Option<String> = None;
+        let mut native = match new_debouncer(
+            DEBOUNCE_TIMEOUT,
             None,  // No separate queue thread
-            move |result: Result<Vec<DebouncedEvent>, Vec<notify::Error>>| {
-                // CLOSURE (like arrow function in JS):
-                // This runs whenever file events happen
-
-                match result {
-                    Ok(events) => {
-                        // Process the events
-                        for event in events {
-                            handle_file_event(&app_handle, event);
-                        }
-                    }
-                    Err(errors) => {
-                        for error in errors {
-                            eprintln!("❌ File watch error: {:?}", error);
-                        }
-                    }
-                }
-            },
+            event_handler(app_handle.clone(), queue.clone()),
         ) {
-            Ok(debouncer) => debouncer,
+            Ok(debouncer) => Some(debouncer),
             Err(e) => {
                 eprintln!("❌ Failed to create file watcher: {}", e);
-                return;
+                native_error = Some(describe_watch_error(&e));
+                None
             }
         };
+        // Only created if some folder needs it
+        let mut poller: Option<Debouncer<PollWatcher, FileIdMap>> = None;
 
         // Watch each directory recursively
+        // RecursiveMode::Recursive means: watch this directory AND all subdirectories
+        let mut watched: Vec<WatchedPath> = Vec::new();
         for path in watch_paths {
             let path_obj = Path::new(&path);
 
-            if !path_obj.exists() {
-                eprintln!("⚠️  Path does not exist: {}", path);
-                continue;
-            }
-
-            match debouncer.watcher().watch(path_obj, RecursiveMode::Recursive) {
-                // RecursiveMode::Recursive means: watch this directory AND all subdirectories
+            let mut error: Option<String> = None;
+            let poll_reason = match settings.polling_reason(path_obj) {
+                Some(reason) => reason,
+                None => {
+                    let result = match native.as_mut() {
+                        Some(debouncer) => debouncer.watcher().watch(path_obj, RecursiveMode::Recursive),
+                        None => Err(notify::Error::generic(native_error.as_deref().unwrap_or_default())),
+                    };
+                    match result {
+                        Ok(_) => {
+                            println!("✅ Watching: {}", path);
+                            watched.push(WatchedPath::native(path));
+                            continue;
+                        }
+                        Err(e) => {
+                            eprintln!("❌ Failed to watch {}: {}", path, e);
+                            // A recursive watch can fail halfway through
+                            // (watch limit) - drop the half that worked
+                            if let Some(debouncer) = native.as_mut() {
+                                let _ = debouncer.watcher().unwatch(path_obj);
+                            }
+                            let described = describe_watch_error(&e);
+                            error = Some(described.clone());
+                            if !settings.auto_fallback {
+                                watched.push(WatchedPath::failed(path, described));
+                                continue;
+                            }
+                            format!("The native watcher failed: {}", described)
+                        }
+                    }
+                }
+            };
 
-                Ok(_) => println!("✅ Watching: {}", path),
-                Err(e) => eprintln!("❌ Failed to watch {}: {}", path, e),
+            // POLLING FALLBACK
+            if poller.is_none() {
+                let config = notify::Config::default().with_poll_interval(settings.poll_interval());
+                match new_debouncer_opt::<_, PollWatcher, FileIdMap>(
+                    DEBOUNCE_TIMEOUT,
+                    None,
+                    event_handler(app_handle.clone(), queue.clone()),
+                    FileIdMap::new(),
+                    config,
+                ) {
+                    Ok(debouncer) => poller = Some(debouncer),
+                    Err(e) => eprintln!("❌ Failed to create polling watcher: {}", e),
+                }
+            }
+            // The first poll reads the whole folder, later ones compare with it
+            let result = match poller.as_mut() {
+                Some(debouncer) => debouncer.watcher().watch(path_obj, RecursiveMode::Recursive),
+                None => Err(notify::Error::generic("The polling watcher couldn't be created")),
+            };
+            match result {
+                Ok(_) => {
+                    println!("🔁 Polling every {}s: {} ({})", settings.poll_interval().as_secs(), path, poll_reason);
+                    watched.push(WatchedPath {
+                        path,
+                        watching: true,
+                        polling: true,
+                        poll_reason: Some(poll_reason),
+                        error,
+                        starting: false,
+                    });
+                }
+                Err(e) => {
+                    eprintln!("❌ Failed to poll {}: {}", path, e);
+                    watched.push(WatchedPath::failed(path, error.unwrap_or_else(|| describe_watch_error(&e))));
+                }
             }
         }
 
+        let any_watched = watched.iter().any(|w| w.watching);
+        report_watched(&app_handle, generation, watched);
+        if !any_watched {
+            return;  // Nothing to keep alive
+        }
+
         println!("👀 File watcher active!");
 
         // Keep the watcher alive until stop is requested.
@@ -114,12 +345,43 @@ fn start_watching_inner(app_handle: AppHandle, watch_paths: Vec<String>) -> mpsc
     stop_tx
 }
 
+/// Replace the watched folders. Returns the status right away, with the
+/// folders still `starting`; listen to "watcher-status" (or ask
+/// watch_status) for how each one went. Fails if none of them exist.
 #[tauri::command]
 pub async fn watch_set_paths(
     app_handle: AppHandle,
     state: State<'_, WatcherState>,
     paths: Vec<String>,
-) -> Result<(), String> {
+) -> Result<WatcherStatus, String> {
+    watch_paths(&app_handle, &state, paths)
+}
+
+/// Watch the enabled scan sources (from the scan_sources table).
+/// Called at startup and whenever sources are added/removed/toggled.
+pub fn restore_from_sources(app_handle: &AppHandle) -> Result<WatcherStatus, String> {
+    let paths = crate::db::get_enabled_source_paths(&app_handle.state::<DbPool>().reader())
+        .map_err(|e| format!("Failed to load scan sources: {}", e))?;
+    if paths.is_empty() {
+        println!("👀 No enabled scan sources - file watcher idle");
+    }
+    watch_paths(app_handle, &app_handle.state::<WatcherState>(), paths)
+}
+
+/// Restart the watcher on the same folders (e.g. after its settings changed)
+pub fn rewatch(app_handle: &AppHandle) -> Result<WatcherStatus, String> {
+    let state = app_handle.state::<WatcherState>();
+    let paths: Vec<String> = state.status().paths.into_iter().map(|w| w.path).collect();
+    watch_paths(app_handle, &state, paths)
+}
+
+/// Replace whatever is being watched with `paths`. Returns right away:
+/// folders that need setting up are reported as `starting`, and the
+/// watcher thread fills in how each one went (a "watcher-status" event)
+/// once it's done - reading a big network folder for polling can take a
+/// while, and nobody should wait for that. Fails only if no folder can
+/// even be tried (missing, not a directory...).
+fn watch_paths(app_handle: &AppHandle, state: &WatcherState, paths: Vec<String>) -> Result<WatcherStatus, String> {
     let mut watch_paths: Vec<String> = paths
         .into_iter()
         .map(|p| p.trim().to_string())
@@ -128,169 +390,655 @@ pub async fn watch_set_paths(
     watch_paths.sort();
     watch_paths.dedup();
 
-    stop_watching_inner(&state);
-
-    if watch_paths.is_empty() {
-        return Ok(());
-    }
+    stop_watching_inner(state);
 
     let mut valid_paths: Vec<String> = Vec::new();
+    let mut invalid_paths: Vec<WatchedPath> = Vec::new();
     for path in watch_paths {
         let path_obj = Path::new(&path);
-        if !path_obj.is_absolute() {
-            eprintln!("⚠️  Watch path must be absolute, skipping: {}", path);
-            continue;
-        }
-        if !path_obj.exists() {
-            eprintln!("⚠️  Watch path does not exist, skipping: {}", path);
-            continue;
-        }
-        if !path_obj.is_dir() {
-            eprintln!("⚠️  Watch path is not a directory, skipping: {}", path);
+        let problem = if !path_obj.is_absolute() {
+            "Watch path must be absolute"
+        } else if !path_obj.exists() {
+            "Watch path does not exist"
+        } else if !path_obj.is_dir() {
+            "Watch path is not a directory"
+        } else {
+            valid_paths.push(path);
             continue;
+        };
+        eprintln!("⚠️  {}, skipping: {}", problem, path);
+        invalid_paths.push(WatchedPath::failed(path, problem.to_string()));
+    }
+
+    // A fresh start: counters describe the current set of folders
+    let mut generation = 0;
+    state.update_status(|status| {
+        generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        *status = WatcherStatus::default();
+        status.starting = !valid_paths.is_empty();
+        status.paths = valid_paths.iter().cloned().map(WatchedPath::starting).collect();
+        status.paths.extend(invalid_paths.iter().cloned());
+        status.paths.sort_by(|a, b| a.path.cmp(&b.path));
+        set_error_counts(status);
+    });
+
+    if !valid_paths.is_empty() {
+        println!("👀 Starting file system watcher for: {:?}", valid_paths);
+        let settings = WatcherSettings::load(&app_handle.state::<DbPool>().reader());
+        let tx = start_watching_inner(app_handle.clone(), valid_paths.clone(), settings, generation);
+        if let Ok(mut guard) = state.stop_tx.lock() {
+            *guard = Some(tx);
         }
-        valid_paths.push(path);
     }
+    emit_status(app_handle);
 
-    if valid_paths.is_empty() {
-        return Err("No valid watch paths provided".to_string());
+    let status = state.status();
+    if valid_paths.is_empty() && !invalid_paths.is_empty() {
+        let errors: Vec<String> = invalid_paths
+            .iter()
+            .map(|w| format!("{}: {}", w.path, w.error.as_deref().unwrap_or("not watched")))
+            .collect();
+        return Err(format!("No folder could be watched ({})", errors.join("; ")));
     }
+    Ok(status)
+}
 
-    println!("👀 Starting file system watcher for: {:?}", valid_paths);
-    let tx = start_watching_inner(app_handle, valid_paths);
-    if let Ok(mut guard) = state.stop_tx.lock() {
-        *guard = Some(tx);
+/// The watcher thread finished setting up its folders: replace their
+/// `starting` entries with how it went, and tell the frontend
+fn report_watched(app_handle: &AppHandle, generation: u64, watched: Vec<WatchedPath>) {
+    let state = app_handle.state::<WatcherState>();
+    let mut current = false;
+    state.update_status(|status| {
+        // Replaced while starting up: this thread has been told to stop
+        if state.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        current = true;
+        for result in watched {
+            match status.paths.iter_mut().find(|w| w.path == result.path) {
+                Some(entry) => *entry = result,
+                None => status.paths.push(result),
+            }
+        }
+        status.starting = false;
+        status.active = status.paths.iter().any(|w| w.watching);
+        status.polling_fallback = status.paths.iter().any(|w| w.polling);
+        set_error_counts(status);
+    });
+    if !current {
+        return;
     }
 
-    Ok(())
+    let status = state.status();
+    if !status.active {
+        eprintln!("⚠️  No folder could be watched: {}", status.last_error.as_deref().unwrap_or("unknown error"));
+    }
+    emit_status(app_handle);
+}
+
+/// errors / last_error from the per-folder errors
+fn set_error_counts(status: &mut WatcherStatus) {
+    status.last_error = status.paths.iter().rev().find_map(|w| w.error.clone());
+    status.errors = status.paths.iter().filter(|w| w.error.is_some()).count() as u64;
 }
 
 #[tauri::command]
-pub async fn watch_stop(state: State<'_, WatcherState>) -> Result<(), String> {
+pub async fn watch_stop(app_handle: AppHandle, state: State<'_, WatcherState>) -> Result<(), String> {
     stop_watching_inner(&state);
+    state.update_status(|status| {
+        // A watcher thread still starting up mustn't report in afterwards
+        state.generation.fetch_add(1, Ordering::SeqCst);
+        *status = WatcherStatus::default();
+    });
+    emit_status(&app_handle);
     Ok(())
 }
 
+/// Which folders are watched, their errors, and event counts
+#[tauri::command]
+pub async fn watch_status(state: State<'_, WatcherState>) -> Result<WatcherStatus, String> {
+    Ok(state.status())
+}
+
 // ============================================================================
 // EVENT HANDLING
 // ============================================================================
 
-fn handle_file_event(app_handle: &AppHandle, event: DebouncedEvent) {
-    // PATTERN MATCHING on event kind
-    // Different event types require different actions
+/// Version of the "file-changes" payload. Bump it whenever the shape of
+/// FileChanges/FileChange changes, so the frontend can tell.
+pub const FILE_CHANGES_VERSION: u32 = 1;
 
-    // DebouncedEvent wraps the notify event with additional metadata
-    use notify::EventKind;
+/// What happened to a path - from a watcher event, or found by
+/// reconciliation (reconcile.rs) for changes made while Aurora was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]  // "created", "modified", ... in JSON
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,  // Moved, with the same row (only produced by handle_renames)
+}
+
+/// One change in a "file-changes" event
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    /// The row as saved, with its database id. For removed files it's the
+    /// row as it was just before it was deleted.
+    pub file: Option<FileInfo>,
+    /// Where a renamed file used to be
+    pub previous_path: Option<String>,
+}
+
+/// Payload of the "file-changes" event: everything that changed during one
+/// debounce window, at most one change per path
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChanges {
+    pub version: u32,
+    pub changes: Vec<FileChange>,
+}
 
-    match event.event.kind {
-        EventKind::Create(_) => {
-            // New file created!
-            println!("📝 File created: {:?}", event.paths);
+/// Collects the changes of one debounce window, merging changes to the
+/// same path so the frontend sees the end result:
+///   created + modified -> created      created + removed -> (nothing)
+///   modified + removed -> removed      removed + created -> modified
+///   a -> b renamed + b modified -> a -> b renamed (with the fresh row)
+///
+/// A `git checkout` can be thousands of events, so changes are found by
+/// path through `index` instead of searching the list. Dropped changes
+/// leave a None behind, so the other positions stay valid.
+#[derive(Default)]
+struct ChangeSet {
+    changes: Vec<Option<FileChange>>,
+    index: HashMap<String, usize>,  // path -> position in `changes`
+}
 
-            // TODO in Phase 3:
-            // 1. Extract file metadata
-            // 2. Insert into database
-            // 3. Emit event to frontend to update UI
+impl ChangeSet {
+    /// Take the change to `path` out of the set
+    fn take(&mut self, path: &str) -> Option<FileChange> {
+        let i = self.index.remove(path)?;
+        self.changes[i].take()
+    }
 
-            if let Ok(mut conn) = crate::db::get_connection(app_handle) {
-                for path in &event.paths {
-                    if let Some(file_info) = path_to_file_info(path) {
-                        let _ = crate::db::upsert_files(&mut conn, &[file_info]);
+    fn push(&mut self, mut change: FileChange) {
+        // A rename continues whatever happened at its old path
+        if change.kind == ChangeKind::Renamed {
+            if let Some(earlier) = change.previous_path.as_deref().and_then(|p| self.take(p)) {
+                match earlier.kind {
+                    // Still new to the frontend, just somewhere else
+                    ChangeKind::Created => {
+                        change.kind = ChangeKind::Created;
+                        change.previous_path = None;
                     }
+                    // a -> b, then b -> c: the frontend only knows a
+                    ChangeKind::Renamed => change.previous_path = earlier.previous_path,
+                    ChangeKind::Modified | ChangeKind::Removed => {}
                 }
             }
+        }
 
-            // Emit to frontend
-            let paths: Vec<String> = event.paths.iter()
-                .map(|p| p.to_string_lossy().to_string())
-                .collect();
-            app_handle.emit("file-created", paths).ok();
-            // .ok() converts Result to Option (we don't care if emit fails)
+        let Some(earlier) = self.index.get(&change.path).and_then(|&i| self.changes[i].as_mut()) else {
+            self.index.insert(change.path.clone(), self.changes.len());
+            self.changes.push(Some(change));
+            return;
+        };
+        match (earlier.kind, change.kind) {
+            // Came and went within one window - the frontend never saw it
+            (ChangeKind::Created, ChangeKind::Removed) => {
+                self.take(&change.path);
+            }
+            // Newer row, same story
+            (ChangeKind::Created | ChangeKind::Renamed, ChangeKind::Modified) => {
+                if change.file.is_some() {
+                    earlier.file = change.file;
+                }
+            }
+            // Deleted and written again (an editor's "safe save")
+            (ChangeKind::Removed, ChangeKind::Created | ChangeKind::Modified) => {
+                earlier.kind = ChangeKind::Modified;
+                earlier.file = change.file;
+            }
+            // a -> b, then b deleted: the frontend only knows a, so that's
+            // what was removed
+            (ChangeKind::Renamed, ChangeKind::Removed) if earlier.previous_path.is_some() => {
+                let previous = earlier.previous_path.take().unwrap_or_default();
+                self.take(&change.path);
+                match self.index.get(&previous).and_then(|&i| self.changes[i].as_mut()) {
+                    // A new file took a's place meanwhile: a was replaced
+                    Some(replacement) if replacement.kind == ChangeKind::Created => {
+                        replacement.kind = ChangeKind::Modified;
+                    }
+                    _ => {
+                        change.path = previous;
+                        self.push(change);
+                    }
+                }
+            }
+            _ => *earlier = change,
+        }
+    }
+
+    fn extend(&mut self, changes: Vec<FileChange>) {
+        for change in changes {
+            self.push(change);
         }
+    }
 
-        EventKind::Modify(_) => {
-            // File modified!
-            println!("✏️  File modified: {:?}", event.paths);
+    /// The merged changes, in the order their paths first changed
+    fn into_changes(self) -> Vec<FileChange> {
+        self.changes.into_iter().flatten().collect()
+    }
+}
 
-            // TODO in Phase 3:
-            // 1. Update modified_at timestamp in database
-            // 2. Regenerate thumbnail if needed
-            // 3. Emit event to frontend
+/// What one watcher event means for the index
+enum EventAction {
+    Change(ChangeKind),
+    Rename(PathBuf, PathBuf),  // from, to
+}
 
-            if let Ok(mut conn) = crate::db::get_connection(app_handle) {
-                for path in &event.paths {
-                    if let Some(file_info) = path_to_file_info(path) {
-                        let _ = crate::db::upsert_files(&mut conn, &[file_info]);
-                    }
-                }
+/// Handle one debounced batch of events, then send the frontend ONE
+/// "file-changes" event describing all of it.
+///
+/// RENAMES:
+/// A rename used to look like "delete + create", which threw away the row
+/// (open count, tile position, tags, metadata...). Now renames are found
+/// first and update the existing row's path:
+/// - The debouncer pairs both halves of a rename into one
+///   Modify(Name(Both)) event with [from, to] paths
+/// - A delete + create of the same file (same inode) is a move as well
+///
+/// The whole batch is written in ONE transaction (a `git checkout` can be
+/// thousands of events). Returns how many changes were sent.
+fn handle_file_events(app_handle: &AppHandle, rules: &IndexRules, events: Vec<DebouncedEvent>) -> usize {
+    let mut renames: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut changes: Vec<(ChangeKind, Vec<PathBuf>)> = Vec::new();
+    for event in events {
+        match classify(&event) {
+            Some(EventAction::Rename(from, to)) => renames.push((from, to)),
+            // Runs of the same kind are applied together (keeps the order)
+            Some(EventAction::Change(kind)) => match changes.last_mut() {
+                Some((last_kind, paths)) if *last_kind == kind => paths.extend(event.event.paths),
+                _ => changes.push((kind, event.event.paths)),
+            },
+            None => {}  // Other events (access, etc.) - we don't care about these for now
+        }
+    }
+
+    // Renames go first: a later "removed" in this batch must not delete a
+    // row that was really just moved. Inode pairing runs after the paired
+    // renames, so a file moved out of a just-renamed folder is found
+    // under the folder's new path.
+    let result = write_batch(app_handle, |batch| {
+        let mut change_set = ChangeSet::default();
+        change_set.extend(handle_renames(batch, rules, renames));
+        let mut inode_renames = Vec::new();
+        pair_by_inode(batch, &mut inode_renames, &mut changes);
+        change_set.extend(handle_renames(batch, rules, inode_renames));
+
+        for (kind, paths) in changes {
+            change_set.extend(handle_file_event(batch, rules, kind, paths));
+        }
+        change_set
+    });
+    let change_set = match result {
+        Ok(change_set) => change_set,
+        Err(e) => {
+            // Nothing was saved, so there's nothing to tell the frontend
+            eprintln!("❌ Failed to save file changes: {}", e);
+            return 0;
+        }
+    };
+
+    let changes = change_set.into_changes();
+    let change_count = changes.len();
+    if change_count > 0 {
+        // Emit to frontend
+        let payload = FileChanges {
+            version: FILE_CHANGES_VERSION,
+            changes,
+        };
+        app_handle.emit("file-changes", payload).ok();
+        // .ok() converts Result to Option (we don't care if emit fails)
+    }
+    change_count
+}
+
+fn classify(event: &DebouncedEvent) -> Option<EventAction> {
+    // PATTERN MATCHING on event kind
+    // Different event types require different actions
+
+    // DebouncedEvent wraps the notify event with additional metadata
+    use notify::event::{ModifyKind, RenameMode};
+    use notify::EventKind;
+
+    let kind = match event.event.kind {
+        EventKind::Create(_) => ChangeKind::Created,
+        EventKind::Remove(_) => ChangeKind::Removed,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            return Some(EventAction::Rename(event.paths[0].clone(), event.paths[1].clone()));
+        }
+        // Only one half of a rename: the path is where the file went to
+        // (or left from). Unknown halves are decided by looking at the disk.
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => ChangeKind::Created,
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => ChangeKind::Removed,
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            if event.paths.first().is_some_and(|p| p.exists()) {
+                ChangeKind::Created
+            } else {
+                ChangeKind::Removed
             }
+        }
+        EventKind::Modify(_) => ChangeKind::Modified,
+        _ => return None,
+    };
+    Some(EventAction::Change(kind))
+}
 
-            let paths: Vec<String> = event.paths.iter()
-                .map(|p| p.to_string_lossy().to_string())
-                .collect();
-            app_handle.emit("file-modified", paths).ok();
+/// Turn "created" paths whose inode we already know (under a path that's
+/// gone now) into renames, and drop both halves from `changes`
+fn pair_by_inode(
+    batch: &WriteBatch,
+    renames: &mut Vec<(PathBuf, PathBuf)>,
+    changes: &mut [(ChangeKind, Vec<PathBuf>)],
+) {
+    let mut moved_from: HashSet<PathBuf> = HashSet::new();
+    let mut moved_to: HashSet<PathBuf> = HashSet::new();
+    let created = changes
+        .iter()
+        .filter(|(kind, _)| *kind == ChangeKind::Created)
+        .flat_map(|(_, paths)| paths);
+    for path in created {
+        let Some(old_path) = moved_row_path(batch, path).map(PathBuf::from) else {
+            continue;
+        };
+        if moved_from.insert(old_path.clone()) {
+            moved_to.insert(path.clone());
+            renames.push((old_path, path.clone()));
         }
+    }
+
+    if moved_to.is_empty() {
+        return;
+    }
+    for (kind, paths) in changes.iter_mut() {
+        match kind {
+            ChangeKind::Created => paths.retain(|p| !moved_to.contains(p)),
+            _ => paths.retain(|p| !moved_from.contains(p)),
+        }
+    }
+}
 
-        EventKind::Remove(_) => {
-            // File deleted!
-            println!("🗑️  File removed: {:?}", event.paths);
+fn handle_renames(batch: &mut WriteBatch, rules: &IndexRules, renames: Vec<(PathBuf, PathBuf)>) -> Vec<FileChange> {
+    let mut changes: Vec<FileChange> = Vec::new();
 
-            // TODO in Phase 3:
-            // 1. Remove from database
-            // 2. Delete cached thumbnail
-            // 3. Emit event to frontend
+    for (from, to) in renames {
+        let is_dir = to.is_dir();
+        let from_ignored = rules.is_ignored(&from, is_dir);
 
-            if let Ok(conn) = crate::db::get_connection(app_handle) {
-                for path in &event.paths {
-                    let path_str = path.to_string_lossy().to_string();
-                    let _ = crate::db::delete_file(&conn, &path_str);
-                }
+        // Moved somewhere we don't index (into node_modules, a hidden
+        // folder, out of the size limits...) - same as deleting it
+        if rules.is_ignored(&to, is_dir) || (!is_dir && !rules.wants(&to)) {
+            if !from_ignored {
+                changes.extend(handle_file_event(batch, rules, ChangeKind::Removed, vec![from]));
             }
+            continue;
+        }
 
-            let paths: Vec<String> = event.paths.iter()
-                .map(|p| p.to_string_lossy().to_string())
-                .collect();
-            app_handle.emit("file-removed", paths).ok();
+        let mut old_path = from.to_string_lossy().to_string();
+        let new_path = to.to_string_lossy().to_string();
+        let mut moved = rename_rows(batch, &old_path, &new_path);
+        if moved.is_empty() {
+            // notify can name a file by its folder's path from BEFORE the
+            // folder was renamed - the inode still knows where the row is
+            if let Some(indexed) = moved_row_path(batch, &to) {
+                moved = rename_rows(batch, &indexed, &new_path);
+                old_path = indexed;
+            }
+        }
+        if moved.is_empty() {
+            // We never indexed the old path (e.g. an editor's temp file
+            // being saved over the real one) - treat it as a new file
+            changes.extend(handle_file_event(batch, rules, ChangeKind::Created, vec![to]));
+            continue;
         }
 
-        _ => {
-            // Other events (access, etc.) - we don't care about these for now
+        println!("🚚 File renamed: {} -> {} ({} row(s))", old_path, new_path, moved.len());
+        // Refresh name, type, size and tags from the file at its new path
+        apply_changes(batch, rules, ChangeKind::Modified, &[to]);
+
+        // One change per moved row (a folder's files all moved with it)
+        let new_paths: Vec<String> = moved.iter().map(|(_, new)| new.clone()).collect();
+        let mut rows = crate::db::get_files_by_paths(&batch.tx, &new_paths).unwrap_or_default();
+        for (old, new) in moved {
+            let file = rows.iter().position(|f| f.path == new).map(|i| rows.swap_remove(i));
+            changes.push(FileChange {
+                kind: ChangeKind::Renamed,
+                path: new,
+                file,
+                previous_path: Some(old),
+            });
         }
     }
+
+    changes
 }
 
-fn system_time_to_unix(time: std::time::SystemTime) -> i64 {
-    time.duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs() as i64
+fn rename_rows(batch: &mut WriteBatch, from: &str, to: &str) -> Vec<(String, String)> {
+    crate::db::rename_path(&mut batch.tx, from, to).unwrap_or_else(|e| {
+        eprintln!("❌ Failed to rename {} -> {}: {}", from, to, e);
+        Vec::new()
+    })
 }
 
-fn path_to_file_info(path: &Path) -> Option<FileInfo> {
-    if !path.exists() || !path.is_file() {
+/// The indexed path of the file now at `path`, if that's somewhere else
+/// that no longer exists (i.e. the file was moved)
+fn moved_row_path(batch: &WriteBatch, path: &Path) -> Option<String> {
+    let inode = std::fs::metadata(path).ok().and_then(|m| scanner::inode_of(&m))?;
+    let indexed = crate::db::find_path_by_inode(&batch.tx, inode).ok()??;
+    // Same inode at a path that still exists is a hard link, not a move
+    let indexed_path = Path::new(&indexed);
+    (indexed_path != path && !indexed_path.exists()).then_some(indexed)
+}
+
+fn handle_file_event(batch: &mut WriteBatch, rules: &IndexRules, kind: ChangeKind, paths: Vec<PathBuf>) -> Vec<FileChange> {
+    // Drop ignored paths (node_modules, .git, ...) before doing anything -
+    // the frontend shouldn't hear about files a scan would skip either.
+    // (A removed path no longer exists, so is_dir() is false; folder rules
+    // like "node_modules/" still match through its parent folders.)
+    let mut event_paths: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|p| !rules.is_ignored(p, p.is_dir()))
+        .collect();
+    if event_paths.is_empty() {
+        return Vec::new();
+    }
+    if kind == ChangeKind::Created {
+        event_paths = with_folder_contents(event_paths, rules);
+    }
+
+    match kind {
+        ChangeKind::Created => println!("📝 File created: {}", describe_paths(&event_paths)),
+        ChangeKind::Modified => println!("✏️  File modified: {}", describe_paths(&event_paths)),
+        ChangeKind::Removed => println!("🗑️  File removed: {}", describe_paths(&event_paths)),
+        ChangeKind::Renamed => {}
+    }
+
+    apply_changes(batch, rules, kind, &event_paths)
+}
+
+/// Add the files inside newly created folders. An unzip or checkout writes
+/// them before the watcher has started watching the new folder, so their
+/// own events may never come (and a folder moved in from outside the
+/// watched area arrives as ONE event).
+fn with_folder_contents(paths: Vec<PathBuf>, rules: &IndexRules) -> Vec<PathBuf> {
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut all: Vec<PathBuf> = Vec::new();
+    let mut folders: Vec<PathBuf> = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            folders.push(path.clone());
+        }
+        if seen.insert(path.clone()) {
+            all.push(path);
+        }
+    }
+
+    while let Some(folder) = folders.pop() {
+        let Ok(entries) = std::fs::read_dir(&folder) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // file_type() doesn't follow symlinks, so a link loop can't trap us
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if rules.is_ignored(&path, is_dir) {
+                continue;
+            }
+            if is_dir {
+                folders.push(path.clone());
+            }
+            if seen.insert(path.clone()) {
+                all.push(path);
+            }
+        }
+    }
+
+    all
+}
+
+/// A short list for the log (an unzip can touch thousands of files)
+fn describe_paths(paths: &[PathBuf]) -> String {
+    const SHOWN: usize = 5;
+    if paths.len() <= SHOWN {
+        return format!("{:?}", paths);
+    }
+    format!("{:?} and {} more", &paths[..SHOWN], paths.len() - SHOWN)
+}
+
+// ============================================================================
+// BATCHED WRITES
+// ============================================================================
+
+/// The database side of one batch of changes: ONE write transaction for
+/// all of it, and the thumbnails to queue once it's committed
+pub(crate) struct WriteBatch<'conn> {
+    tx: rusqlite::Transaction<'conn>,
+    thumbnails: Vec<String>,
+}
+
+/// Run `work` inside one write transaction, commit it, then queue the
+/// thumbnails it asked for. If the commit fails nothing was saved.
+pub(crate) fn write_batch<T>(
+    app_handle: &AppHandle,
+    work: impl FnOnce(&mut WriteBatch) -> T,
+) -> Result<T, String> {
+    let pool = app_handle.state::<DbPool>();
+    // The writer is released at the end of this block, before the
+    // thumbnail workers need it
+    let (result, thumbnails) = {
+        let mut conn = pool.writer();
+        let tx = conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let mut batch = WriteBatch { tx, thumbnails: Vec::new() };
+
+        let result = work(&mut batch);
+
+        let WriteBatch { tx, thumbnails } = batch;
+        tx.commit().map_err(|e| format!("Failed to commit changes: {}", e))?;
+        (result, thumbnails)
+    };
+
+    // New photos get a thumbnail in the background (and edited
+    // photos hash differently, so they get a fresh one)
+    if !thumbnails.is_empty() {
+        app_handle
+            .state::<ThumbnailQueue>()
+            .enqueue(thumbnails, thumbnails::DEFAULT_SIZES);
+        // Changed files lost their hashes and text, new ones may be
+        // duplicates or have text to index
+        app_handle.state::<HashQueue>().wake();
+        app_handle.state::<ContentQueue>().wake();
+    }
+    Ok(result)
+}
+
+/// Bring the database in line with changed paths (and queue thumbnails).
+/// Watcher events and reconciliation both go through here, so a file
+/// changed while Aurora was closed is handled exactly like a live change.
+/// Returns a change (with its row) for every path that is, or was, indexed.
+pub(crate) fn apply_changes(
+    batch: &mut WriteBatch,
+    rules: &IndexRules,
+    kind: ChangeKind,
+    changed: &[PathBuf],
+) -> Vec<FileChange> {
+    let mut paths: Vec<String> = changed.iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+
+    // "Created" at a path we already have a row for (an editor saving
+    // through a temp file) is a modification as far as the index goes
+    let mut already_indexed: HashSet<String> = HashSet::new();
+
+    // Reads go through the batch's own transaction, so they see what
+    // this batch already wrote (another connection wouldn't, yet)
+    let rows = if kind == ChangeKind::Removed {
+        // A deleted folder arrives as ONE event - everything indexed
+        // inside it is gone too
+        let inside: Vec<String> = paths
+            .iter()
+            .filter_map(|path| crate::db::get_file_stamps_under(&batch.tx, path).ok())
+            .flat_map(|stamps| stamps.into_keys())
+            .collect();
+        paths.extend(inside);
+
+        // Read the rows while they still exist, so the frontend gets ids
+        let rows = crate::db::get_files_by_paths(&batch.tx, &paths).unwrap_or_default();
+        if let Err(e) = crate::db::delete_files_in_tx(&batch.tx, &paths) {
+            eprintln!("❌ Failed to remove files: {}", e);
+        }
+        rows
+    } else {
+        if kind == ChangeKind::Created {
+            already_indexed = crate::db::get_files_by_paths(&batch.tx, &paths)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|file| file.path)
+                .collect();
+        }
+
+        let files: Vec<FileInfo> = changed
+            .iter()
+            .filter_map(|path| path_to_file_info(path, rules))
+            .collect();
+        if let Err(e) = crate::db::upsert_files_in_tx(&batch.tx, &files) {
+            eprintln!("❌ Failed to save files: {}", e);
+        }
+        batch.thumbnails.extend(paths.iter().cloned());
+
+        crate::db::get_files_by_paths(&batch.tx, &paths).unwrap_or_default()
+    };
+
+    rows.into_iter()
+        .map(|file| FileChange {
+            kind: if already_indexed.contains(&file.path) { ChangeKind::Modified } else { kind },
+            path: file.path.clone(),
+            file: Some(file),
+            previous_path: None,
+        })
+        .collect()
+}
+
+fn path_to_file_info(path: &Path, rules: &IndexRules) -> Option<FileInfo> {
+    // Same ignore rules and scan policy as the scanner, so scanning and
+    // watching agree (this also skips folders unless the policy indexes them)
+    if !rules.wants(path) {
         return None;
     }
 
     let metadata = std::fs::metadata(path).ok()?;
-    let path_str = path.to_string_lossy().to_string();
-    let name = path.file_name()?.to_string_lossy().to_string();
-    let file_type = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
-
-    let created_at = metadata.created().ok().map(system_time_to_unix).unwrap_or(0);
-    let modified_at = metadata.modified().ok().map(system_time_to_unix).unwrap_or(0);
-
-    Some(FileInfo {
-        id: None,
-        path: path_str,
-        name,
-        file_type,
-        size: metadata.len(),
-        created_at,
-        modified_at,
-        last_opened_at: None,
-        thumbnail_path: None,
-        finder_tags: Vec::new(),
-        finder_colors: Vec::new(),
-    })
+    Some(scanner::file_info_for(path, &metadata, &rules.tag_backends))
 }
 
 // ============================================================================
@@ -304,8 +1052,8 @@ fn path_to_file_info(path: &Path) -> Option<FileInfo> {
 //
 // import { listen } from '@tauri-apps/api/event'
 //
-// listen('file-created', (event) => {
-//   console.log('New file:', event.payload)
+// listen('file-changes', (event) => {
+//   console.log('Changed:', event.payload.changes)
 //   // Update your UI!
 // })
 //
@@ -323,7 +1071,7 @@ fn path_to_file_info(path: &Path) -> Option<FileInfo> {
 //    - "move" keyword transfers ownership into closure
 //
 // 2. THREADING:
-//    - tokio::task::spawn_blocking() runs code on a thread pool
+//    - tauri::async_runtime::spawn_blocking() runs code on a thread pool
 //    - std::thread::sleep() pauses the thread
 //    - Prevents blocking the main UI thread
 //
@@ -353,3 +1101,175 @@ fn path_to_file_info(path: &Path) -> Option<FileInfo> {
 // For now, they just log and emit to frontend
 //
 // ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paths in each group of the generated stream
+    const GROUP: usize = 1_000;
+
+    fn change(kind: ChangeKind, path: &str, previous_path: Option<&str>, size: u64) -> FileChange {
+        let file = (kind != ChangeKind::Removed).then(|| FileInfo {
+            id: None,
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            file_type: "txt".to_string(),
+            size,
+            created_at: 0,
+            modified_at: 0,
+            last_opened_at: None,
+            thumbnail_path: None,
+            finder_tags: Vec::new(),
+            finder_colors: Vec::new(),
+            metadata: None,
+            inode: None,
+            changed_at: None,
+        });
+        FileChange { kind, path: path.to_string(), file, previous_path: previous_path.map(String::from) }
+    }
+
+    /// 12,000 changes to 10,000 paths, in the order a busy debounce window
+    /// could deliver them: every step goes through all groups before the
+    /// next step, so changes to one path are far apart
+    fn event_stream() -> Vec<FileChange> {
+        let mut steps: Vec<Vec<FileChange>> = vec![Vec::new(); 2];
+        for i in 0..GROUP {
+            // created, modified -> created (with the newer row)
+            let path = format!("/w/new/{i}.txt");
+            steps[0].push(change(ChangeKind::Created, &path, None, 1));
+            steps[1].push(change(ChangeKind::Modified, &path, None, 3));
+
+            // created, removed -> nothing
+            let path = format!("/w/temp/{i}.txt");
+            steps[0].push(change(ChangeKind::Created, &path, None, 1));
+            steps[1].push(change(ChangeKind::Removed, &path, None, 0));
+
+            // removed, created ("safe save") -> modified
+            let path = format!("/w/saved/{i}.txt");
+            steps[0].push(change(ChangeKind::Removed, &path, None, 0));
+            steps[1].push(change(ChangeKind::Created, &path, None, 7));
+
+            // a -> b, b -> c -> a -> c renamed
+            let (a, b, c) = (format!("/w/a/{i}.txt"), format!("/w/b/{i}.txt"), format!("/w/c/{i}.txt"));
+            steps[0].push(change(ChangeKind::Renamed, &b, Some(&a), 1));
+            steps[1].push(change(ChangeKind::Renamed, &c, Some(&b), 1));
+
+            // created, then renamed -> created at the new path
+            let (from, to) = (format!("/w/draft/{i}.txt"), format!("/w/final/{i}.txt"));
+            steps[0].push(change(ChangeKind::Created, &from, None, 1));
+            steps[1].push(change(ChangeKind::Renamed, &to, Some(&from), 1));
+
+            // renamed, then removed -> removed at the old path
+            let (from, to) = (format!("/w/gone/{i}.txt"), format!("/w/moved/{i}.txt"));
+            steps[0].push(change(ChangeKind::Renamed, &to, Some(&from), 1));
+            steps[1].push(change(ChangeKind::Removed, &to, None, 0));
+        }
+        steps.concat()
+    }
+
+    #[test]
+    fn coalesces_a_generated_stream_of_12k_events() {
+        let events = event_stream();
+        assert_eq!(events.len(), 12 * GROUP);
+
+        let mut change_set = ChangeSet::default();
+        change_set.extend(events);
+        let changes = change_set.into_changes();
+
+        // new + saved first (their paths changed first), then the renames,
+        // moved drafts and removals, which end up where they were last pushed
+        assert_eq!(changes.len(), 5 * GROUP);
+        let by_path: HashMap<&str, &FileChange> = changes.iter().map(|c| (c.path.as_str(), c)).collect();
+        assert_eq!(by_path.len(), changes.len(), "one change per path");
+
+        for i in 0..GROUP {
+            let new = by_path[format!("/w/new/{i}.txt").as_str()];
+            assert_eq!(new.kind, ChangeKind::Created);
+            assert_eq!(new.file.as_ref().map(|f| f.size), Some(3));
+
+            assert!(!by_path.contains_key(format!("/w/temp/{i}.txt").as_str()));
+
+            let saved = by_path[format!("/w/saved/{i}.txt").as_str()];
+            assert_eq!(saved.kind, ChangeKind::Modified);
+            assert_eq!(saved.file.as_ref().map(|f| f.size), Some(7));
+
+            assert!(!by_path.contains_key(format!("/w/b/{i}.txt").as_str()));
+            let renamed = by_path[format!("/w/c/{i}.txt").as_str()];
+            assert_eq!(renamed.kind, ChangeKind::Renamed);
+            assert_eq!(renamed.previous_path.as_deref(), Some(format!("/w/a/{i}.txt").as_str()));
+
+            assert!(!by_path.contains_key(format!("/w/draft/{i}.txt").as_str()));
+            let moved = by_path[format!("/w/final/{i}.txt").as_str()];
+            assert_eq!(moved.kind, ChangeKind::Created);
+            assert_eq!(moved.previous_path, None);
+
+            assert!(!by_path.contains_key(format!("/w/moved/{i}.txt").as_str()));
+            let gone = by_path[format!("/w/gone/{i}.txt").as_str()];
+            assert_eq!(gone.kind, ChangeKind::Removed);
+            assert_eq!((gone.previous_path.as_deref(), gone.file.is_none()), (None, true));
+        }
+
+        // Paths keep the order they first changed in
+        assert_eq!(changes[0].path, "/w/new/0.txt");
+        assert_eq!(changes[1].path, "/w/saved/0.txt");
+        assert_eq!(changes[2].path, "/w/new/1.txt");
+        assert_eq!(changes[2 * GROUP].path, "/w/c/0.txt");
+        assert_eq!(changes[2 * GROUP + 1].path, "/w/final/0.txt");
+        assert_eq!(changes[2 * GROUP + 2].path, "/w/gone/0.txt");
+    }
+
+    #[test]
+    fn removing_a_renamed_file_removes_what_the_frontend_saw() {
+        // a -> b -> c, then c deleted: only a was ever shown
+        let mut change_set = ChangeSet::default();
+        change_set.push(change(ChangeKind::Renamed, "/w/b.txt", Some("/w/a.txt"), 1));
+        change_set.push(change(ChangeKind::Renamed, "/w/c.txt", Some("/w/b.txt"), 1));
+        change_set.push(change(ChangeKind::Removed, "/w/c.txt", None, 0));
+
+        // x -> y, a new x written, then y deleted: x was replaced
+        change_set.push(change(ChangeKind::Renamed, "/w/y.txt", Some("/w/x.txt"), 1));
+        change_set.push(change(ChangeKind::Created, "/w/x.txt", None, 2));
+        change_set.push(change(ChangeKind::Removed, "/w/y.txt", None, 0));
+
+        let summary: Vec<(ChangeKind, String, Option<String>)> = change_set
+            .into_changes()
+            .into_iter()
+            .map(|c| (c.kind, c.path, c.previous_path))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Removed, "/w/a.txt".to_string(), None),
+                (ChangeKind::Modified, "/w/x.txt".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_path_can_come_back_after_it_was_dropped() {
+        let mut change_set = ChangeSet::default();
+        change_set.push(change(ChangeKind::Created, "/w/x.txt", None, 1));
+        change_set.push(change(ChangeKind::Modified, "/w/other.txt", None, 1));
+        change_set.push(change(ChangeKind::Removed, "/w/x.txt", None, 0));
+        change_set.push(change(ChangeKind::Created, "/w/x.txt", None, 2));
+        change_set.push(change(ChangeKind::Modified, "/w/x.txt", None, 3));
+
+        let changes = change_set.into_changes();
+        let summary: Vec<(ChangeKind, &str, Option<u64>)> = changes
+            .iter()
+            .map(|c| (c.kind, c.path.as_str(), c.file.as_ref().map(|f| f.size)))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Modified, "/w/other.txt", Some(1)),
+                (ChangeKind::Created, "/w/x.txt", Some(3)),
+            ]
+        );
+    }
+}
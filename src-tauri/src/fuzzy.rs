@@ -0,0 +1,626 @@
+// ============================================================================
+// FUZZY MODULE - Find "resume.pdf" when the user types "resme"
+// ============================================================================
+//
+// FTS5 matches whole words (and word prefixes). Type one letter wrong or
+// skip one and it finds nothing. Fuzzy matching, as in fzf or VS Code's
+// "Go to File", only asks that the letters appear IN ORDER:
+//
+//   "resme"  ->  r e s u m e . p d f      (the u is skipped)
+//                ^ ^ ^   ^ ^
+//
+// Lots of files contain any given letters in order somewhere, so each match
+// gets a SCORE that prefers what a human meant:
+//
+// - letters right after each other ("res" in "resume") beat scattered ones
+// - letters at the start of a word ("q3 Plan", "my_plan", "myPlan") count extra
+// - every skipped letter costs a little (a gap costs more to open than to grow)
+//
+// The best score is found with dynamic programming (Smith-Waterman style):
+// for every pattern letter and every position it could match at, keep the
+// best score of any way to get there. Then we walk back to find WHICH
+// letters matched, so the UI can bold them.
+//
+// Each word must match the file's name - or, failing that, its path (so
+// "wrk plan" finds ~/work/plan.md) at half the score.
+//
+// ============================================================================
+
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::State;
+use crate::commands::FileInfo;
+use crate::db::DbPool;
+use crate::ranking::SearchRanking;
+use crate::search_query::SearchQuery;
+
+const SCORE_MATCH: i64 = 16;
+const GAP_START: i64 = -3;
+const GAP_EXTENSION: i64 = -1;
+/// First letter, or after a space or "/"
+const BONUS_BOUNDARY_WHITE: i64 = 10;
+/// After "-", "_", "." and other punctuation
+const BONUS_BOUNDARY: i64 = 8;
+/// camelCase hump, or a digit after a letter
+const BONUS_CAMEL: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+/// Where the FIRST letter lands matters most
+const FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// "Can't get here" in the score table
+const IMPOSSIBLE: i64 = i64::MIN / 2;
+
+/// Longer words are cut (the score table grows with pattern x text)
+const MAX_PATTERN_CHARS: usize = 64;
+
+/// Best-scoring files kept before ranking.rs adds frecency etc.
+const FUZZY_CANDIDATES: usize = 500;
+
+/// Hits scoring under 1/MIN_SCORE_DIVISOR of the best hit are dropped
+const MIN_SCORE_DIVISOR: i64 = 2;
+
+/// Bytes [start, end) of a matched part of a name or path
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A search result with the parts the UI should highlight
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzyMatch {
+    pub file: FileInfo,
+    pub score: i64,
+    pub name_ranges: Vec<MatchRange>,
+    pub path_ranges: Vec<MatchRange>,
+}
+
+// ============================================================================
+// MATCHING
+// ============================================================================
+
+/// One word to look for, ready to compare
+pub struct Pattern {
+    chars: Vec<char>,
+    /// Smart case: "Plan" only matches "Plan", "plan" matches both
+    case_sensitive: bool,
+}
+
+impl Pattern {
+    /// None for an empty (or all-whitespace) word
+    pub fn new(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        let case_sensitive = text.chars().any(|c| c.is_uppercase());
+        let chars = text
+            .chars()
+            .take(MAX_PATTERN_CHARS)
+            .map(|c| if case_sensitive { c } else { fold(c) })
+            .collect();
+        Some(Self { chars, case_sensitive })
+    }
+
+    fn matches(&self, pattern_char: char, text_char: char) -> bool {
+        if self.case_sensitive {
+            pattern_char == text_char
+        } else {
+            pattern_char == fold(text_char)
+        }
+    }
+}
+
+/// Lowercase one character, and drop accents from common Latin letters
+/// so "cafe" finds "café" (like FTS5's tokenizer does)
+pub(crate) fn fold(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+    match c.to_lowercase().next().unwrap_or(c) {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'ç' => 'c',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ñ' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        lower => lower,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    White,
+    Delimiter,
+    Lower,
+    Upper,
+    Digit,
+    Other,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() || c == '/' || c == '\\' {
+        CharClass::White
+    } else if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_numeric() {
+        CharClass::Digit
+    } else if c.is_alphanumeric() {
+        CharClass::Other  // Letters without case (CJK...)
+    } else {
+        CharClass::Delimiter
+    }
+}
+
+/// Extra points for matching a character of class `current` that follows one of class `previous`
+fn bonus(previous: CharClass, current: CharClass) -> i64 {
+    match (previous, current) {
+        (_, CharClass::White | CharClass::Delimiter) => 0,
+        (CharClass::White, _) => BONUS_BOUNDARY_WHITE,
+        (CharClass::Delimiter, _) => BONUS_BOUNDARY,
+        (CharClass::Lower, CharClass::Upper) => BONUS_CAMEL,
+        (CharClass::Lower | CharClass::Upper | CharClass::Other, CharClass::Digit) => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+/// Scores texts against patterns. Keeps its tables between calls, so
+/// matching 100k names doesn't allocate 100k times.
+#[derive(Default)]
+pub struct Matcher {
+    chars: Vec<(usize, char)>,
+    folded: Vec<char>,  // chars, lowercased unless the pattern is case sensitive
+    bonuses: Vec<i64>,
+    previous_row: Vec<i64>,
+    row: Vec<i64>,
+    from: Vec<usize>,
+}
+
+impl Matcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Score `text` against `pattern`, or None if the letters aren't all
+    /// there in order
+    pub fn score(&mut self, pattern: &Pattern, text: &str) -> Option<i64> {
+        self.run(pattern, text, false).map(|(score, _)| score)
+    }
+
+    /// Like score(), plus the matched bytes as ranges
+    pub fn find(&mut self, pattern: &Pattern, text: &str) -> Option<(i64, Vec<MatchRange>)> {
+        let (score, mut j) = self.run(pattern, text, true)?;
+
+        // Walk back through `from` to every matched letter
+        let (n, m) = (self.chars.len(), pattern.chars.len());
+        let mut positions = vec![0usize; m];
+        for i in (0..m).rev() {
+            positions[i] = j;
+            if i > 0 {
+                j = self.from[i * n + j];
+            }
+        }
+        Some((score, to_ranges(&self.chars, &positions)))
+    }
+
+    /// Fill the score table. Returns the best score and where the last
+    /// pattern letter matched for it. `track` fills `from` for find().
+    fn run(&mut self, pattern: &Pattern, text: &str, track: bool) -> Option<(i64, usize)> {
+        // Quick check first: most texts don't contain the letters in order
+        // at all, and this costs almost nothing
+        let mut remaining = pattern.chars.iter().peekable();
+        for c in text.chars() {
+            if remaining.next_if(|p| pattern.matches(**p, c)).is_some() && remaining.peek().is_none() {
+                break;
+            }
+        }
+        if remaining.peek().is_some() {
+            return None;
+        }
+
+        self.chars.clear();
+        self.folded.clear();
+        self.bonuses.clear();
+        let mut previous = CharClass::White;  // The start of the text counts as a boundary
+        for (offset, c) in text.char_indices() {
+            let class = char_class(c);
+            self.chars.push((offset, c));
+            self.folded.push(if pattern.case_sensitive { c } else { fold(c) });
+            self.bonuses.push(bonus(previous, class));
+            previous = class;
+        }
+        let (n, m) = (self.chars.len(), pattern.chars.len());
+
+        // row[j]: best score with the current pattern letter matched at j.
+        // from[i * n + j]: where pattern letter i-1 was matched on that best path.
+        self.previous_row.clear();
+        self.previous_row.resize(n, IMPOSSIBLE);
+        self.row.clear();
+        self.row.resize(n, IMPOSSIBLE);
+        self.from.clear();
+        if track {
+            self.from.resize(m * n, 0);
+        }
+
+        for (i, &pattern_char) in pattern.chars.iter().enumerate() {
+            // Best "previous letter matched at k, then a gap" for k <= j - 2
+            let mut gapped = (IMPOSSIBLE, 0usize);
+
+            for j in 0..n {
+                if i > 0 && j >= 2 {
+                    let opened = self.previous_row[j - 2] + GAP_START;
+                    let grown = gapped.0 + GAP_EXTENSION;
+                    gapped = if opened >= grown { (opened, j - 2) } else { (grown, gapped.1) };
+                }
+
+                self.row[j] = IMPOSSIBLE;
+                if self.folded[j] != pattern_char {
+                    continue;
+                }
+
+                if i == 0 {
+                    self.row[j] = SCORE_MATCH + self.bonuses[j] * FIRST_CHAR_MULTIPLIER;
+                    continue;
+                }
+                let consecutive = if j >= 1 && self.previous_row[j - 1] > IMPOSSIBLE {
+                    self.previous_row[j - 1] + BONUS_CONSECUTIVE
+                } else {
+                    IMPOSSIBLE
+                };
+                let (best, came_from) = if consecutive >= gapped.0 {
+                    (consecutive, j.wrapping_sub(1))
+                } else {
+                    gapped
+                };
+                if best > IMPOSSIBLE {
+                    self.row[j] = best + SCORE_MATCH + self.bonuses[j];
+                    if track {
+                        self.from[i * n + j] = came_from;
+                    }
+                }
+            }
+            std::mem::swap(&mut self.previous_row, &mut self.row);
+        }
+
+        // The best place for the last letter (earliest wins a tie)
+        let (j, score) = self
+            .previous_row
+            .iter()
+            .enumerate()
+            .fold((0, IMPOSSIBLE), |best, (j, &score)| if score > best.1 { (j, score) } else { best });
+        (score > IMPOSSIBLE).then_some((score, j))
+    }
+}
+
+/// Character positions -> merged byte ranges ("res" = one range, not three)
+fn to_ranges(chars: &[(usize, char)], positions: &[usize]) -> Vec<MatchRange> {
+    let mut ranges: Vec<MatchRange> = Vec::new();
+    for &position in positions {
+        let (start, c) = chars[position];
+        let end = start + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(MatchRange { start, end }),
+        }
+    }
+    ranges
+}
+
+// ============================================================================
+// SEARCH
+// ============================================================================
+
+/// A file whose name/path matched every word
+struct Hit {
+    id: i64,
+    score: i64,
+}
+
+/// Score one file against every word: its name, or else its path at half
+/// the score. None if any word doesn't match either.
+fn score_file(matcher: &mut Matcher, patterns: &[Pattern], name: &str, path: &str) -> Option<i64> {
+    let mut total = 0;
+    for pattern in patterns {
+        total += match matcher.score(pattern, name) {
+            Some(score) => score,
+            None => matcher.score(pattern, path)? / 2,
+        };
+    }
+    Some(total)
+}
+
+/// The same decisions as score_file, but returning what to highlight
+fn highlight_file(
+    matcher: &mut Matcher,
+    patterns: &[Pattern],
+    name: &str,
+    path: &str,
+) -> (Vec<MatchRange>, Vec<MatchRange>) {
+    let mut name_ranges = Vec::new();
+    let mut path_ranges = Vec::new();
+    for pattern in patterns {
+        if let Some((_, ranges)) = matcher.find(pattern, name) {
+            name_ranges.extend(ranges);
+        } else if let Some((_, ranges)) = matcher.find(pattern, path) {
+            path_ranges.extend(ranges);
+        }
+    }
+    name_ranges.sort_by_key(|r| r.start);
+    path_ranges.sort_by_key(|r| r.start);
+    (name_ranges, path_ranges)
+}
+
+/// Keep the `keep` best hits (highest score, then lowest id)
+fn keep_best(hits: &mut Vec<Hit>, keep: usize) {
+    if hits.len() > keep {
+        hits.select_nth_unstable_by(keep, |a, b| b.score.cmp(&a.score).then(a.id.cmp(&b.id)));
+        hits.truncate(keep);
+    }
+}
+
+/// Fuzzy-match the query's words over every file that passes its filters,
+/// then order the best ones with ranking.rs (fuzzy score as the text score)
+pub fn fuzzy_search(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    limit: usize,
+) -> Result<Vec<FuzzyMatch>, Box<dyn std::error::Error>> {
+    let patterns: Vec<Pattern> = query.words.iter().filter_map(|w| Pattern::new(w)).collect();
+    if patterns.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Pass 1: scores only, over every row
+    let mut matcher = Matcher::new();
+    let mut hits: Vec<Hit> = Vec::new();
+    crate::db::visit_search_rows(conn, query, |id, name, path| {
+        if let Some(score) = score_file(&mut matcher, &patterns, name, path) {
+            hits.push(Hit { id, score });
+            // Trim now and then, so "e" over 100k files doesn't keep 100k hits
+            if hits.len() >= FUZZY_CANDIDATES * 4 {
+                keep_best(&mut hits, FUZZY_CANDIDATES);
+            }
+        }
+    })?;
+    keep_best(&mut hits, FUZZY_CANDIDATES);
+
+    // Letters scattered all over a long path are barely a match: once
+    // something matched well, drop the hits that are far behind it
+    let best = hits.iter().map(|h| h.score).max().unwrap_or(0);
+    hits.retain(|h| h.score * MIN_SCORE_DIVISOR >= best);
+
+    // Pass 2: frecency etc. for the survivors. bm25 style: MORE NEGATIVE =
+    // better, so ranking.rs can compare each one to the best hit.
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.id.cmp(&b.id)));
+    let ids: Vec<i64> = hits.iter().map(|h| h.id).collect();
+    let scores: HashMap<i64, i64> = hits.iter().map(|h| (h.id, h.score)).collect();
+    let mut candidates = crate::db::search_signals(conn, &ids)?;
+    for candidate in &mut candidates {
+        candidate.text_score = scores.get(&candidate.id).map(|score| -(*score as f64));
+    }
+    // Same starting order as the hits, so ranking ties are stable
+    let position: HashMap<i64, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    candidates.sort_by_key(|c| position.get(&c.id).copied());
+
+    let ranking = SearchRanking::load(conn);
+    let ids = crate::ranking::rank(&candidates, &ranking, chrono::Utc::now().timestamp(), limit);
+
+    // Pass 3: highlight ranges, only for the files we return
+    let files = crate::db::get_files_by_ids(conn, &ids)?;
+    Ok(files
+        .into_iter()
+        .map(|file| {
+            let (name_ranges, path_ranges) = highlight_file(&mut matcher, &patterns, &file.name, &file.path);
+            let score = file.id.and_then(|id| scores.get(&id).copied()).unwrap_or(0);
+            FuzzyMatch { file, score, name_ranges, path_ranges }
+        })
+        .collect())
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Fuzzy search with highlight ranges (same query syntax as db_search_files)
+#[tauri::command]
+pub async fn db_fuzzy_search(
+    pool: State<'_, DbPool>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FuzzyMatch>, String> {
+    let query = SearchQuery::parse(&query)?;
+    fuzzy_search(&pool.reader(), &query, limit.unwrap_or(50).clamp(1, FUZZY_CANDIDATES))
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. Dynamic programming with two rows:
+//    - Each pattern letter only needs the previous letter's scores, so we
+//      keep two Vecs and std::mem::swap them instead of a whole table
+//
+// 2. char_indices():
+//    - Rust strings are UTF-8: "é" is 2 bytes. char_indices gives each
+//      character WITH its byte offset, so highlight ranges stay correct
+//
+// 3. select_nth_unstable_by:
+//    - Puts the best `keep` items first without fully sorting - O(n)
+//      instead of O(n log n) when all we need is "the top 500"
+//
+// 4. Closures that borrow mutably:
+//    - visit_search_rows calls our closure per row; it uses `matcher` and
+//      pushes into `hits`, both owned by the function around it
+//
+// 5. Reusing buffers:
+//    - Matcher's Vecs are clear()ed, not dropped, between texts: they keep
+//      their capacity, so after the first few files nothing is allocated
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+//
+// The benchmark is ignored by default - run it with:
+//   cargo test --release bench_fuzzy_search -- --ignored --nocapture
+// AURORA_BENCH_FILES changes the number of rows (default 100k).
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{file_info, memory_database};
+    use std::time::Instant;
+
+    fn pattern(text: &str) -> Pattern {
+        Pattern::new(text).unwrap()
+    }
+
+    fn score(pattern_text: &str, text: &str) -> Option<i64> {
+        Matcher::new().score(&pattern(pattern_text), text)
+    }
+
+    /// The matched parts of `text` as (start, end) byte pairs
+    fn ranges(pattern_text: &str, text: &str) -> Vec<(usize, usize)> {
+        let (_, ranges) = Matcher::new().find(&pattern(pattern_text), text).unwrap();
+        ranges.iter().map(|r| (r.start, r.end)).collect()
+    }
+
+    #[test]
+    fn skipped_letters_still_match() {
+        assert!(score("resme", "resume.pdf").is_some());
+        assert_eq!(ranges("resme", "resume.pdf"), vec![(0, 3), (4, 6)]);
+        assert_eq!(score("resmue", "resume.pdf"), None);
+        assert_eq!(score("xyz", "resume.pdf"), None);
+        assert!(Pattern::new("   ").is_none());
+
+        // Closer together is better
+        assert!(score("res", "resume") > score("res", "rxexs"));
+        assert!(score("resme", "resume.pdf") > score("resme", "rxesxume.pdf"));
+    }
+
+    #[test]
+    fn smart_case() {
+        // All lowercase matches any case...
+        assert!(score("plan", "Plan.md").is_some());
+        assert!(score("plan", "PLAN.md").is_some());
+        // ...one capital letter makes the whole word exact
+        assert!(score("Plan", "Plan.md").is_some());
+        assert_eq!(score("Plan", "plan.md"), None);
+        assert_eq!(score("Plan", "PLAN.md"), None);
+    }
+
+    #[test]
+    fn accents_are_folded() {
+        assert_eq!(fold('É'), 'e');
+        assert_eq!(fold('ñ'), 'n');
+        assert_eq!(fold('Q'), 'q');
+        assert_eq!(fold('ß'), 'ß');
+
+        assert_eq!(ranges("cafe", "Café.txt"), vec![(0, 5)]);
+        assert!(score("café", "cafe.txt").is_some());
+        assert_eq!(score("naive", "naïve.txt"), score("naive", "naive.txt"));
+    }
+
+    #[test]
+    fn word_starts_score_extra() {
+        // After a space or "/", after punctuation, at a camelCase hump,
+        // in the middle of a word
+        let white = score("plan", "my plan.md").unwrap();
+        let delimiter = score("plan", "my_plan.md").unwrap();
+        let camel = score("plan", "myPlan.md").unwrap();
+        let middle = score("plan", "myplan.md").unwrap();
+        assert!(white > delimiter && delimiter > camel && camel > middle, "{white} {delimiter} {camel} {middle}");
+
+        // The very start of the text is a boundary too
+        assert!(score("plan", "plan.md") > score("plan", "xplan.md"));
+        // Digits after letters are a hump ("q3")
+        assert!(score("3", "q3.pdf") > score("3", "x03.pdf"));
+        assert_eq!(bonus(CharClass::Lower, CharClass::Digit), BONUS_CAMEL);
+        assert_eq!(bonus(CharClass::Digit, CharClass::Digit), 0);
+    }
+
+    #[test]
+    fn find_walks_back_along_the_best_path() {
+        // Reading left to right, p-x-l-x-a-x-n matches first - but the
+        // whole word later on scores better, and that's what is bolded
+        let text = "pxlxaxn plan.md";
+        assert_eq!(ranges("plan", text), vec![(8, 12)]);
+        let (score, _) = Matcher::new().find(&pattern("plan"), text).unwrap();
+        assert_eq!(Some(score), Matcher::new().score(&pattern("plan"), text));
+
+        // A gap in the middle of the best path
+        assert_eq!(ranges("rprt", "report-2024.pdf"), vec![(0, 1), (2, 3), (4, 6)]);
+    }
+
+    #[test]
+    fn ranges_are_byte_offsets() {
+        // "Résumé " is 9 bytes: é is 2
+        assert_eq!(ranges("final", "Résumé final.pdf"), vec![(9, 14)]);
+        assert_eq!(ranges("rsm", "résumé.pdf"), vec![(0, 1), (3, 4), (5, 6)]);
+        assert_eq!(ranges("me", "résumé.pdf"), vec![(5, 8)]);
+        let text = "日本 notes.txt";
+        let matched: Vec<&str> = ranges("本n", text).iter().map(|&(start, end)| &text[start..end]).collect();
+        assert_eq!(matched, vec!["本", "n"]);
+
+        let chars: Vec<(usize, char)> = "aé b".char_indices().collect();
+        assert_eq!(
+            to_ranges(&chars, &[0, 1, 3]),
+            vec![MatchRange { start: 0, end: 3 }, MatchRange { start: 4, end: 5 }]
+        );
+        assert_eq!(to_ranges(&chars, &[]), vec![]);
+    }
+
+    #[test]
+    fn words_fall_back_to_the_path_at_half_the_score() {
+        let mut matcher = Matcher::new();
+        let patterns = vec![pattern("wrk"), pattern("plan")];
+        let (name, path) = ("plan.md", "/home/me/work/plan.md");
+
+        let expected = score("plan", name).unwrap() + score("wrk", path).unwrap() / 2;
+        assert_eq!(score_file(&mut matcher, &patterns, name, path), Some(expected));
+        assert_eq!(score_file(&mut matcher, &[pattern("zzz")], name, path), None);
+
+        let (name_ranges, path_ranges) = highlight_file(&mut matcher, &patterns, name, path);
+        assert_eq!(name_ranges, vec![MatchRange { start: 0, end: 4 }]);
+        assert_eq!(&path[path_ranges[0].start..path_ranges[0].end], "w");
+    }
+
+    #[test]
+    #[ignore = "benchmark: fuzzy-matches 100k rows per keystroke"]
+    fn bench_fuzzy_search() {
+        let files: usize = std::env::var("AURORA_BENCH_FILES")
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(100_000);
+        const WORDS: &[&str] = &["invoice", "resume", "holiday", "budget", "notes", "scan", "draft", "photo"];
+
+        let mut conn = memory_database();
+        let started = Instant::now();
+        let rows: Vec<FileInfo> = (0..files)
+            .map(|i| {
+                let word = WORDS[i % WORDS.len()];
+                let path = format!("/bench/{}/{}/{}-{}.pdf", WORDS[(i / 7) % WORDS.len()], i / 1000, word, i);
+                file_info(&path, i as u64, i as i64)
+            })
+            .collect();
+        for chunk in rows.chunks(10_000) {
+            crate::db::upsert_files(&mut conn, chunk).unwrap();
+        }
+        println!("🗃️  Inserted {} rows in {:.1?}", files, started.elapsed());
+
+        // What the search box sends while someone types "resme"
+        for typed in ["r", "re", "res", "resm", "resme"] {
+            let query = SearchQuery::parse(typed).unwrap();
+            let started = Instant::now();
+            let results = fuzzy_search(&conn, &query, 50).unwrap();
+            println!("⏱️  {:>6}: {} results in {:.1?}", typed, results.len(), started.elapsed());
+            assert!(!results.is_empty());
+        }
+    }
+}
@@ -0,0 +1,378 @@
+// ============================================================================
+// HASHING MODULE - Content hashes and duplicate files
+// ============================================================================
+//
+// Aurora knows files by their PATH. Two copies of the same PDF in Downloads
+// ("report.pdf", "report (1).pdf") are two unrelated rows - until we look at
+// what's inside them.
+//
+// Hashing every byte of every file would be slow, and pointless: a file can
+// only have a duplicate if another file has EXACTLY the same size. So:
+//
+// 1. Files that share their size with another file get a PARTIAL hash:
+//    BLAKE3 of the first and last 64 KB (plus the size). Cheap - even for a
+//    4 GB video it's 128 KB of reading.
+// 2. Files whose size AND partial hash match get a FULL hash of all their
+//    bytes. Only these can really be identical.
+// 3. find_duplicates groups files by full hash.
+//
+// It all runs on one background thread, woken after scans and watcher
+// batches. Hashes are cleared when a file changes (see upsert_files), so
+// the next pass hashes it again.
+//
+// ============================================================================
+
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::commands::{system_time_to_unix, FileInfo};
+use crate::db::{DbPool, HashCandidate};
+
+/// Empty files are all "identical" - not worth reporting
+const MIN_HASH_SIZE: u64 = 1;
+
+/// Bytes read from each end of a file for its partial hash
+const PARTIAL_CHUNK: u64 = 64 * 1024;
+
+/// Files hashed (and saved in one transaction) per round
+const HASH_BATCH: usize = 200;
+
+/// After a wake-up, wait this long for more (a scan wakes us once, a busy
+/// watcher many times)
+const SETTLE_DELAY: Duration = Duration::from_secs(3);
+
+/// Largest number of groups find_duplicates returns
+const MAX_GROUPS: usize = 1000;
+
+// ============================================================================
+// HASHING
+// ============================================================================
+
+#[derive(Debug, Clone, Copy)]
+enum Stage {
+    Partial,
+    Full,
+}
+
+impl Stage {
+    fn column(self) -> &'static str {
+        match self {
+            Stage::Partial => "partial_hash",
+            Stage::Full => "content_hash",
+        }
+    }
+}
+
+/// BLAKE3 of the size, the first and the last PARTIAL_CHUNK bytes
+fn partial_hash(path: &Path, size: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&size.to_le_bytes());
+
+    if size <= PARTIAL_CHUNK * 2 {
+        // Small enough that "both ends" is the whole file
+        io::copy(&mut file, &mut hasher)?;
+    } else {
+        let mut chunk = vec![0u8; PARTIAL_CHUNK as usize];
+        file.read_exact(&mut chunk)?;
+        hasher.update(&chunk);
+        file.seek(SeekFrom::End(-(PARTIAL_CHUNK as i64)))?;
+        file.read_exact(&mut chunk)?;
+        hasher.update(&chunk);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// BLAKE3 of every byte
+fn content_hash(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    // Hasher implements io::Write, so io::copy streams the file through it
+    // without loading it all into memory
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Hash one file, or None if it changed since it was indexed (the watcher
+/// will update the row, and that clears its hashes anyway)
+fn hash_file(file: &HashCandidate, stage: Stage) -> io::Result<Option<String>> {
+    let path = Path::new(&file.path);
+    let metadata = std::fs::metadata(path)?;
+    let modified_at = metadata.modified().map(system_time_to_unix).unwrap_or(0);
+    if metadata.len() != file.size || modified_at != file.modified_at {
+        return Ok(None);
+    }
+
+    let hash = match stage {
+        Stage::Partial => partial_hash(path, file.size)?,
+        Stage::Full => content_hash(path)?,
+    };
+    Ok(Some(hash))
+}
+
+/// Hash every file that needs it for one stage. Returns how many were saved.
+fn hash_stage(pool: &DbPool, stage: Stage) -> usize {
+    let mut after_id = 0i64;
+    let mut saved = 0usize;
+
+    loop {
+        let candidates = match stage {
+            Stage::Partial => crate::db::files_needing_partial_hash(&pool.reader(), MIN_HASH_SIZE, after_id, HASH_BATCH),
+            Stage::Full => crate::db::files_needing_content_hash(&pool.reader(), after_id, HASH_BATCH),
+        };
+        let candidates = match candidates {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                eprintln!("⚠️  Failed to list files to hash: {}", e);
+                break;
+            }
+        };
+        let Some(last) = candidates.last() else {
+            break;  // Nothing (more) to do
+        };
+        after_id = last.id;
+
+        // Read the files WITHOUT holding the database writer
+        let hashes: Vec<(HashCandidate, String)> = candidates
+            .into_iter()
+            .filter_map(|file| match hash_file(&file, stage) {
+                Ok(Some(hash)) => Some((file, hash)),
+                Ok(None) => None,
+                Err(e) => {
+                    eprintln!("⚠️  Failed to hash {}: {}", file.path, e);
+                    None
+                }
+            })
+            .collect();
+
+        let mut conn = pool.writer();
+        let result = conn
+            .transaction()
+            .map_err(|e| e.into())
+            .and_then(|tx| {
+                let count = crate::db::set_file_hashes(&tx, stage.column(), &hashes)?;
+                tx.commit()?;
+                Ok::<_, Box<dyn std::error::Error>>(count)
+            });
+        match result {
+            Ok(count) => saved += count,
+            Err(e) => eprintln!("⚠️  Failed to save file hashes: {}", e),
+        }
+    }
+
+    saved
+}
+
+// ============================================================================
+// BACKGROUND WORKER (Tauri managed state)
+// ============================================================================
+
+/// Payload of the "hashing-complete" event
+#[derive(Debug, Clone, Serialize)]
+pub struct HashingSummary {
+    pub partial_hashed: usize,
+    pub fully_hashed: usize,
+}
+
+pub struct HashQueue {
+    wake_tx: Mutex<mpsc::Sender<()>>,
+}
+
+impl HashQueue {
+    /// Spawn the hashing thread (it does a first pass right away).
+    /// Call once from main.rs setup().
+    pub fn start(app_handle: AppHandle) -> Self {
+        let (wake_tx, wake_rx) = mpsc::channel::<()>();
+
+        std::thread::spawn(move || loop {
+            let pool = app_handle.state::<DbPool>();
+            let summary = HashingSummary {
+                partial_hashed: hash_stage(&pool, Stage::Partial),
+                fully_hashed: hash_stage(&pool, Stage::Full),
+            };
+            if summary.partial_hashed + summary.fully_hashed > 0 {
+                println!(
+                    "#️⃣  Content hashes saved: {} partial, {} full",
+                    summary.partial_hashed, summary.fully_hashed
+                );
+                app_handle.emit("hashing-complete", summary).ok();
+            }
+
+            // Sleep until something new was indexed
+            if wake_rx.recv().is_err() {
+                break;  // Sender dropped: the app is shutting down
+            }
+            std::thread::sleep(SETTLE_DELAY);
+            while wake_rx.try_recv().is_ok() {}
+        });
+
+        Self { wake_tx: Mutex::new(wake_tx) }
+    }
+
+    /// Files were added or changed: hash whatever now needs it
+    pub fn wake(&self) {
+        if let Ok(tx) = self.wake_tx.lock() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Identical files (same size and content)
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    pub wasted_bytes: u64,  // size x (copies - 1): what deleting the extras frees
+    pub files: Vec<FileInfo>,  // Oldest first - usually the "original"
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub total_wasted_bytes: u64,
+    pub pending: usize,  // Files still waiting to be hashed (results may grow)
+}
+
+/// Groups of identical files, the ones wasting the most space first
+#[tauri::command]
+pub async fn find_duplicates(
+    pool: State<'_, DbPool>,
+    queue: State<'_, HashQueue>,
+    limit: Option<usize>,
+) -> Result<DuplicateReport, String> {
+    let limit = limit.unwrap_or(100).clamp(1, MAX_GROUPS);
+    let conn = pool.reader();
+    let groups = duplicate_groups(&conn, limit)?;
+
+    let pending = crate::db::count_files_needing_hash(&conn, MIN_HASH_SIZE)
+        .map_err(|e| format!("Failed to count unhashed files: {}", e))?;
+    if pending > 0 {
+        queue.wake();
+    }
+
+    Ok(DuplicateReport {
+        total_wasted_bytes: groups.iter().map(|g| g.wasted_bytes).sum(),
+        groups,
+        pending,
+    })
+}
+
+fn duplicate_groups(conn: &rusqlite::Connection, limit: usize) -> Result<Vec<DuplicateGroup>, String> {
+    let groups = crate::db::get_duplicate_groups(conn, limit)
+        .map_err(|e| format!("Failed to find duplicates: {}", e))?
+        .into_iter()
+        .map(|(hash, size, files)| DuplicateGroup {
+            hash,
+            size,
+            wasted_bytes: size * (files.len().saturating_sub(1) as u64),
+            files,
+        })
+        .collect();
+    Ok(groups)
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. io::copy + io::Write:
+//    - blake3::Hasher implements Write, so any Reader (a File) can be
+//      "copied into" it in small chunks - constant memory for any file size
+//
+// 2. Seek:
+//    - SeekFrom::End(-n) jumps to n bytes before the end, so reading the
+//      tail of a huge file doesn't read the middle
+//
+// 3. Copy enums:
+//    - #[derive(Clone, Copy)] on a small enum lets us pass `stage` by value
+//      as often as we like, like a number
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{pool, scan, TempDir};
+
+    /// `size` bytes of a repeating pattern, with the byte at `flip` changed
+    fn pattern(size: u64, flip: Option<u64>) -> Vec<u8> {
+        let mut data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+        if let Some(i) = flip {
+            data[i as usize] ^= 0xff;
+        }
+        data
+    }
+
+    #[test]
+    fn partial_hash_reads_the_whole_file_up_to_two_chunks() {
+        let dir = TempDir::new("hash-partial");
+        for size in [PARTIAL_CHUNK * 2 - 1, PARTIAL_CHUNK * 2, PARTIAL_CHUNK * 2 + 1] {
+            let original = dir.write(&format!("{}-a.bin", size), &pattern(size, None));
+            let middle = dir.write(&format!("{}-b.bin", size), &pattern(size, Some(PARTIAL_CHUNK)));
+            let last = dir.write(&format!("{}-c.bin", size), &pattern(size, Some(size - 1)));
+            let hash = |path: &str| partial_hash(Path::new(path), size).unwrap();
+
+            // Up to 2 x PARTIAL_CHUNK every byte counts; past that the
+            // middle is skipped (the full hash has to tell those apart)
+            let middle_counts = size <= PARTIAL_CHUNK * 2;
+            assert_eq!(hash(&original) != hash(&middle), middle_counts, "size {}", size);
+            assert_ne!(hash(&original), hash(&last), "size {}", size);
+            assert_eq!(hash(&original), hash(&original));
+            assert_ne!(content_hash(Path::new(&original)).unwrap(), content_hash(Path::new(&middle)).unwrap());
+        }
+    }
+
+    #[test]
+    fn partial_hash_includes_the_size() {
+        let dir = TempDir::new("hash-size");
+        let path = dir.write("file.bin", &pattern(100, None));
+        // Same bytes read, different size recorded: a truncated copy
+        // never matches the original
+        assert_ne!(partial_hash(Path::new(&path), 100).unwrap(), partial_hash(Path::new(&path), 99).unwrap());
+        // A file that shrank since it was indexed can't be hashed
+        assert!(partial_hash(Path::new(&path), PARTIAL_CHUNK * 3).is_err());
+    }
+
+    #[test]
+    fn identical_files_form_one_group() {
+        let dir = TempDir::new("hash-duplicates");
+        let size = PARTIAL_CHUNK * 3;
+        let original = dir.write("files/original.bin", &pattern(size, None));
+        let copy = dir.write("files/copy/original.bin", &pattern(size, None));
+        // Same size and same ends: only the full hash tells it apart
+        dir.write("files/lookalike.bin", &pattern(size, Some(size / 2)));
+        dir.write("files/other.bin", &pattern(size - 1, None));
+        dir.write("files/empty.txt", b"");
+        let pool = pool(&dir);
+        scan(&mut pool.writer(), &dir.path().join("files").to_string_lossy());
+
+        // The three same-size files get a partial hash, and as their ends
+        // all match, a full one too
+        assert_eq!(hash_stage(&pool, Stage::Partial), 3);
+        assert_eq!(hash_stage(&pool, Stage::Full), 3);
+        assert_eq!(hash_stage(&pool, Stage::Partial), 0);
+
+        let groups = duplicate_groups(&pool.reader(), 10).unwrap();
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!((group.size, group.wasted_bytes), (size, size));
+        let mut paths: Vec<&str> = group.files.iter().map(|f| f.path.as_str()).collect();
+        paths.sort();
+        let mut expected = vec![copy.as_str(), original.as_str()];
+        expected.sort();
+        assert_eq!(paths, expected);
+        assert_eq!(group.hash, content_hash(Path::new(&original)).unwrap());
+    }
+}
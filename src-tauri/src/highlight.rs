@@ -0,0 +1,439 @@
+// ============================================================================
+// HIGHLIGHT MODULE - Why did this file match?
+// ============================================================================
+//
+// A bare list of files doesn't say why "budget" found "trip.txt". Each
+// search result carries what matched, as byte ranges the UI can bold:
+//
+//   name / path   FTS5 highlight(): the whole text with every hit marked
+//   content       FTS5 snippet(): a few words of the file's text around
+//                 the best hit (contents_fts stores the text, see content.rs)
+//   notes         notes aren't in FTS, so we find the words ourselves and
+//                 cut a snippet the same way
+//
+// FTS5 marks hits with strings we choose. We use two control characters
+// that never appear in names or documents, then strip them out again and
+// remember where they were - that gives plain text plus ranges, instead of
+// HTML the UI would have to trust.
+//
+// Any word counts for highlighting, not only full matches: when "lisbon
+// budget" finds a file by its contents, "budget" is still bolded in its
+// name.
+//
+// ============================================================================
+
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::commands::FileInfo;
+use crate::fuzzy::{fold, FuzzyMatch, MatchRange};
+use crate::search_query::SearchQuery;
+
+/// Put around every hit by highlight()/snippet(), then stripped
+const MARK_START: char = '\u{2}';
+const MARK_END: char = '\u{3}';
+
+/// Where snippets were cut short
+const ELLIPSIS: &str = "…";
+
+/// Words of context in a content snippet (FTS5 allows up to 64)
+const SNIPPET_TOKENS: i64 = 16;
+
+/// Characters shown before the first hit in a notes snippet...
+const NOTES_CONTEXT_CHARS: usize = 40;
+/// ...and in the whole notes snippet
+const NOTES_SNIPPET_CHARS: usize = 120;
+
+/// Some text with the parts that matched
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    pub text: String,
+    pub ranges: Vec<MatchRange>,  // Bytes of `text` to bold
+}
+
+/// A search result with the parts the UI should highlight
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub file: FileInfo,
+    pub name_ranges: Vec<MatchRange>,  // Bytes of file.name to bold
+    pub path_ranges: Vec<MatchRange>,  // Bytes of file.path to bold
+    pub content: Option<Snippet>,      // The file's text around the match
+    pub notes: Option<Snippet>,        // Its notes around the match
+}
+
+// ============================================================================
+// MARKED TEXT -> TEXT + RANGES
+// ============================================================================
+
+/// Strip MARK_START/MARK_END out of FTS5 output, keeping where they were
+fn unmark(marked: &str) -> Snippet {
+    let mut text = String::with_capacity(marked.len());
+    let mut ranges = Vec::new();
+    let mut start = None;
+
+    for c in marked.chars() {
+        match c {
+            MARK_START => start = Some(text.len()),
+            MARK_END => {
+                if let Some(start) = start.take() {
+                    if start < text.len() {
+                        ranges.push(MatchRange { start, end: text.len() });
+                    }
+                }
+            }
+            _ => text.push(c),
+        }
+    }
+    Snippet { text, ranges }
+}
+
+/// Where any of `terms` starts a word in `text` (ignoring case and
+/// accents, like FTS5 prefix matching), sorted and merged
+fn find_terms(text: &str, terms: &[String]) -> Vec<MatchRange> {
+    let chars: Vec<(usize, char)> = text.char_indices().map(|(i, c)| (i, fold(c))).collect();
+    let mut ranges = Vec::new();
+
+    for term in terms {
+        let pattern: Vec<char> = term.chars().map(fold).collect();
+        if pattern.is_empty() || pattern.len() > chars.len() {
+            continue;
+        }
+        for start in 0..=chars.len() - pattern.len() {
+            let word_start = start == 0 || !chars[start - 1].1.is_alphanumeric();
+            let matches = chars[start..start + pattern.len()]
+                .iter()
+                .map(|(_, c)| *c)
+                .eq(pattern.iter().copied());
+            if word_start && matches {
+                let end = chars.get(start + pattern.len()).map(|(i, _)| *i).unwrap_or(text.len());
+                ranges.push(MatchRange { start: chars[start].0, end });
+            }
+        }
+    }
+
+    // Overlapping hits ("plan" and "planning") become one range
+    ranges.sort_by_key(|r| (r.start, r.end));
+    let mut merged: Vec<MatchRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// A short piece of `notes` around the first hit, or None without one
+fn notes_snippet(notes: &str, terms: &[String]) -> Option<Snippet> {
+    let ranges = find_terms(notes, terms);
+    let first = *ranges.first()?;
+
+    // Back up a little from the first hit, then take a window from there
+    // (char_indices keeps the cuts on character boundaries)
+    let start = notes[..first.start]
+        .char_indices()
+        .rev()
+        .nth(NOTES_CONTEXT_CHARS - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let end = notes[start..]
+        .char_indices()
+        .nth(NOTES_SNIPPET_CHARS)
+        .map(|(i, _)| start + i)
+        .unwrap_or(notes.len())
+        .max(first.end);
+
+    let mut text = String::new();
+    if start > 0 {
+        text.push_str(ELLIPSIS);
+    }
+    let offset = text.len();
+    text.push_str(&notes[start..end]);
+    if end < notes.len() {
+        text.push_str(ELLIPSIS);
+    }
+
+    let ranges = ranges
+        .into_iter()
+        .filter(|r| r.start >= start && r.end <= end)
+        .map(|r| MatchRange { start: r.start - start + offset, end: r.end - start + offset })
+        .collect();
+    Some(Snippet { text, ranges })
+}
+
+/// "a" OR "b" OR ...: files_fts/contents_fts rows with ANY of the terms
+fn any_of(terms: &[String]) -> Option<String> {
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" OR "))
+    }
+}
+
+// ============================================================================
+// LOADING HIGHLIGHTS
+// ============================================================================
+
+/// Highlighted (name, path) of each file with a hit in files_fts
+fn name_path_highlights(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    ids: &[i64],
+) -> Result<HashMap<i64, (Snippet, Snippet)>, Box<dyn std::error::Error>> {
+    let terms: Vec<String> = query.word_terms.iter().chain(&query.column_terms).cloned().collect();
+    let Some(fts) = any_of(&terms) else {
+        return Ok(HashMap::new());
+    };
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT rowid, highlight(files_fts, 0, ?1, ?2), highlight(files_fts, 1, ?1, ?2)
+         FROM files_fts
+         WHERE files_fts MATCH ?3 AND rowid IN (SELECT value FROM json_each(?4))",
+    )?;
+    let rows = stmt.query_map(
+        rusqlite::params![MARK_START.to_string(), MARK_END.to_string(), fts, serde_json::to_string(ids)?],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+    )?;
+
+    let mut highlights = HashMap::new();
+    for row in rows {
+        let (id, name, path) = row?;
+        highlights.insert(id, (unmark(&name), unmark(&path)));
+    }
+    Ok(highlights)
+}
+
+/// A snippet of the indexed text of each file with a hit in contents_fts
+fn content_snippets(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    ids: &[i64],
+) -> Result<HashMap<i64, Snippet>, Box<dyn std::error::Error>> {
+    let Some(fts) = any_of(&query.word_terms) else {
+        return Ok(HashMap::new());
+    };
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT rowid, snippet(contents_fts, 0, ?1, ?2, ?3, ?4)
+         FROM contents_fts
+         WHERE contents_fts MATCH ?5 AND rowid IN (SELECT value FROM json_each(?6))",
+    )?;
+    let rows = stmt.query_map(
+        rusqlite::params![
+            MARK_START.to_string(),
+            MARK_END.to_string(),
+            ELLIPSIS,
+            SNIPPET_TOKENS,
+            fts,
+            serde_json::to_string(ids)?
+        ],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+    )?;
+
+    let mut snippets = HashMap::new();
+    for row in rows {
+        let (id, marked) = row?;
+        // Line breaks and indentation from documents would make a ragged
+        // one-line preview (the marks aren't whitespace, so they survive)
+        let marked: Vec<&str> = marked.split_whitespace().collect();
+        snippets.insert(id, unmark(&marked.join(" ")));
+    }
+    Ok(snippets)
+}
+
+/// A snippet of the notes of each file whose notes contain a word (or a
+/// notes: filter value)
+fn notes_snippets(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    ids: &[i64],
+) -> Result<HashMap<i64, Snippet>, Box<dyn std::error::Error>> {
+    let terms: Vec<String> = query.words.iter().chain(&query.notes).cloned().collect();
+    if terms.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT file_id, notes FROM file_metadata
+         WHERE file_id IN (SELECT value FROM json_each(?1)) AND notes IS NOT NULL AND notes != ''",
+    )?;
+    let rows = stmt.query_map([serde_json::to_string(ids)?], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut snippets = HashMap::new();
+    for row in rows {
+        let (id, notes) = row?;
+        if let Some(snippet) = notes_snippet(&notes, &terms) {
+            snippets.insert(id, snippet);
+        }
+    }
+    Ok(snippets)
+}
+
+/// Add highlights to FTS5 search results (in the order given)
+pub fn text_results(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    files: Vec<FileInfo>,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let ids: Vec<i64> = files.iter().filter_map(|f| f.id).collect();
+    let mut names = name_path_highlights(conn, query, &ids)?;
+    let mut contents = content_snippets(conn, query, &ids)?;
+    let mut notes = notes_snippets(conn, query, &ids)?;
+
+    Ok(files
+        .into_iter()
+        .map(|file| {
+            let id = file.id.unwrap_or_default();
+            let (name_ranges, path_ranges) = match names.remove(&id) {
+                Some((name, path)) => (name.ranges, path.ranges),
+                None => (Vec::new(), Vec::new()),
+            };
+            SearchResult {
+                name_ranges,
+                path_ranges,
+                content: contents.remove(&id),
+                notes: notes.remove(&id),
+                file,
+            }
+        })
+        .collect())
+}
+
+/// Fuzzy results already know their name/path ranges; add notes snippets
+pub fn fuzzy_results(
+    conn: &rusqlite::Connection,
+    query: &SearchQuery,
+    matches: Vec<FuzzyMatch>,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    let ids: Vec<i64> = matches.iter().filter_map(|m| m.file.id).collect();
+    let mut notes = notes_snippets(conn, query, &ids)?;
+
+    Ok(matches
+        .into_iter()
+        .map(|m| SearchResult {
+            notes: m.file.id.and_then(|id| notes.remove(&id)),
+            content: None,
+            name_ranges: m.name_ranges,
+            path_ranges: m.path_ranges,
+            file: m.file,
+        })
+        .collect())
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. Byte offsets vs characters:
+//    - Rust strings are UTF-8, so "é" is 2 bytes. Ranges are byte offsets
+//      (what text.len() counts), and char_indices() gives the byte offset
+//      of each character so we never cut one in half
+//
+// 2. HashMap::remove:
+//    - Takes the value OUT of the map, so it can be moved into the result
+//      without a clone
+//
+// 3. Iterator::eq:
+//    - Compares two iterators item by item - here the folded letters of
+//      the text against the letters of a search word
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    /// The highlighted pieces of `text`, so failures read as words
+    fn bolded<'a>(text: &'a str, ranges: &[MatchRange]) -> Vec<&'a str> {
+        ranges.iter().map(|r| &text[r.start..r.end]).collect()
+    }
+
+    fn range(start: usize, end: usize) -> MatchRange {
+        MatchRange { start, end }
+    }
+
+    #[test]
+    fn marks_are_stripped_into_byte_ranges() {
+        let snippet = unmark("the \u{2}budget\u{3} for \u{2}Lisbon\u{3}");
+        assert_eq!(snippet.text, "the budget for Lisbon");
+        assert_eq!(snippet.ranges, [range(4, 10), range(15, 21)]);
+
+        // Ranges count bytes of the stripped text: "Café" is 5 bytes
+        let snippet = unmark("\u{2}Café\u{3} in \u{2}Málaga\u{3}");
+        assert_eq!(snippet.text, "Café in Málaga");
+        assert_eq!(snippet.ranges, [range(0, 5), range(9, 16)]);
+        assert_eq!(bolded(&snippet.text, &snippet.ranges), ["Café", "Málaga"]);
+    }
+
+    #[test]
+    fn empty_or_unbalanced_marks_give_no_ranges() {
+        let snippet = unmark("\u{2}\u{3}a\u{3} b \u{2}c");
+        assert_eq!(snippet.text, "a b c");
+        assert!(snippet.ranges.is_empty());
+    }
+
+    #[test]
+    fn terms_match_word_starts_ignoring_case_and_accents() {
+        let text = "Café budget, explanation: BUDGETS";
+        let ranges = find_terms(text, &terms(&["cafe", "budget", "plan"]));
+        // "plan" inside "explanation" doesn't start a word
+        assert_eq!(bolded(text, &ranges), ["Café", "budget", "BUDGET"]);
+
+        // An accented search word finds the plain spelling too
+        let text = "resume and résumé";
+        let ranges = find_terms(text, &terms(&["résumé"]));
+        assert_eq!(ranges, [range(0, 6), range(11, 19)]);
+    }
+
+    #[test]
+    fn overlapping_hits_are_merged() {
+        let text = "planning plans";
+        let ranges = find_terms(text, &terms(&["plan", "planning", "plans"]));
+        assert_eq!(ranges, [range(0, 8), range(9, 14)]);
+
+        // A hit inside another merges into it, whatever order the terms are in
+        let text = "ab-cd";
+        let ranges = find_terms(text, &terms(&["cd", "ab", "a"]));
+        assert_eq!(ranges, [range(0, 2), range(3, 5)]);
+        assert!(find_terms(text, &terms(&["", "abcdef"])).is_empty());
+    }
+
+    #[test]
+    fn notes_snippets_start_at_the_text_when_the_hit_is_early() {
+        let notes = "Budget for the trip";
+        let snippet = notes_snippet(notes, &terms(&["budget", "trip"])).unwrap();
+        assert_eq!(snippet.text, notes);
+        assert_eq!(bolded(&snippet.text, &snippet.ranges), ["Budget", "trip"]);
+
+        assert_eq!(notes_snippet(notes, &terms(&["lisbon"])), None);
+    }
+
+    #[test]
+    fn notes_snippets_cut_mid_text_shift_ranges_past_the_ellipsis() {
+        // 50 two-byte characters and a space before the hit, so characters
+        // and bytes disagree about where the snippet starts
+        let notes = format!("{} budget {} budget", "é".repeat(50), "x".repeat(200));
+        let snippet = notes_snippet(&notes, &terms(&["budget"])).unwrap();
+
+        // NOTES_CONTEXT_CHARS characters of context: 39 é and the space
+        let expected_start = format!("{}{} budget", ELLIPSIS, "é".repeat(39));
+        assert!(snippet.text.starts_with(&expected_start));
+        assert!(snippet.text.ends_with(ELLIPSIS));
+        assert_eq!(snippet.text.chars().count(), 1 + NOTES_SNIPPET_CHARS + 1);
+
+        // The hit sits after the 3-byte ellipsis and 78 bytes of é, plus
+        // the space; the second "budget" is past the window, so it's dropped
+        let offset = ELLIPSIS.len() + 39 * 2 + 1;
+        assert_eq!(snippet.ranges, [range(offset, offset + 6)]);
+        assert_eq!(bolded(&snippet.text, &snippet.ranges), ["budget"]);
+    }
+}
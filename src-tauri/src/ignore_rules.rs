@@ -0,0 +1,380 @@
+// ============================================================================
+// IGNORE RULES MODULE - Which files Aurora should never index
+// ============================================================================
+//
+// Nobody wants node_modules, .git or build caches showing up as tiles!
+// Aurora uses the same pattern language as .gitignore, from three places:
+//
+// 1. A global list in settings (defaults below) - applies everywhere
+// 2. `.auroraignore` files - apply to the folder they're in (and below)
+// 3. `.gitignore` files - only if the "honor .gitignore" setting is on
+//
+// Just like git, a rule in a deeper folder wins over one higher up, and a
+// `!pattern` line un-ignores something a broader rule ignored. Only ignore
+// files inside the source folder count - a ~/.gitignore doesn't get a say
+// in what ~/Documents shows.
+//
+// The scanner AND the file watcher ask the same IgnoreRules, so a file
+// that a scan skips never sneaks in through a watcher event either.
+//
+// ============================================================================
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use crate::db::DbPool;
+
+/// Per-folder ignore file Aurora always reads
+pub const AURORA_IGNORE_FILE: &str = ".auroraignore";
+
+/// Settings key for IgnoreSettings
+const IGNORE_SETTING: &str = "ignore_rules";
+
+/// Global patterns used until the user changes them
+const DEFAULT_PATTERNS: &[&str] = &[
+    ".git/",
+    "node_modules/",
+    ".cache/",
+    "__pycache__/",
+    ".venv/",
+    ".next/",
+    "target/",
+    ".DS_Store",
+    ".Trash/",
+];
+
+/// What the user can change in Settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IgnoreSettings {
+    /// gitignore-style patterns applied in every folder
+    pub patterns: Vec<String>,
+    /// Also read `.gitignore` files (off by default - a project's build
+    /// output might be exactly what someone wants to find)
+    #[serde(default)]
+    pub honor_gitignore: bool,
+}
+
+impl Default for IgnoreSettings {
+    fn default() -> Self {
+        Self {
+            patterns: DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect(),
+            honor_gitignore: false,
+        }
+    }
+}
+
+impl IgnoreSettings {
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        match crate::db::get_setting::<IgnoreSettings>(conn, IGNORE_SETTING) {
+            Ok(Some(settings)) => settings,
+            Ok(None) => Self::default(),
+            Err(e) => {
+                eprintln!("⚠️  Failed to load ignore rules, using defaults: {}", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+// ============================================================================
+// MATCHING
+// ============================================================================
+
+/// Compiled ignore rules + a cache of every folder's ignore files.
+///
+/// Build one per scan (or per batch of watcher events) with `load()`, so
+/// edits to settings or .auroraignore files are picked up next time.
+pub struct IgnoreRules {
+    global: Gitignore,
+    honor_gitignore: bool,
+    // folder -> its compiled ignore files (None = folder has none)
+    // Behind a Mutex so several scanning threads can share one IgnoreRules
+    per_dir: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+impl IgnoreRules {
+    /// Read the global settings from SQLite and compile them
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        Self::new(&IgnoreSettings::load(conn))
+    }
+
+    pub fn new(settings: &IgnoreSettings) -> Self {
+        // Global patterns aren't tied to any folder, so they're matched
+        // against paths relative to their source (see relative_path)
+        let mut builder = GitignoreBuilder::new("");
+        for pattern in &settings.patterns {
+            if let Err(e) = builder.add_line(None, pattern) {
+                eprintln!("⚠️  Ignoring invalid ignore pattern {:?}: {}", pattern, e);
+            }
+        }
+        let global = builder.build().unwrap_or_else(|e| {
+            eprintln!("⚠️  Failed to compile ignore patterns: {}", e);
+            Gitignore::empty()
+        });
+
+        Self {
+            global,
+            honor_gitignore: settings.honor_gitignore,
+            per_dir: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Should this path be left out of the index? `root` is the source
+    /// folder it's in: ignore files are read from there down to the path's
+    /// folder. Without one, only the global patterns apply.
+    pub fn is_ignored(&self, root: Option<&Path>, path: &Path, is_dir: bool) -> bool {
+        // Deepest folder first: the closest ignore file has the final say
+        let folders = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| root.is_some_and(|root| dir.starts_with(root)));
+        for dir in folders {
+            if let Some(rules) = self.rules_for_dir(dir) {
+                match rules.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+
+        let relative = relative_path(root, path);
+        if relative.as_os_str().is_empty() {
+            return false;  // The source folder itself
+        }
+        self.global.matched_path_or_any_parents(relative, is_dir).is_ignore()
+    }
+
+    /// The compiled .auroraignore (+ .gitignore) of one folder, cached
+    fn rules_for_dir(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        if let Ok(cache) = self.per_dir.lock() {
+            if let Some(cached) = cache.get(dir) {
+                return cached.clone();
+            }
+        }
+
+        let rules = self.compile_dir(dir).map(Arc::new);
+        if let Ok(mut cache) = self.per_dir.lock() {
+            cache.insert(dir.to_path_buf(), rules.clone());
+        }
+        rules
+    }
+
+    fn compile_dir(&self, dir: &Path) -> Option<Gitignore> {
+        let mut files = Vec::with_capacity(2);
+        // .gitignore is added first so .auroraignore can override it
+        if self.honor_gitignore {
+            files.push(dir.join(".gitignore"));
+        }
+        files.push(dir.join(AURORA_IGNORE_FILE));
+
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+        for file in files.iter().filter(|f| f.is_file()) {
+            found = true;
+            if let Some(e) = builder.add(file) {
+                eprintln!("⚠️  Problem reading {}: {}", file.display(), e);
+            }
+        }
+        if !found {
+            return None;
+        }
+
+        match builder.build() {
+            Ok(rules) if !rules.is_empty() => Some(rules),
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("⚠️  Failed to compile ignore rules in {}: {}", dir.display(), e);
+                None
+            }
+        }
+    }
+}
+
+/// "/Users/me/Docs/node_modules/x" in "/Users/me/Docs" -> "node_modules/x".
+/// Outside any source, only the filesystem root / drive letter is dropped,
+/// so global patterns still match anywhere.
+fn relative_path(root: Option<&Path>, path: &Path) -> PathBuf {
+    match root.and_then(|root| path.strip_prefix(root).ok()) {
+        Some(relative) => relative.to_path_buf(),
+        None => path
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect(),
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn ignore_get_settings(pool: State<'_, DbPool>) -> Result<IgnoreSettings, String> {
+    Ok(IgnoreSettings::load(&pool.reader()))
+}
+
+/// Save new global rules. They apply to the next scan and the next watcher
+/// events; already-indexed files that are now ignored go away on rescan.
+#[tauri::command]
+pub async fn ignore_set_settings(pool: State<'_, DbPool>, settings: IgnoreSettings) -> Result<(), String> {
+    let patterns: Vec<String> = settings
+        .patterns
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty() && !p.starts_with('#'))
+        .collect();
+
+    // Reject broken patterns now instead of silently skipping them later
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in &patterns {
+        builder
+            .add_line(None, pattern)
+            .map_err(|e| format!("Invalid ignore pattern {:?}: {}", pattern, e))?;
+    }
+
+    let settings = IgnoreSettings { patterns, honor_gitignore: settings.honor_gitignore };
+    crate::db::set_setting(&pool.writer(), IGNORE_SETTING, &settings).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. Path::ancestors():
+//    - "/a/b/c.txt" -> "/a/b/c.txt", "/a/b", "/a", "/" (skip(1) drops the file)
+//    - take_while() stops at the first folder above the source root
+//
+// 2. Option<Arc<T>> IN A CACHE:
+//    - Caching None too means "this folder has no ignore file" is only
+//      checked on disk once
+//
+// 3. matches!():
+//    - A quick `match` that returns true/false for one pattern
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn rules(patterns: &[&str], honor_gitignore: bool) -> IgnoreRules {
+        IgnoreRules::new(&IgnoreSettings {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            honor_gitignore,
+        })
+    }
+
+    /// Is `relative` (a file, or a folder if it ends in '/') ignored in `root`?
+    fn ignored(rules: &IgnoreRules, root: &Path, relative: &str) -> bool {
+        let is_dir = relative.ends_with('/');
+        rules.is_ignored(Some(root), &root.join(relative.trim_end_matches('/')), is_dir)
+    }
+
+    #[test]
+    fn default_patterns() {
+        let dir = TempDir::new("ignore-defaults");
+        let rules = IgnoreRules::new(&IgnoreSettings::default());
+        let root = dir.path();
+
+        assert!(ignored(&rules, root, "node_modules/"));
+        assert!(ignored(&rules, root, "app/node_modules/left-pad/index.js"));
+        assert!(ignored(&rules, root, ".git/config"));
+        assert!(ignored(&rules, root, "rust/target/debug/app"));
+        assert!(ignored(&rules, root, "photos/.DS_Store"));
+        assert!(!ignored(&rules, root, "photos/beach.jpg"));
+        // "target/" only means folders
+        assert!(!ignored(&rules, root, "notes/target"));
+    }
+
+    #[test]
+    fn default_patterns_match_inside_the_source_only() {
+        // A source that itself lives in a "target" folder is still indexed
+        let dir = TempDir::new("ignore-inside");
+        let root = dir.path().join("target").join("docs");
+        let rules = IgnoreRules::new(&IgnoreSettings::default());
+
+        assert!(!ignored(&rules, &root, "report.pdf"));
+        assert!(ignored(&rules, &root, "target/report.pdf"));
+        // The source folder itself is never ignored
+        assert!(!rules.is_ignored(Some(&root), &root, true));
+
+        // Without a known source the whole path counts
+        assert!(rules.is_ignored(None, &root.join("report.pdf"), false));
+    }
+
+    #[test]
+    fn nested_ignore_files_and_whitelists() {
+        let dir = TempDir::new("ignore-nested");
+        dir.write(".auroraignore", b"*.log\ndrafts/\n");
+        dir.write("server/.auroraignore", b"!keep.log\n");
+        dir.write("server/old/.auroraignore", b"*.txt\n");
+        let rules = rules(&[], false);
+        let root = dir.path();
+
+        assert!(ignored(&rules, root, "app.log"));
+        assert!(ignored(&rules, root, "server/other.log"));
+        assert!(ignored(&rules, root, "drafts/"));
+        assert!(ignored(&rules, root, "server/drafts/idea.md"));
+        // The deeper file un-ignores one name, for its folder and below
+        assert!(!ignored(&rules, root, "server/keep.log"));
+        assert!(!ignored(&rules, root, "server/old/keep.log"));
+        assert!(ignored(&rules, root, "keep.log"));
+        // ...and adds its own rules that don't reach back up
+        assert!(ignored(&rules, root, "server/old/readme.txt"));
+        assert!(!ignored(&rules, root, "server/readme.txt"));
+    }
+
+    #[test]
+    fn whitelist_beats_a_global_pattern() {
+        let dir = TempDir::new("ignore-whitelist");
+        dir.write("vendor/.auroraignore", b"!node_modules/\n");
+        let rules = rules(&["node_modules/"], false);
+        let root = dir.path();
+
+        assert!(ignored(&rules, root, "app/node_modules/x.js"));
+        assert!(!ignored(&rules, root, "vendor/node_modules/x.js"));
+    }
+
+    #[test]
+    fn gitignore_only_when_honored() {
+        let dir = TempDir::new("ignore-gitignore");
+        dir.write(".gitignore", b"build/\n*.tmp\n");
+        dir.write("site/.auroraignore", b"!build/\n");
+        let root = dir.path();
+
+        let off = rules(&[], false);
+        assert!(!ignored(&off, root, "build/app.js"));
+        assert!(!ignored(&off, root, "scratch.tmp"));
+
+        let on = rules(&[], true);
+        assert!(ignored(&on, root, "build/app.js"));
+        assert!(ignored(&on, root, "scratch.tmp"));
+        // .auroraignore still has the last word
+        assert!(!ignored(&on, root, "site/build/app.js"));
+    }
+
+    #[test]
+    fn ignore_files_above_the_source_are_not_read() {
+        let dir = TempDir::new("ignore-above");
+        dir.write(".auroraignore", b"*.txt\n");
+        dir.write(".gitignore", b"*.md\n");
+        let root = dir.path().join("source");
+        let file = dir.write("source/notes.txt", b"hi");
+        let rules = rules(&[], true);
+
+        assert!(!rules.is_ignored(Some(&root), Path::new(&file), false));
+        assert!(!ignored(&rules, &root, "readme.md"));
+        assert!(!rules.is_ignored(None, Path::new(&file), false));
+        // ...but from the folder above, they apply
+        assert!(rules.is_ignored(Some(dir.path()), Path::new(&file), false));
+    }
+}
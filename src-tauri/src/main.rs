@@ -25,11 +25,29 @@
 // These are module declarations - we're telling Rust about other files
 // "mod" creates a module (like a namespace)
 mod commands;  // This will contain our Tauri commands (Rust functions callable from JS)
+mod content;   // Text extraction (txt/md/code/html/office/pdf) for content search
 mod db;        // Database operations
 mod file_watcher;  // File system watching
+mod fuzzy;         // fzf-style fuzzy filename matching with highlight ranges
+mod hashing;       // Content hashes + duplicate detection (background)
+mod highlight;     // Search result snippets + match ranges to bold
+mod ignore_rules;  // .auroraignore / .gitignore-style rules for scans + watcher
+mod migrations;    // Versioned database schema upgrades
+mod ranking;       // Search result scoring: text match + frecency + recency
+mod reconcile;     // Catch up on changes made while Aurora was closed
+mod scan_policy;   // Per-source depth/symlink/hidden/size/extension rules
+mod scanner;       // Folder scans as cancellable jobs with progress events
+mod search_query;  // Search box syntax: words, "phrases", type:pdf, size:>10mb...
+mod sources;       // Registered scan folders (scan_sources table)
+mod tags;          // Finder tags: read/write xattrs + keep SQLite in sync
+mod thumbnails;    // Image thumbnail cache + background workers
+mod watch_fallback;  // Polling for network/FUSE folders and watch-limit failures
+#[cfg(test)]
+mod test_support;    // Scratch folders, databases and rows for unit tests
 
 // "use" imports items from modules or crates
 // Think of it like: import { something } from 'somewhere'
+use tauri::Manager;  // Gives `app` .manage() (shared state) and .path()
 
 // ============================================================================
 // MAIN FUNCTION - Your App Starts Here!
@@ -55,6 +73,7 @@ fn main() {
         .plugin(tauri_plugin_shell::init())   // Run shell commands
         .plugin(tauri_plugin_dialog::init())  // Native open/save dialogs
         .manage(file_watcher::WatcherState::default())
+        .manage(scanner::ScanJobs::default())
 
         // ====================================================================
         // SETUP HOOK - Runs once when app starts
@@ -62,39 +81,40 @@ fn main() {
         // The |app| syntax is a closure (like arrow functions in JS)
         // Think of it as: (app) => { ... }
         .setup(|app| {
-            // Get the app handle - this lets us interact with the Tauri app
-            // In Rust, we often need to clone() things because of ownership rules
-            // Don't worry about this for now - just know clone() creates a copy
-
-            // RUST OWNERSHIP:
-            // We need to clone app_handle for each async block
-            // because "move" takes ownership, and we can't use it twice
-            let app_handle_db = app.handle().clone();
-
-            // Initialize the database when the app starts
-            // spawn() runs this on a separate thread so it doesn't block the UI
-            // Like Promise.all() or async/await in JavaScript
-            tauri::async_runtime::spawn(async move {
-                // RUST OWNERSHIP CONCEPT:
-                // "move" means: move ownership of app_handle_db into this closure
-                // It's like saying "this closure now owns app_handle_db"
-
-                match db::init_database(&app_handle_db).await {
-                    // RUST PATTERN MATCHING:
-                    // "match" is like switch/case but way more powerful
-                    // It checks all possible outcomes of a Result (Ok or Err)
-
-                    Ok(_) => println!("✅ Database initialized successfully"),
-                    // println! is a macro (note the !) that prints to console
-                    // It's like console.log() in JavaScript
-
-                    Err(e) => eprintln!("❌ Failed to initialize database: {}", e),
-                    // eprintln! prints to stderr (error output)
-                    // {} is a placeholder (like ${e} in JS template literals)
-                }
-            });
+            // Open the database (and run any schema migrations) before the
+            // UI can call commands. The ? operator means a broken or too-new
+            // database stops startup with a clear error instead of limping on.
+            let db_pool = db::init_database(app.handle()).map_err(|e| {
+                eprintln!("❌ Failed to initialize database: {}", e);
+                e
+            })?;
+
+            // Store the connection pool in Tauri "managed state"
+            // (right next to WatcherState). Commands get it back with a
+            // `State<'_, DbPool>` parameter.
+            app.manage(db_pool);
+
+            // Start the background thumbnail workers. Thumbnails are cached
+            // inside the app data dir, next to aurora.db.
+            let thumbnail_dir = app.path().app_data_dir()?.join("thumbnails");
+            app.manage(thumbnails::ThumbnailQueue::start(app.handle().clone(), thumbnail_dir));
+
+            // Duplicate detection hashes files on its own thread
+            app.manage(hashing::HashQueue::start(app.handle().clone()));
+
+            // ...and so does reading text out of documents for search
+            app.manage(content::ContentQueue::start(app.handle().clone()));
+
+            // Watch the folders the user added in an earlier session right
+            // away (a failure here just means no live updates, not no app).
+            // Doesn't wait: the watcher thread reports via "watcher-status".
+            if let Err(e) = file_watcher::restore_from_sources(app.handle()) {
+                eprintln!("⚠️  File watcher not started: {}", e);
+            }
 
-            // File watching is started by the frontend once the user chooses folders.
+            // ...and catch up on whatever changed while Aurora was closed
+            // (in the background - the UI shows the old index meanwhile)
+            reconcile::spawn(app.handle().clone());
 
             // RUST RESULT TYPE:
             // Functions often return Result<T, E>
@@ -120,23 +140,60 @@ fn main() {
             // File system commands
             commands::greet,
             commands::apple_calendar_list_events,
-            commands::scan_directories,
+            scanner::scan_directories,
+            scanner::scan_cancel,
+            reconcile::reconcile_sources,
             commands::get_all_files,
             commands::get_finder_tags,
-            commands::generate_thumbnail,
             commands::update_file_metadata,
+            commands::get_file_metadata,
+            commands::clear_file_metadata,
             commands::search_files,
 
             // Database commands (Phase 2)
             db::db_get_all_files,
             db::db_search_files,
+            fuzzy::db_fuzzy_search,
             db::db_get_file_count,
             db::db_record_open,
             db::db_get_resurfaced_files,
 
+            // Tag editing (writes Finder tags + database together)
+            tags::tags_add,
+            tags::tags_remove,
+            tags::tags_set_color,
+            tags::tags_get_color_map,
+            tags::tags_set_color_map,
+            ignore_rules::ignore_get_settings,
+            ignore_rules::ignore_set_settings,
+            scan_policy::scan_policy_get,
+            scan_policy::scan_policy_set,
+            scan_policy::scan_policy_reset,
+            sources::sources_list,
+            sources::sources_add,
+            sources::sources_remove,
+            sources::sources_set_enabled,
+
+            // Thumbnails
+            thumbnails::generate_thumbnail,
+            thumbnails::thumbnails_enqueue,
+
+            // Search ranking weights
+            ranking::search_get_ranking,
+            ranking::search_set_ranking,
+
+            // Duplicate files
+            hashing::find_duplicates,
+
+            // Content search indexing
+            content::content_index_status,
+
             // File watcher controls
             file_watcher::watch_set_paths,
             file_watcher::watch_stop,
+            file_watcher::watch_status,
+            watch_fallback::watch_get_settings,
+            watch_fallback::watch_set_settings,
         ])
 
         // Build and run the app!
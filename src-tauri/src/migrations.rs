@@ -0,0 +1,484 @@
+// ============================================================================
+// MIGRATIONS MODULE - Versioned schema upgrades for aurora.db
+// ============================================================================
+//
+// WHY MIGRATIONS?
+// "CREATE TABLE IF NOT EXISTS" only helps the very first time a database is
+// created. If we later add a column to `files`, people who already have an
+// aurora.db would never get it. Migrations fix that:
+//
+// 1. Every schema change is a numbered step (version 1, 2, 3, ...)
+// 2. SQLite remembers which step a database is at (PRAGMA user_version)
+// 3. On startup we run every step newer than that, in order
+//
+// Each step runs inside its own transaction. If a step fails, SQLite rolls
+// it back and the database stays at the previous version - never half-done.
+//
+// ADDING A NEW MIGRATION:
+// Append a new `Migration` to the end of MIGRATIONS with the next version
+// number. Never edit or reorder a migration that has already shipped!
+//
+// ============================================================================
+
+use rusqlite::Connection;
+
+/// One step in the schema history
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// The full, ordered schema history.
+/// Version 0 is an empty database (or one created before migrations existed).
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial schema",
+        // LEARNING NOTE:
+        // This is the original create_schema() batch. It keeps the
+        // "IF NOT EXISTS" guards so databases created before migrations
+        // existed (which already have these tables) upgrade cleanly.
+        sql: "
+        -- Files table: stores all indexed files
+        CREATE TABLE IF NOT EXISTS files (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT UNIQUE NOT NULL,
+            name TEXT NOT NULL,
+            file_type TEXT,
+            size INTEGER,
+            created_at INTEGER,
+            modified_at INTEGER,
+            last_opened_at INTEGER,
+            open_count INTEGER DEFAULT 0,
+            thumbnail_path TEXT,
+
+            -- Spatial memory: tile positions
+            tile_x REAL,
+            tile_y REAL,
+            tile_cluster TEXT,
+
+            -- Timestamps
+            indexed_at INTEGER DEFAULT (strftime('%s', 'now'))
+        );
+
+        -- Finder tags table
+        CREATE TABLE IF NOT EXISTS finder_tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT UNIQUE NOT NULL,
+            color INTEGER DEFAULT 0
+        );
+
+        -- File-tag associations (many-to-many)
+        CREATE TABLE IF NOT EXISTS file_tags (
+            file_id INTEGER,
+            tag_id INTEGER,
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE,
+            FOREIGN KEY (tag_id) REFERENCES finder_tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (file_id, tag_id)
+        );
+
+        -- Emotional metadata
+        CREATE TABLE IF NOT EXISTS file_metadata (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_id INTEGER UNIQUE,
+            mood TEXT,
+            season TEXT,
+            vibe_color TEXT,
+            location TEXT,
+            energy_level TEXT,
+            notes TEXT,
+            created_at INTEGER DEFAULT (strftime('%s', 'now')),
+            updated_at INTEGER DEFAULT (strftime('%s', 'now')),
+            FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+        );
+
+        -- Clusters for visual grouping
+        CREATE TABLE IF NOT EXISTS clusters (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT UNIQUE NOT NULL,
+            color TEXT,
+            sort_order INTEGER DEFAULT 0
+        );
+
+        -- Full-text search index (FTS5)
+        -- This makes search SUPER fast!
+        CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(
+            file_id UNINDEXED,
+            path,
+            name,
+            content=''
+        );
+
+        -- Indexes for faster queries
+        CREATE INDEX IF NOT EXISTS idx_files_path ON files(path);
+        CREATE INDEX IF NOT EXISTS idx_files_modified ON files(modified_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_files_cluster ON files(tile_cluster);
+        CREATE INDEX IF NOT EXISTS idx_metadata_file ON file_metadata(file_id);
+
+        -- Triggers to keep FTS5 in sync
+        -- These automatically update the search index when files change
+
+        CREATE TRIGGER IF NOT EXISTS files_ai AFTER INSERT ON files
+        BEGIN
+            INSERT INTO files_fts(file_id, path, name)
+            VALUES (new.id, new.path, new.name);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS files_ad AFTER DELETE ON files
+        BEGIN
+            DELETE FROM files_fts WHERE file_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS files_au AFTER UPDATE ON files
+        BEGIN
+            UPDATE files_fts
+            SET path = new.path, name = new.name
+            WHERE file_id = old.id;
+        END;
+        ",
+    },
+    Migration {
+        version: 2,
+        name: "settings table",
+        // Small key/value store for backend preferences.
+        // Values are JSON so each feature can store whatever shape it needs.
+        sql: "
+        CREATE TABLE settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at INTEGER DEFAULT (strftime('%s', 'now'))
+        );
+        ",
+    },
+    Migration {
+        version: 3,
+        name: "scan policies",
+        // One row per source folder that doesn't use the default policy.
+        // Extension lists are JSON arrays: '["jpg","png"]'
+        sql: "
+        CREATE TABLE scan_policies (
+            root TEXT PRIMARY KEY,
+            max_depth INTEGER NOT NULL DEFAULT 20,
+            follow_links INTEGER NOT NULL DEFAULT 0,
+            include_hidden INTEGER NOT NULL DEFAULT 1,
+            min_size INTEGER,
+            max_size INTEGER,
+            allowed_extensions TEXT NOT NULL DEFAULT '[]',
+            denied_extensions TEXT NOT NULL DEFAULT '[]',
+            index_directories INTEGER NOT NULL DEFAULT 0,
+            updated_at INTEGER DEFAULT (strftime('%s', 'now'))
+        );
+        ",
+    },
+    Migration {
+        version: 4,
+        name: "scan sources",
+        // The folders the user added (used to live only in localStorage).
+        // last_scan_* columns describe the most recent scan of that folder.
+        sql: "
+        CREATE TABLE scan_sources (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT UNIQUE NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            added_at INTEGER DEFAULT (strftime('%s', 'now')),
+            last_scan_at INTEGER,
+            last_scan_status TEXT,
+            last_scan_added INTEGER NOT NULL DEFAULT 0,
+            last_scan_updated INTEGER NOT NULL DEFAULT 0,
+            last_scan_removed INTEGER NOT NULL DEFAULT 0,
+            last_scan_unchanged INTEGER NOT NULL DEFAULT 0,
+            last_scan_errors INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT
+        );
+        ",
+    },
+    Migration {
+        version: 5,
+        name: "file inodes",
+        // How the file system itself identifies a file. It doesn't change
+        // when the file is renamed or moved (on the same disk), so the
+        // watcher can tell "moved" apart from "deleted + a new file".
+        sql: "
+        ALTER TABLE files ADD COLUMN device INTEGER;
+        ALTER TABLE files ADD COLUMN inode INTEGER;
+        CREATE INDEX idx_files_inode ON files(inode, device);
+        ",
+    },
+    Migration {
+        version: 6,
+        name: "content hashes",
+        // Filled in the background by hashing.rs, only for files that could
+        // be duplicates. partial_hash covers the start and end of the file,
+        // content_hash all of it. Both are cleared when the file changes.
+        sql: "
+        ALTER TABLE files ADD COLUMN partial_hash TEXT;
+        ALTER TABLE files ADD COLUMN content_hash TEXT;
+        CREATE INDEX idx_files_size_partial ON files(size, partial_hash);
+        CREATE INDEX idx_files_content_hash ON files(content_hash);
+        ",
+    },
+    Migration {
+        version: 7,
+        name: "searchable files_fts",
+        // LEARNING NOTE:
+        // The v1 index was "contentless" (content=''): it can't return its
+        // file_id column or apply UPDATEs, so joins against it found nothing.
+        // An EXTERNAL CONTENT table reads name/path from `files` itself and
+        // uses files.id as its rowid. prefix='2 3' keeps as-you-type prefix
+        // searches ("rep*") fast. 'rebuild' indexes the existing rows.
+        sql: "
+        DROP TRIGGER IF EXISTS files_ai;
+        DROP TRIGGER IF EXISTS files_ad;
+        DROP TRIGGER IF EXISTS files_au;
+        DROP TABLE IF EXISTS files_fts;
+
+        CREATE VIRTUAL TABLE files_fts USING fts5(
+            name,
+            path,
+            content='files',
+            content_rowid='id',
+            prefix='2 3'
+        );
+
+        CREATE TRIGGER files_ai AFTER INSERT ON files
+        BEGIN
+            INSERT INTO files_fts(rowid, name, path)
+            VALUES (new.id, new.name, new.path);
+        END;
+
+        CREATE TRIGGER files_ad AFTER DELETE ON files
+        BEGIN
+            INSERT INTO files_fts(files_fts, rowid, name, path)
+            VALUES ('delete', old.id, old.name, old.path);
+        END;
+
+        -- Only renames/moves touch the index (not size or thumbnail updates)
+        CREATE TRIGGER files_au AFTER UPDATE OF name, path ON files
+        BEGIN
+            INSERT INTO files_fts(files_fts, rowid, name, path)
+            VALUES ('delete', old.id, old.name, old.path);
+            INSERT INTO files_fts(rowid, name, path)
+            VALUES (new.id, new.name, new.path);
+        END;
+
+        INSERT INTO files_fts(files_fts) VALUES ('rebuild');
+        ",
+    },
+    Migration {
+        version: 8,
+        name: "file contents index",
+        // Text pulled out of documents by content.rs. contents_fts keeps its
+        // own copy of the text (so snippets can be shown), one row per file
+        // with rowid = files.id. content_indexed_at is NULL until a file has
+        // been read, and is cleared again when it changes.
+        sql: "
+        ALTER TABLE files ADD COLUMN content_indexed_at INTEGER;
+
+        CREATE VIRTUAL TABLE contents_fts USING fts5(content);
+
+        CREATE TRIGGER files_ad_contents AFTER DELETE ON files
+        BEGIN
+            DELETE FROM contents_fts WHERE rowid = old.id;
+        END;
+        ",
+    },
+    Migration {
+        version: 9,
+        name: "file change times",
+        // The file's ctime (nanoseconds). Editing tags only touches an xattr, which leaves
+        // modified_at and size alone but bumps the ctime - so rescans compare
+        // it too. NULL (rows from before this version) means "read it again".
+        sql: "
+        ALTER TABLE files ADD COLUMN changed_at INTEGER;
+        ",
+    },
+];
+
+/// The schema version this build of Aurora knows how to work with
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Read the schema version stored in the database file
+pub fn current_version(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring the database up to `latest_version()`.
+/// Returns how many migrations were applied.
+pub fn run_migrations(conn: &mut Connection) -> Result<usize, Box<dyn std::error::Error>> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    // SAFETY CHECK:
+    // A database written by a newer Aurora may have tables/columns we don't
+    // understand. Opening it anyway could corrupt the user's data.
+    if current > latest {
+        return Err(format!(
+            "aurora.db is at schema version {}, but this build of Aurora only supports up to version {}. Please update Aurora.",
+            current, latest
+        )
+        .into());
+    }
+
+    let mut applied = 0usize;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        println!("🧱 Applying migration {}: {}", migration.version, migration.name);
+
+        // RUST TRANSACTIONS:
+        // If anything below returns early with ?, `tx` is dropped without
+        // commit() and SQLite rolls back every statement in this migration.
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.sql).map_err(|e| {
+            format!(
+                "Migration {} ({}) failed: {}",
+                migration.version, migration.name, e
+            )
+        })?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database as an older Aurora left it: every migration up to
+    /// `version` applied, plus one indexed file
+    fn database_at(version: i64) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn.pragma_update(None, "user_version", version).unwrap();
+        if version >= 1 {
+            conn.execute(
+                "INSERT INTO files (path, name, file_type, size) VALUES ('/docs/quarterly-report.pdf', 'quarterly-report.pdf', 'pdf', 10)",
+                [],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn has_column(conn: &Connection, table: &str, column: &str) -> bool {
+        conn.prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))
+            .unwrap()
+            .exists([column])
+            .unwrap()
+    }
+
+    fn has_trigger(conn: &Connection, name: &str) -> bool {
+        conn.prepare("SELECT 1 FROM sqlite_master WHERE type = 'trigger' AND name = ?1")
+            .unwrap()
+            .exists([name])
+            .unwrap()
+    }
+
+    fn fts_hits(conn: &Connection, fts: &str) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM files_fts WHERE files_fts MATCH ?1", [fts], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn assert_latest_schema(conn: &Connection) {
+        assert_eq!(current_version(conn).unwrap(), latest_version());
+        for column in ["device", "inode", "partial_hash", "content_hash", "content_indexed_at", "changed_at"] {
+            assert!(has_column(conn, "files", column), "files.{} missing", column);
+        }
+        assert!(has_column(conn, "scan_sources", "last_scan_errors"));
+        assert!(has_column(conn, "scan_policies", "index_directories"));
+        for trigger in ["files_ai", "files_ad", "files_au", "files_ad_contents"] {
+            assert!(has_trigger(conn, trigger), "trigger {} missing", trigger);
+        }
+    }
+
+    #[test]
+    fn upgrades_every_historical_version() {
+        for version in 0..latest_version() {
+            let mut conn = database_at(version);
+            let applied = run_migrations(&mut conn).unwrap();
+            assert_eq!(applied as i64, latest_version() - version, "from version {}", version);
+            assert_latest_schema(&conn);
+
+            if version >= 1 {
+                // The file indexed before the upgrade is searchable after it
+                // (v7 rebuilt files_fts from the files table)
+                assert_eq!(fts_hits(&conn, "quarterly"), 1, "from version {}", version);
+                assert_eq!(fts_hits(&conn, "name : report"), 1, "from version {}", version);
+            }
+        }
+    }
+
+    #[test]
+    fn triggers_keep_files_fts_in_sync_after_upgrade() {
+        let mut conn = database_at(1);
+        run_migrations(&mut conn).unwrap();
+
+        conn.execute("UPDATE files SET name = 'budget.pdf', path = '/docs/budget.pdf'", []).unwrap();
+        assert_eq!(fts_hits(&conn, "quarterly"), 0);
+        assert_eq!(fts_hits(&conn, "budget"), 1);
+
+        conn.execute("DELETE FROM files", []).unwrap();
+        assert_eq!(fts_hits(&conn, "budget"), 0);
+    }
+
+    #[test]
+    fn database_from_before_migrations_upgrades() {
+        // The old create_schema() made the v1 tables but never set user_version
+        let mut conn = database_at(1);
+        conn.pragma_update(None, "user_version", 0).unwrap();
+
+        run_migrations(&mut conn).unwrap();
+        assert_latest_schema(&conn);
+        assert_eq!(fts_hits(&conn, "quarterly"), 1);
+    }
+
+    #[test]
+    fn up_to_date_database_is_left_alone() {
+        let mut conn = database_at(latest_version());
+        assert_eq!(run_migrations(&mut conn).unwrap(), 0);
+        assert_latest_schema(&conn);
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back() {
+        // A table in the way makes v8 fail AFTER its ALTER TABLE ran
+        let mut conn = database_at(6);
+        conn.execute_batch("CREATE TABLE contents_fts (content TEXT)").unwrap();
+
+        let error = run_migrations(&mut conn).unwrap_err().to_string();
+        assert!(error.contains("Migration 8 (file contents index) failed"), "{}", error);
+
+        // v7 committed before v8 started; nothing of v8 is left behind
+        assert_eq!(current_version(&conn).unwrap(), 7);
+        assert_eq!(fts_hits(&conn, "quarterly"), 1);
+        assert!(!has_column(&conn, "files", "content_indexed_at"));
+        assert!(!has_trigger(&conn, "files_ad_contents"));
+
+        // Once the problem is gone, the next launch finishes the upgrade
+        conn.execute_batch("DROP TABLE contents_fts").unwrap();
+        assert_eq!(run_migrations(&mut conn).unwrap() as i64, latest_version() - 7);
+        assert_latest_schema(&conn);
+    }
+
+    #[test]
+    fn newer_database_is_refused() {
+        let mut conn = database_at(latest_version());
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        let error = run_migrations(&mut conn).unwrap_err().to_string();
+        assert!(error.contains("Please update Aurora"), "{}", error);
+        // ...and nothing was touched
+        assert_eq!(current_version(&conn).unwrap(), latest_version() + 1);
+    }
+}
@@ -0,0 +1,301 @@
+// ============================================================================
+// RANKING MODULE - Which search results come first
+// ============================================================================
+//
+// FTS5's own rank only knows how well the WORDS match. But the file you
+// want is usually one you open a lot, or opened this morning, or just
+// edited. So every search hit gets a score that blends:
+//
+//   text       how well the words match (bm25), relative to the best hit
+//   frecency   how OFTEN and how RECENTLY it was opened (record_open)
+//   recency    how recently it was modified
+//   tag        a small bonus for files with a Finder tag
+//   cluster    a small bonus for files the user put in a cluster
+//
+//   score = text_weight     * text      (0..1)
+//         + frecency_weight * frecency  (0..1)
+//         + recency_weight  * recency   (0..1)
+//         + tag_boost       (if tagged)
+//         + cluster_boost   (if in a cluster)
+//
+// "Recently" fades with a half-life: a file opened 14 days ago counts half
+// as much as one opened just now (with the default opened_half_life_days).
+//
+// The weights are a setting, so they can be tuned without a rebuild.
+// Everything here is a pure function of its inputs (including `now`), so
+// the same database always ranks the same way.
+//
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use tauri::State;
+use crate::db::{DbPool, SearchCandidate};
+
+/// Settings key for SearchRanking
+const RANKING_SETTING: &str = "search_ranking";
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// How search results are ordered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]  // Settings saved before a field existed still load
+pub struct SearchRanking {
+    pub text_weight: f64,
+    pub frecency_weight: f64,
+    pub recency_weight: f64,
+    pub tag_boost: f64,
+    pub cluster_boost: f64,
+    /// After this many days, an open counts half as much
+    pub opened_half_life_days: f64,
+    /// After this many days, a modification counts half as much
+    pub modified_half_life_days: f64,
+}
+
+impl Default for SearchRanking {
+    fn default() -> Self {
+        Self {
+            text_weight: 1.0,
+            frecency_weight: 0.6,
+            recency_weight: 0.3,
+            tag_boost: 0.1,
+            cluster_boost: 0.1,
+            opened_half_life_days: 14.0,
+            modified_half_life_days: 30.0,
+        }
+    }
+}
+
+impl SearchRanking {
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        match crate::db::get_setting::<SearchRanking>(conn, RANKING_SETTING) {
+            Ok(Some(ranking)) => ranking,
+            Ok(None) => Self::default(),
+            Err(e) => {
+                eprintln!("⚠️  Failed to load search ranking, using defaults: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Reject weights that would make scores meaningless
+    fn validate(&self) -> Result<(), String> {
+        let weights = [
+            ("text_weight", self.text_weight),
+            ("frecency_weight", self.frecency_weight),
+            ("recency_weight", self.recency_weight),
+            ("tag_boost", self.tag_boost),
+            ("cluster_boost", self.cluster_boost),
+        ];
+        for (name, weight) in weights {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("{} must be 0 or more", name));
+            }
+        }
+        for (name, days) in [
+            ("opened_half_life_days", self.opened_half_life_days),
+            ("modified_half_life_days", self.modified_half_life_days),
+        ] {
+            if !days.is_finite() || days <= 0.0 {
+                return Err(format!("{} must be more than 0", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SCORING
+// ============================================================================
+
+/// 1.0 for "just now", 0.5 after one half-life, 0.25 after two...
+fn decay(timestamp: i64, now: i64, half_life_days: f64) -> f64 {
+    // Clock skew can put timestamps in the future: treat those as "now"
+    let age_days = (now - timestamp).max(0) as f64 / SECONDS_PER_DAY;
+    0.5f64.powf(age_days / half_life_days)
+}
+
+/// Opens, faded by how long ago the last one was, squashed into 0..1
+/// (1 fresh open -> 0.5, 3 -> 0.75, 9 -> 0.9)
+fn frecency(candidate: &SearchCandidate, ranking: &SearchRanking, now: i64) -> f64 {
+    let Some(last_opened_at) = candidate.last_opened_at else {
+        return 0.0;
+    };
+    let opens = candidate.open_count.max(1) as f64;
+    let faded = opens * decay(last_opened_at, now, ranking.opened_half_life_days);
+    faded / (faded + 1.0)
+}
+
+/// Score one candidate. `best_text` is the best (most negative) bm25 of
+/// the whole result set, so text relevance is "how close to the best hit".
+pub fn score(candidate: &SearchCandidate, best_text: Option<f64>, ranking: &SearchRanking, now: i64) -> f64 {
+    let text = match (candidate.text_score, best_text) {
+        (Some(bm25), Some(best)) if best < 0.0 => (bm25 / best).clamp(0.0, 1.0),
+        _ => 0.0,
+    };
+
+    let mut score = ranking.text_weight * text
+        + ranking.frecency_weight * frecency(candidate, ranking, now)
+        + ranking.recency_weight * decay(candidate.modified_at, now, ranking.modified_half_life_days);
+    if candidate.tagged {
+        score += ranking.tag_boost;
+    }
+    if candidate.clustered {
+        score += ranking.cluster_boost;
+    }
+    score
+}
+
+/// Ids of the best `limit` candidates, best first. Ties keep the order the
+/// candidates came in (text match, then id), so results never shuffle.
+pub fn rank(candidates: &[SearchCandidate], ranking: &SearchRanking, now: i64, limit: usize) -> Vec<i64> {
+    let best_text = candidates
+        .iter()
+        .filter_map(|c| c.text_score)
+        .min_by(|a, b| a.total_cmp(b));
+
+    let mut scored: Vec<(f64, i64)> = candidates
+        .iter()
+        .map(|c| (score(c, best_text, ranking, now), c.id))
+        .collect();
+    // sort_by is stable: equal scores stay in candidate order
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+    scored.into_iter().take(limit).map(|(_, id)| id).collect()
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn search_get_ranking(pool: State<'_, DbPool>) -> Result<SearchRanking, String> {
+    Ok(SearchRanking::load(&pool.reader()))
+}
+
+/// Save new ranking weights (used from the next search on)
+#[tauri::command]
+pub async fn search_set_ranking(pool: State<'_, DbPool>, ranking: SearchRanking) -> Result<SearchRanking, String> {
+    ranking.validate()?;
+    crate::db::set_setting(&pool.writer(), RANKING_SETTING, &ranking).map_err(|e| e.to_string())?;
+    Ok(ranking)
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. f64 ordering:
+//    - Floats aren't Ord (NaN isn't smaller or bigger than anything), so
+//      sorting uses partial_cmp / total_cmp instead of plain sort()
+//
+// 2. Stable sorting:
+//    - sort_by keeps equal elements in their original order - that's what
+//      makes ties come out the same way every time
+//
+// 3. Pure functions:
+//    - score() and rank() take `now` as an argument instead of reading the
+//      clock, so the same inputs always give the same ranking
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fixed "now", so nothing depends on the clock
+    const NOW: i64 = 1_700_000_000;
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn candidate(id: i64) -> SearchCandidate {
+        SearchCandidate {
+            id,
+            text_score: None,
+            open_count: 0,
+            last_opened_at: None,
+            modified_at: NOW - 10 * 365 * DAY,  // Long enough ago to count for ~nothing
+            tagged: false,
+            clustered: false,
+        }
+    }
+
+    /// Candidates in the order search_candidates would return them, with
+    /// their scores under the default weights
+    fn corpus() -> Vec<SearchCandidate> {
+        vec![
+            // Best words, but untouched for a year: 1.0 + 0.3 * 0.5^(365/30)
+            SearchCandidate { text_score: Some(-10.0), modified_at: NOW - 365 * DAY, ..candidate(1) },
+            // Half as good a match, opened 9 times today, edited now:
+            // 0.5 + 0.6 * 9/10 + 0.3 = 1.34
+            SearchCandidate {
+                text_score: Some(-5.0),
+                open_count: 9,
+                last_opened_at: Some(NOW),
+                modified_at: NOW,
+                ..candidate(2)
+            },
+            // The same, but last opened two half-lives ago (9 opens fade
+            // to 2.25): 0.5 + 0.6 * 2.25/3.25 + 0.3 = 1.215...
+            SearchCandidate {
+                text_score: Some(-5.0),
+                open_count: 9,
+                last_opened_at: Some(NOW - 28 * DAY),
+                modified_at: NOW,
+                ..candidate(3)
+            },
+            // Edited a month ago, nothing else: 0.3 * 0.5 = 0.15
+            SearchCandidate { modified_at: NOW - 30 * DAY, ..candidate(8) },
+            // Clustered / tagged, edited now: 0.3 + 0.1 = 0.4 each - a tie,
+            // so they stay in this order
+            SearchCandidate { modified_at: NOW, clustered: true, ..candidate(5) },
+            SearchCandidate { modified_at: NOW, tagged: true, ..candidate(4) },
+            // Both boosts: 0.3 + 0.1 + 0.1 = 0.5
+            SearchCandidate { modified_at: NOW, tagged: true, clustered: true, ..candidate(9) },
+            // Nothing going for it: ~0
+            candidate(12),
+            // Opened once, one half-life ago: 0.6 * 0.5/1.5 = 0.2
+            SearchCandidate { open_count: 1, last_opened_at: Some(NOW - 14 * DAY), ..candidate(11) },
+            // Opened once, just now: 0.6 * 1/2 = 0.3
+            SearchCandidate { open_count: 1, last_opened_at: Some(NOW), ..candidate(10) },
+        ]
+    }
+
+    #[test]
+    fn ranks_a_fixed_corpus_in_a_fixed_order() {
+        let ranked = rank(&corpus(), &SearchRanking::default(), NOW, usize::MAX);
+        assert_eq!(ranked, vec![2, 3, 1, 9, 5, 4, 10, 11, 8, 12]);
+    }
+
+    #[test]
+    fn limit_keeps_the_best() {
+        let ranked = rank(&corpus(), &SearchRanking::default(), NOW, 4);
+        assert_eq!(ranked, vec![2, 3, 1, 9]);
+    }
+
+    #[test]
+    fn ties_keep_candidate_order() {
+        let twins: Vec<SearchCandidate> = [7, 3, 5].into_iter().map(candidate).collect();
+        assert_eq!(rank(&twins, &SearchRanking::default(), NOW, usize::MAX), vec![7, 3, 5]);
+    }
+
+    #[test]
+    fn opens_fade_with_the_half_life() {
+        let ranking = SearchRanking::default();
+        let opened = |days_ago: i64| SearchCandidate {
+            open_count: 1,
+            last_opened_at: Some(NOW - days_ago * DAY),
+            ..candidate(1)
+        };
+
+        assert!((frecency(&opened(0), &ranking, NOW) - 0.5).abs() < 1e-9);
+        assert!((frecency(&opened(14), &ranking, NOW) - 1.0 / 3.0).abs() < 1e-9);
+        assert!((frecency(&opened(28), &ranking, NOW) - 0.2).abs() < 1e-9);
+        // Opened "in the future" (clock skew) counts as now
+        let skewed = SearchCandidate { last_opened_at: Some(NOW + DAY), ..opened(0) };
+        assert!((frecency(&skewed, &ranking, NOW) - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,274 @@
+// ============================================================================
+// RECONCILE MODULE - Catching up on changes made while Aurora was closed
+// ============================================================================
+//
+// The file watcher only hears about changes while the app is running.
+// Anything created, edited, moved or deleted while Aurora was closed would
+// leave the index out of date (stale rows for files that are gone!).
+//
+// Reconciliation fixes that:
+// 1. Walk every enabled scan source (same walker, ignore rules and scan
+//    policies as a normal scan)
+// 2. Compare with what the database knows: new files, changed files
+//    (modified time/size differ) and rows whose file is gone
+// 3. Apply those differences through file_watcher::apply_changes - the
+//    exact path a live watcher event takes
+// 4. Emit ONE "reconcile-complete" event with the counts, instead of
+//    "file-changes" events listing possibly thousands of files
+//
+// It runs once in the background at startup, and on demand with the
+// reconcile_sources command. It's registered as a scan job, so
+// scan_cancel(job_id) can stop it.
+//
+// ============================================================================
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::db::{DbPool, FileStamp};
+use crate::file_watcher::{self, ChangeKind};
+use crate::scanner::{self, IndexRules, ScanJobs, WalkStats};
+
+/// Files written per database transaction
+const APPLY_CHUNK_SIZE: usize = 1000;
+
+/// Payload of the "reconcile-complete" event (and reconcile_sources' result)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileSummary {
+    pub job_id: u64,
+    pub sources: usize,   // Enabled sources that were found on disk
+    pub created: usize,
+    pub modified: usize,
+    pub removed: usize,
+    pub cancelled: bool,  // Stopped early: nothing was removed
+    pub elapsed_ms: u64,
+}
+
+/// What changed on disk since the database was last updated
+#[derive(Debug, Default)]
+struct Differences {
+    created: Vec<PathBuf>,
+    modified: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+    cancelled: bool,
+}
+
+/// Compare every enabled source with the database and apply the
+/// differences. Blocking - run it off the async runtime.
+pub fn run(app_handle: &AppHandle) -> Result<ReconcileSummary, String> {
+    let started = Instant::now();
+    let pool = app_handle.state::<DbPool>();
+    let jobs = app_handle.state::<ScanJobs>();
+
+    let sources = crate::db::get_enabled_source_paths(&pool.reader())
+        .map_err(|e| format!("Failed to load scan sources: {}", e))?;
+    let rules = IndexRules::load(&pool.reader());
+    let (roots, known) = known_files(&pool, sources)?;
+
+    let (job_id, cancel) = jobs.start();
+    println!("🔄 Reconciling {} source(s) (job {})", roots.len(), job_id);
+    let differences = find_differences(&roots, &rules, known, &cancel);
+    jobs.finish(job_id);
+
+    // One transaction per chunk: much faster than one per file, without
+    // holding the database writer for a whole big library
+    for (kind, paths) in [
+        (ChangeKind::Created, &differences.created),
+        (ChangeKind::Modified, &differences.modified),
+        (ChangeKind::Removed, &differences.removed),
+    ] {
+        for chunk in paths.chunks(APPLY_CHUNK_SIZE) {
+            file_watcher::write_batch(app_handle, |batch| {
+                file_watcher::apply_changes(batch, &rules, kind, chunk);
+            })?;
+        }
+    }
+
+    let summary = ReconcileSummary {
+        job_id,
+        sources: roots.len(),
+        created: differences.created.len(),
+        modified: differences.modified.len(),
+        removed: differences.removed.len(),
+        cancelled: differences.cancelled,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    println!(
+        "✅ Reconcile job {} {}: {} created, {} modified, {} removed",
+        job_id,
+        if summary.cancelled { "cancelled" } else { "done" },
+        summary.created, summary.modified, summary.removed
+    );
+
+    app_handle.emit("reconcile-complete", summary.clone()).ok();
+    Ok(summary)
+}
+
+/// The sources that are on disk, and what the database knows about the
+/// files in them
+fn known_files(pool: &DbPool, sources: Vec<String>) -> Result<(Vec<String>, HashMap<String, FileStamp>), String> {
+    let mut known: HashMap<String, FileStamp> = HashMap::new();
+    let mut roots = Vec::new();
+    for source in sources {
+        // A missing folder is probably an unplugged drive - its files
+        // aren't "deleted", so leave them alone
+        if !Path::new(&source).is_dir() {
+            eprintln!("⚠️  Not reconciling missing source: {}", source);
+            continue;
+        }
+        let stamps = crate::db::get_file_stamps_under(&pool.reader(), &source)
+            .map_err(|e| format!("Failed to load indexed files for {}: {}", source, e))?;
+        known.extend(stamps);
+        roots.push(source);
+    }
+    Ok((roots, known))
+}
+
+/// Walk the roots and sort what's different from `known` into created,
+/// modified and removed
+fn find_differences(
+    roots: &[String],
+    rules: &IndexRules,
+    known: HashMap<String, FileStamp>,
+    cancel: &AtomicBool,
+) -> Differences {
+    let mut differences = Differences::default();
+    let stats = WalkStats::new(roots);
+    let leftover = scanner::walk_changes(
+        roots,
+        rules,
+        known,
+        &stats,
+        cancel,
+        |file_info, was_known| {
+            let list = if was_known { &mut differences.modified } else { &mut differences.created };
+            list.push(PathBuf::from(file_info.path));
+        },
+        || {},
+    );
+
+    // `leftover` only means "missing" if we walked everything
+    differences.cancelled = cancel.load(Ordering::SeqCst);
+    if !differences.cancelled {
+        differences.removed = scanner::missing_paths(leftover, rules).into_iter().map(PathBuf::from).collect();
+    }
+    differences
+}
+
+/// Start reconciliation on a background thread (used at startup)
+pub fn spawn(app_handle: AppHandle) {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = run(&app_handle) {
+            eprintln!("❌ Reconciliation failed: {}", e);
+        }
+    });
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Catch up with changes the watcher missed. Resolves when done; the
+/// "reconcile-complete" event carries the same summary.
+#[tauri::command]
+pub async fn reconcile_sources(app_handle: AppHandle) -> Result<ReconcileSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || run(&app_handle))
+        .await
+        .map_err(|e| format!("Reconciliation failed: {}", e))?
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{pool, scan, TempDir};
+
+    /// A registered, scanned source with three files. Returns its path.
+    fn scanned_source(dir: &TempDir, pool: &DbPool) -> String {
+        dir.write("source/keep.txt", b"keep");
+        dir.write("source/edit.txt", b"edit");
+        dir.write("source/old/delete.log", b"delete");
+        let source = dir.path().join("source").to_string_lossy().to_string();
+        crate::db::add_scan_source(&pool.writer(), &source).unwrap();
+        assert_eq!(scan(&mut pool.writer(), &source), (3, 0));
+        source
+    }
+
+    /// known_files + find_differences, like run() does between the two
+    fn differences(pool: &DbPool, cancelled: bool) -> Differences {
+        let sources = crate::db::get_enabled_source_paths(&pool.reader()).unwrap();
+        let rules = IndexRules::load(&pool.reader());
+        let (roots, known) = known_files(pool, sources).unwrap();
+        find_differences(&roots, &rules, known, &AtomicBool::new(cancelled))
+    }
+
+    fn names(paths: &[PathBuf]) -> Vec<String> {
+        let mut names: Vec<String> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn finds_files_added_changed_and_removed_while_closed() {
+        let dir = TempDir::new("reconcile-diff");
+        let pool = pool(&dir);
+        scanned_source(&dir, &pool);
+
+        // Nothing happened: nothing to do
+        let nothing = differences(&pool, false);
+        assert!(nothing.created.is_empty() && nothing.modified.is_empty() && nothing.removed.is_empty());
+
+        // "While Aurora was closed"
+        dir.write("source/new/added.txt", b"new");
+        dir.write("source/edit.txt", b"edited, and longer");
+        std::fs::remove_file(dir.path().join("source/old/delete.log")).unwrap();
+
+        let found = differences(&pool, false);
+        assert_eq!(names(&found.created), vec!["added.txt"]);
+        assert_eq!(names(&found.modified), vec!["edit.txt"]);
+        assert_eq!(names(&found.removed), vec!["delete.log"]);
+        assert!(!found.cancelled);
+    }
+
+    #[test]
+    fn rows_the_rules_now_skip_count_as_removed() {
+        let dir = TempDir::new("reconcile-ignored");
+        let pool = pool(&dir);
+        scanned_source(&dir, &pool);
+
+        dir.write("source/.auroraignore", b"*.log\n");
+        let found = differences(&pool, false);
+        // The ignore file itself is new; the .log file is still on disk
+        // but no longer wanted
+        assert_eq!(names(&found.created), vec![".auroraignore"]);
+        assert_eq!(names(&found.removed), vec!["delete.log"]);
+    }
+
+    #[test]
+    fn nothing_is_removed_from_missing_sources_or_cancelled_walks() {
+        let dir = TempDir::new("reconcile-missing");
+        let pool = pool(&dir);
+        let source = scanned_source(&dir, &pool);
+
+        // A cancelled walk didn't see everything, so nothing is "gone"
+        std::fs::remove_file(dir.path().join("source/keep.txt")).unwrap();
+        let found = differences(&pool, true);
+        assert!(found.cancelled && found.removed.is_empty());
+
+        // An unplugged drive: the source is left out entirely
+        std::fs::rename(&source, dir.path().join("unplugged")).unwrap();
+        let sources = crate::db::get_enabled_source_paths(&pool.reader()).unwrap();
+        let (roots, known) = known_files(&pool, sources).unwrap();
+        assert!(roots.is_empty() && known.is_empty());
+        assert!(differences(&pool, false).removed.is_empty());
+    }
+}
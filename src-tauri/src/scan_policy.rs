@@ -0,0 +1,344 @@
+// ============================================================================
+// SCAN POLICY MODULE - How deep, how wide, and what to index per source
+// ============================================================================
+//
+// Every scan source (a folder the user added) can have its own policy:
+//
+//   max_depth          How many folders deep to go (default 20)
+//   follow_links       Follow symlinks? Loops like a -> .. are detected
+//                      by the walker and skipped, never walked forever
+//   include_hidden     Index .dotfiles and .dotfolders?
+//   min_size/max_size  Skip files smaller/larger than this (bytes)
+//   allowed_extensions Only these extensions (empty = everything)
+//   denied_extensions  Never these extensions
+//   index_directories  Also add folders themselves as tiles
+//
+// Policies live in the scan_policies table. A folder without a row gets
+// ScanPolicy::default(), which matches how Aurora always scanned.
+// The scanner AND the watcher use the same checks, so they agree.
+//
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use crate::db::DbPool;
+
+/// `file_type` stored for folder rows (files use their extension)
+pub const FOLDER_FILE_TYPE: &str = "folder";
+
+/// Deepest max_depth a policy may ask for
+const MAX_DEPTH_LIMIT: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]  // The frontend can send only the fields it changes
+pub struct ScanPolicy {
+    pub max_depth: usize,
+    pub follow_links: bool,
+    pub include_hidden: bool,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub allowed_extensions: Vec<String>,  // Lowercase, no leading dot
+    pub denied_extensions: Vec<String>,
+    pub index_directories: bool,
+}
+
+impl Default for ScanPolicy {
+    fn default() -> Self {
+        // The old hard-coded scan settings
+        Self {
+            max_depth: 20,
+            follow_links: false,
+            include_hidden: true,
+            min_size: None,
+            max_size: None,
+            allowed_extensions: Vec::new(),
+            denied_extensions: Vec::new(),
+            index_directories: false,
+        }
+    }
+}
+
+impl ScanPolicy {
+    /// Clean up user input: ".JPG" -> "jpg", sane depth, min <= max
+    pub fn normalized(mut self) -> Result<Self, String> {
+        if self.max_depth == 0 || self.max_depth > MAX_DEPTH_LIMIT {
+            return Err(format!("max_depth must be between 1 and {}", MAX_DEPTH_LIMIT));
+        }
+        if let (Some(min), Some(max)) = (self.min_size, self.max_size) {
+            if min > max {
+                return Err("min_size can't be larger than max_size".to_string());
+            }
+        }
+
+        let clean = |list: Vec<String>| -> Vec<String> {
+            let mut list: Vec<String> = list
+                .iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect();
+            list.sort();
+            list.dedup();
+            list
+        };
+        self.allowed_extensions = clean(self.allowed_extensions);
+        self.denied_extensions = clean(self.denied_extensions);
+        Ok(self)
+    }
+
+    /// Should a file with this path + metadata be indexed?
+    /// (Only the file's own properties - depth/hidden folders are checked
+    /// while walking, or by `should_index` for single paths.)
+    pub fn allows_file(&self, path: &Path, metadata: &Metadata) -> bool {
+        let size = metadata.len();
+        if self.min_size.is_some_and(|min| size < min) || self.max_size.is_some_and(|max| size > max) {
+            return false;
+        }
+
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if self.denied_extensions.contains(&ext) {
+            return false;
+        }
+        self.allowed_extensions.is_empty() || self.allowed_extensions.contains(&ext)
+    }
+
+    /// Full check for one path outside a walk (watcher events, cleanup).
+    /// `root` is the source folder the path belongs to, if known.
+    pub fn should_index(&self, root: Option<&Path>, path: &Path) -> bool {
+        let Ok(link_meta) = std::fs::symlink_metadata(path) else {
+            return false;
+        };
+        if link_meta.file_type().is_symlink() && !self.follow_links {
+            return false;
+        }
+        // With follow_links this reads the target, like the walker does
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return false;
+        };
+
+        // Folders between the root and this path (plus the path itself)
+        let relative = root.and_then(|r| path.strip_prefix(r).ok());
+        match relative {
+            Some(rel) => {
+                let depth = rel.components().count();
+                if depth == 0 || depth > self.max_depth {
+                    return false;  // The root itself, or too deep
+                }
+                if !self.include_hidden && rel.iter().any(is_hidden) {
+                    return false;
+                }
+            }
+            None => {
+                if !self.include_hidden && path.file_name().is_some_and(is_hidden) {
+                    return false;
+                }
+            }
+        }
+
+        if metadata.is_dir() {
+            self.index_directories
+        } else {
+            metadata.is_file() && self.allows_file(path, &metadata)
+        }
+    }
+}
+
+/// "/Users/me/Docs/" -> "/Users/me/Docs" so one folder is always one key
+pub fn normalize_root(root: &str) -> String {
+    let root = root.trim();
+    if root.len() > 1 {
+        root.trim_end_matches('/').to_string()
+    } else {
+        root.to_string()
+    }
+}
+
+/// ".git", ".DS_Store" -> hidden (the Unix convention)
+pub fn is_hidden(name: &OsStr) -> bool {
+    name.to_string_lossy().starts_with('.')
+}
+
+// ============================================================================
+// POLICY SET - every stored policy, for looking up "which source is this in?"
+// ============================================================================
+
+#[derive(Debug, Clone, Default)]
+pub struct PolicySet {
+    policies: Vec<(PathBuf, ScanPolicy)>,
+    default: ScanPolicy,
+}
+
+impl PolicySet {
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        match crate::db::get_scan_policies(conn) {
+            Ok(rows) => Self {
+                policies: rows.into_iter().map(|(root, policy)| (PathBuf::from(root), policy)).collect(),
+                default: ScanPolicy::default(),
+            },
+            Err(e) => {
+                eprintln!("⚠️  Failed to load scan policies, using defaults: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// The policy of the most specific source containing `path`
+    /// (or the default policy, with no root, if no source has one)
+    pub fn for_path(&self, path: &Path) -> (Option<&Path>, &ScanPolicy) {
+        self.policies
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.as_os_str().len())
+            .map(|(root, policy)| (Some(root.as_path()), policy))
+            .unwrap_or((None, &self.default))
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// The policy for a source folder (the default if none was saved)
+#[tauri::command]
+pub async fn scan_policy_get(pool: State<'_, DbPool>, root: String) -> Result<ScanPolicy, String> {
+    crate::db::get_scan_policy(&pool.reader(), &normalize_root(&root))
+        .map(|policy| policy.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Save a source folder's policy (applies from the next scan / watcher event)
+#[tauri::command]
+pub async fn scan_policy_set(pool: State<'_, DbPool>, root: String, policy: ScanPolicy) -> Result<ScanPolicy, String> {
+    let policy = policy.normalized()?;
+    crate::db::set_scan_policy(&pool.writer(), &normalize_root(&root), &policy).map_err(|e| e.to_string())?;
+    Ok(policy)
+}
+
+/// Go back to the default policy for a source folder
+#[tauri::command]
+pub async fn scan_policy_reset(pool: State<'_, DbPool>, root: String) -> Result<(), String> {
+    crate::db::delete_scan_policy(&pool.writer(), &normalize_root(&root)).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn normalized_cleans_extensions_and_checks_limits() {
+        let policy = ScanPolicy {
+            allowed_extensions: strings(&[".JPG", " png ", "jpg", "", "."]),
+            denied_extensions: strings(&["TMP", ".tmp"]),
+            ..ScanPolicy::default()
+        };
+        let policy = policy.normalized().unwrap();
+        assert_eq!(policy.allowed_extensions, strings(&["jpg", "png"]));
+        assert_eq!(policy.denied_extensions, strings(&["tmp"]));
+
+        let depth = |max_depth| ScanPolicy { max_depth, ..ScanPolicy::default() }.normalized();
+        assert!(depth(0).unwrap_err().contains("max_depth"));
+        assert!(depth(MAX_DEPTH_LIMIT + 1).is_err());
+        assert!(depth(1).is_ok() && depth(MAX_DEPTH_LIMIT).is_ok());
+
+        let sizes = |min_size, max_size| ScanPolicy { min_size, max_size, ..ScanPolicy::default() }.normalized();
+        assert!(sizes(Some(10), Some(5)).unwrap_err().contains("min_size"));
+        assert!(sizes(Some(5), Some(5)).is_ok());
+        assert!(sizes(Some(10), None).is_ok());
+    }
+
+    #[test]
+    fn allows_file_by_size_and_extension() {
+        let dir = TempDir::new("policy-files");
+        let small = dir.write("small.JPG", b"12");
+        let big = dir.write("big.jpg", &[0; 100]);
+        let text = dir.write("notes.txt", b"1234");
+        let bare = dir.write("README", b"1234");
+        let allows = |policy: &ScanPolicy, path: &str| {
+            policy.allows_file(Path::new(path), &std::fs::metadata(path).unwrap())
+        };
+
+        let everything = ScanPolicy::default();
+        assert!([&small, &big, &text, &bare].iter().all(|p| allows(&everything, p)));
+
+        let sized = ScanPolicy { min_size: Some(3), max_size: Some(50), ..ScanPolicy::default() };
+        assert!(!allows(&sized, &small));
+        assert!(!allows(&sized, &big));
+        assert!(allows(&sized, &text));
+
+        // Extensions compare lowercase; no extension is ""
+        let photos = ScanPolicy { allowed_extensions: strings(&["jpg"]), ..ScanPolicy::default() };
+        assert!(allows(&photos, &small) && allows(&photos, &big));
+        assert!(!allows(&photos, &text) && !allows(&photos, &bare));
+
+        // Denied wins over allowed
+        let no_text = ScanPolicy {
+            allowed_extensions: strings(&["jpg"]),
+            denied_extensions: strings(&["txt", "jpg"]),
+            ..ScanPolicy::default()
+        };
+        assert!(!allows(&no_text, &text) && !allows(&no_text, &small));
+    }
+
+    #[test]
+    fn should_index_counts_depth_and_hidden_folders_from_the_root() {
+        let dir = TempDir::new("policy-single");
+        let root = dir.path();
+        let shallow = dir.write("a/file.txt", b"");
+        let deep = dir.write("a/b/c/file.txt", b"");
+        let hidden = dir.write(".private/a/file.txt", b"");
+        let policy = ScanPolicy { max_depth: 3, include_hidden: false, ..ScanPolicy::default() };
+
+        assert!(policy.should_index(Some(root), Path::new(&shallow)));
+        assert!(!policy.should_index(Some(root), Path::new(&deep)));
+        assert!(!policy.should_index(Some(root), Path::new(&hidden)));
+        // The root itself, folders (unless asked for) and missing files aren't
+        assert!(!policy.should_index(Some(root), root));
+        assert!(!policy.should_index(Some(root), &root.join("a")));
+        assert!(!policy.should_index(Some(root), &root.join("gone.txt")));
+        let folders = ScanPolicy { index_directories: true, ..policy.clone() };
+        assert!(folders.should_index(Some(root), &root.join("a")));
+    }
+
+    #[test]
+    fn normalize_root_trims_slashes_and_spaces() {
+        assert_eq!(normalize_root("/Users/me/Docs/"), "/Users/me/Docs");
+        assert_eq!(normalize_root(" /Users/me/Docs// "), "/Users/me/Docs");
+        assert_eq!(normalize_root("/Users/me/Docs"), "/Users/me/Docs");
+        assert_eq!(normalize_root("/"), "/");
+    }
+
+    #[test]
+    fn for_path_picks_the_longest_matching_root() {
+        let outer = ScanPolicy { max_depth: 5, ..ScanPolicy::default() };
+        let inner = ScanPolicy { max_depth: 2, ..ScanPolicy::default() };
+        let set = PolicySet {
+            policies: vec![
+                (PathBuf::from("/home/me/Docs/Work"), inner.clone()),
+                (PathBuf::from("/home/me/Docs"), outer.clone()),
+            ],
+            default: ScanPolicy::default(),
+        };
+
+        let (root, policy) = set.for_path(Path::new("/home/me/Docs/Work/plan.md"));
+        assert_eq!((root, policy), (Some(Path::new("/home/me/Docs/Work")), &inner));
+        let (root, policy) = set.for_path(Path::new("/home/me/Docs/plan.md"));
+        assert_eq!((root, policy), (Some(Path::new("/home/me/Docs")), &outer));
+        // Whole folder names only: "Docs2" isn't inside "Docs"
+        let (root, policy) = set.for_path(Path::new("/home/me/Docs2/plan.md"));
+        assert_eq!((root, policy), (None, &ScanPolicy::default()));
+    }
+}
@@ -0,0 +1,1035 @@
+// ============================================================================
+// SCANNER MODULE - Indexing folders as background jobs
+// ============================================================================
+//
+// HOW A SCAN WORKS:
+// 1. scan_directories() gives the scan a job ID and moves the folder walk
+//    off the async runtime onto a pool of walker threads (walking a home
+//    folder can take minutes!)
+// 2. While walking, we emit "scan-progress" events a few times a second
+//    so the UI can show what's happening
+// 3. New/changed files are saved in batches, so a long scan doesn't hold
+//    everything in memory and cancelling keeps what was already found
+// 4. scan_cancel(job_id) flips a flag the walk checks on every entry
+//
+// IGNORE RULES + SCAN POLICIES:
+// Folders matched by ignore_rules.rs (node_modules, .git, .auroraignore
+// patterns...) are never walked, and their files are dropped from the index.
+// Each source's ScanPolicy (scan_policy.rs) decides depth, symlinks, hidden
+// files, size/extension limits and whether folders get rows too.
+//
+// INCREMENTAL SCANNING:
+// Before walking we load modified time + size for every file we already
+// know about. Files whose stamp still matches are skipped, and rows we
+// never saw on disk are deleted at the end (only if the walk finished!).
+//
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use crate::commands::{system_time_to_unix, FileInfo};
+use crate::db::{DbPool, FileStamp};
+use crate::ignore_rules::IgnoreRules;
+use crate::scan_policy::{self, PolicySet, ScanPolicy};
+use crate::tags::TagBackends;
+
+/// How often "scan-progress" is emitted while walking
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Save new/changed files every this many (one transaction per batch)
+pub(crate) const BATCH_SIZE: usize = 500;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// What a scan changed (returned instead of every FileInfo - the frontend
+/// reloads from the database, so sending megabytes of rows back is wasted)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanSummary {
+    pub job_id: u64,
+    pub added: usize,      // New files put in the database
+    pub updated: usize,    // Files whose modified time or size changed
+    pub removed: usize,    // Database rows whose file is gone (or now ignored)
+    pub unchanged: usize,  // Files skipped because nothing changed
+    pub cancelled: bool,   // Stopped early by scan_cancel (nothing was removed)
+    pub sources: Vec<SourceScanStats>,  // The same counts, per scanned folder
+}
+
+/// How the scan of one folder went (also saved on its scan_sources row)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceScanStats {
+    pub path: String,
+    pub status: String,  // "done", "cancelled" or "missing"
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    pub errors: usize,               // Entries that couldn't be read
+    pub last_error: Option<String>,  // The most recent of those errors
+}
+
+/// Payload for the "scan-progress" event
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgress {
+    pub job_id: u64,
+    pub directories_visited: usize,
+    pub files_found: usize,
+    pub current_path: String,
+    pub files_per_second: f64,
+    pub elapsed_ms: u64,
+    pub done: bool,
+}
+
+/// Running scan jobs, stored in Tauri managed state
+#[derive(Default)]
+pub struct ScanJobs {
+    next_id: AtomicU64,
+    // job ID -> "please stop" flag shared with the walking thread
+    running: Mutex<HashMap<u64, Arc<AtomicBool>>>,
+}
+
+impl ScanJobs {
+    pub(crate) fn start(&self) -> (u64, Arc<AtomicBool>) {
+        let job_id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let cancel = Arc::new(AtomicBool::new(false));
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(job_id, Arc::clone(&cancel));
+        }
+        (job_id, cancel)
+    }
+
+    pub(crate) fn finish(&self, job_id: u64) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(&job_id);
+        }
+    }
+
+    /// Ask a job to stop. Returns false if no such job is running.
+    pub fn cancel(&self, job_id: u64) -> bool {
+        let Ok(running) = self.running.lock() else {
+            return false;
+        };
+        match running.get(&job_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Everything that decides whether (and how) a path gets indexed.
+/// Loaded once per scan, and once per batch of watcher events, so both
+/// always follow the same rules.
+pub struct IndexRules {
+    pub tag_backends: TagBackends,  // Which tag formats to read
+    pub ignore_rules: IgnoreRules,  // .auroraignore & friends
+    pub policies: PolicySet,        // Per-source scan policies
+    pub roots: Vec<PathBuf>,        // Source folders (+ the folders being walked)
+}
+
+impl IndexRules {
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        let roots = match crate::db::get_scan_sources(conn) {
+            Ok(sources) => sources.into_iter().map(|source| PathBuf::from(source.path)).collect(),
+            Err(e) => {
+                eprintln!("⚠️  Failed to load scan sources for the index rules: {}", e);
+                Vec::new()
+            }
+        };
+        Self {
+            tag_backends: TagBackends::load(conn),
+            ignore_rules: IgnoreRules::load(conn),
+            policies: PolicySet::load(conn),
+            roots,
+        }
+    }
+
+    /// Also treat these folders as roots (a scan of a folder that isn't
+    /// a registered source)
+    pub fn with_roots(mut self, roots: &[String]) -> Self {
+        for root in roots {
+            let root = PathBuf::from(root);
+            if !self.roots.contains(&root) {
+                self.roots.push(root);
+            }
+        }
+        self
+    }
+
+    /// The most specific root containing `path`
+    pub fn root_of(&self, path: &Path) -> Option<&Path> {
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.as_os_str().len())
+            .map(PathBuf::as_path)
+    }
+
+    /// Is this path left out by the ignore rules of the root it's in?
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.ignore_rules.is_ignored(self.root_of(path), path, is_dir)
+    }
+
+    /// Should this single path be in the index? (For paths found outside
+    /// a walk: watcher events and cleanup of rows the walk didn't see.)
+    pub fn wants(&self, path: &Path) -> bool {
+        if self.is_ignored(path, path.is_dir()) {
+            return false;
+        }
+        // Depth and hidden folders count from the policy's own folder, or
+        // else the source the path is in - like during a walk
+        let (policy_root, policy) = self.policies.for_path(path);
+        policy.should_index(policy_root.or_else(|| self.root_of(path)), path)
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Scan folders and update the index. Resolves when the job is finished
+/// (or cancelled); listen to "scan-progress" for the job ID and live counts.
+#[tauri::command]
+pub async fn scan_directories(
+    app_handle: AppHandle,
+    pool: State<'_, DbPool>,
+    jobs: State<'_, ScanJobs>,
+    directories: Vec<String>,
+) -> Result<ScanSummary, String> {
+    println!("🔍 Scanning directories: {:?}", directories);
+
+    // Tag formats, ignore rules and scan policies, loaded once per scan
+    let rules = IndexRules::load(&pool.reader());
+
+    // Load what we already know (modified time + size) about every file in
+    // these folders. Whatever is left in `known` after walking was not found.
+    let mut known: HashMap<String, FileStamp> = HashMap::new();
+    let mut roots = Vec::new();
+    let mut missing_roots = Vec::new();
+    for directory in directories {
+        let directory = scan_policy::normalize_root(&directory);
+        let directory_path = Path::new(&directory);
+        if !directory_path.exists() || !directory_path.is_dir() {
+            // Also protects an unplugged drive's files from being "removed"
+            eprintln!("⚠️  Skipping non-directory path: {}", directory);
+            missing_roots.push(SourceScanStats {
+                path: directory,
+                status: "missing".to_string(),
+                errors: 1,
+                last_error: Some("Folder not found (moved, deleted or on an unplugged drive?)".to_string()),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        match crate::db::get_file_stamps_under(&pool.reader(), &directory) {
+            Ok(stamps) => known.extend(stamps),
+            Err(e) => return Err(format!("Failed to load indexed files for {}: {}", directory, e)),
+        }
+        roots.push(directory);
+    }
+
+    let (job_id, cancel) = jobs.start();
+    println!("🚀 Scan job {} started", job_id);
+
+    // RUST THREADS:
+    // The walk is plain blocking file system work, so it runs on Tauri's
+    // blocking thread pool. `move` hands it owned copies of everything.
+    let rules = rules.with_roots(&roots);
+    let handle = app_handle.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        run_scan(&handle, job_id, &roots, &rules, known, &cancel)
+    })
+    .await;
+    jobs.finish(job_id);
+
+    let mut summary = result.map_err(|e| format!("Scan job {} failed: {}", job_id, e))?;
+    summary.sources.extend(missing_roots);
+
+    // Registered sources remember how their last scan went
+    {
+        let conn = pool.writer();
+        for source in &summary.sources {
+            if let Err(e) = crate::db::record_source_scan(&conn, source) {
+                eprintln!("⚠️  Failed to save scan stats for {}: {}", source.path, e);
+            }
+        }
+    }
+
+    println!(
+        "✅ Scan job {} {}: {} added, {} updated, {} removed, {} unchanged",
+        job_id,
+        if summary.cancelled { "cancelled" } else { "done" },
+        summary.added, summary.updated, summary.removed, summary.unchanged
+    );
+
+    // New photos need previews - generate them in the background
+    crate::thumbnails::enqueue_missing(&app_handle);
+    // ...and new files may be duplicates
+    app_handle.state::<crate::hashing::HashQueue>().wake();
+    // ...or have text worth searching
+    app_handle.state::<crate::content::ContentQueue>().wake();
+
+    Ok(summary)
+}
+
+/// Stop a running scan. Files found so far are kept.
+/// Returns false if the job already finished (or never existed).
+#[tauri::command]
+pub async fn scan_cancel(jobs: State<'_, ScanJobs>, job_id: u64) -> Result<bool, String> {
+    let found = jobs.cancel(job_id);
+    if found {
+        println!("⏹️  Cancelling scan job {}", job_id);
+    }
+    Ok(found)
+}
+
+// ============================================================================
+// THE WALK
+// ============================================================================
+//
+// PARALLEL WALKING:
+// The `ignore` crate's WalkParallel hands folders out to a small pool of
+// threads, so several roots (and several subfolders of one big root) are
+// walked at the same time. Walker threads send new/changed files down a
+// bounded channel; the scan's own thread saves them in batches and emits
+// progress. If saving falls behind, the channel fills up and the walkers
+// wait - memory stays flat even on a 500k-file library.
+
+/// Upper limit for walker threads (more mostly means more disk seeking)
+const MAX_WALKER_THREADS: usize = 8;
+
+fn walker_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .clamp(2, MAX_WALKER_THREADS)
+}
+
+/// Counters shared by every walker thread
+#[derive(Default)]
+pub(crate) struct WalkStats {
+    directories_visited: AtomicUsize,
+    files_found: AtomicUsize,
+    cancelled: AtomicBool,
+    current_dir: Mutex<String>,
+    roots: Vec<(PathBuf, RootStats)>,  // One set of counters per scanned folder
+}
+
+/// What happened inside one scanned folder
+#[derive(Default)]
+struct RootStats {
+    added: AtomicUsize,
+    updated: AtomicUsize,
+    unchanged: AtomicUsize,
+    errors: AtomicUsize,
+    last_error: Mutex<Option<String>>,
+}
+
+impl WalkStats {
+    pub(crate) fn new(roots: &[String]) -> Self {
+        Self {
+            roots: roots.iter().map(|root| (PathBuf::from(root), RootStats::default())).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Counters of the (most specific) scanned folder containing `path`
+    fn root(&self, path: &Path) -> Option<&RootStats> {
+        root_index(&self.roots, path).map(|i| &self.roots[i].1)
+    }
+
+    fn count(&self, path: &Path, counter: impl Fn(&RootStats) -> &AtomicUsize) {
+        if let Some(stats) = self.root(path) {
+            counter(stats).fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Sum of one counter over every folder
+    fn total(&self, counter: impl Fn(&RootStats) -> &AtomicUsize) -> usize {
+        self.roots.iter().map(|(_, stats)| counter(stats).load(Ordering::SeqCst)).sum()
+    }
+
+    fn record_error(&self, path: Option<&Path>, message: String) {
+        let stats = match path {
+            Some(path) => self.root(path),
+            None => self.roots.first().map(|(_, stats)| stats),
+        };
+        if let Some(stats) = stats {
+            stats.errors.fetch_add(1, Ordering::Relaxed);
+            if let Ok(mut last_error) = stats.last_error.lock() {
+                *last_error = Some(message);
+            }
+        }
+    }
+}
+
+/// Everything a walker thread needs, borrowed from run_scan
+struct WalkContext<'a> {
+    rules: &'a IndexRules,
+    known: &'a Mutex<HashMap<String, FileStamp>>,
+    seen: &'a Mutex<HashSet<String>>,  // Nested roots would otherwise visit files twice
+    stats: &'a WalkStats,
+    cancel: &'a AtomicBool,
+}
+
+/// Walk every root, saving new/changed files in batches.
+fn run_scan(
+    app_handle: &AppHandle,
+    job_id: u64,
+    roots: &[String],
+    rules: &IndexRules,
+    known: HashMap<String, FileStamp>,
+    cancel: &AtomicBool,
+) -> ScanSummary {
+    let pool = app_handle.state::<DbPool>();
+    let stats = WalkStats::new(roots);
+    let mut progress = ProgressReporter::new(app_handle, job_id);
+
+    // Tell the UI the job ID right away
+    progress.emit(&stats, false);
+
+    let mut batch: Vec<FileInfo> = Vec::with_capacity(BATCH_SIZE);
+    let leftover = walk_changes(
+        roots,
+        rules,
+        known,
+        &stats,
+        cancel,
+        |file_info, _was_known| {
+            batch.push(file_info);
+            if batch.len() >= BATCH_SIZE {
+                save_batch(&pool, &mut batch);
+            }
+        },
+        || progress.tick(&stats),
+    );
+    // Whatever is still in the batch - including when cancelled
+    save_batch(&pool, &mut batch);
+
+    let mut summary = ScanSummary {
+        job_id,
+        added: stats.total(|root| &root.added),
+        updated: stats.total(|root| &root.updated),
+        removed: 0,
+        unchanged: stats.total(|root| &root.unchanged),
+        cancelled: stats.cancelled.load(Ordering::SeqCst),
+        sources: Vec::new(),
+    };
+    let mut removed_per_root = vec![0usize; stats.roots.len()];
+
+    if summary.cancelled {
+        // `known` is only "missing" files if we walked everything
+        println!("⏹️  Scan job {} stopped early - not removing any files", job_id);
+    } else {
+        let missing = missing_paths(leftover, rules);
+
+        match crate::db::delete_files(&mut pool.writer(), &missing) {
+            Ok(removed) => {
+                summary.removed = removed;
+                for path in &missing {
+                    if let Some(i) = root_index(&stats.roots, Path::new(path)) {
+                        removed_per_root[i] += 1;
+                    }
+                }
+            }
+            Err(e) => eprintln!("❌ Failed to remove missing files: {}", e),
+        }
+    }
+
+    let status = if summary.cancelled { "cancelled" } else { "done" };
+    summary.sources = stats
+        .roots
+        .iter()
+        .zip(removed_per_root)
+        .map(|((root, root_stats), removed)| SourceScanStats {
+            path: root.to_string_lossy().to_string(),
+            status: status.to_string(),
+            added: root_stats.added.load(Ordering::SeqCst),
+            updated: root_stats.updated.load(Ordering::SeqCst),
+            removed,
+            unchanged: root_stats.unchanged.load(Ordering::SeqCst),
+            errors: root_stats.errors.load(Ordering::SeqCst),
+            last_error: root_stats.last_error.lock().ok().and_then(|e| e.clone()),
+        })
+        .collect();
+
+    progress.emit(&stats, true);
+    summary
+}
+
+/// Walk `roots`, handing every new or changed file to `on_file` (with
+/// whether it was already in `known`) on the calling thread. `on_tick` is
+/// called at least every PROGRESS_INTERVAL. Returns the known files that
+/// weren't found. Shared by scans and startup reconciliation.
+pub(crate) fn walk_changes(
+    roots: &[String],
+    rules: &IndexRules,
+    known: HashMap<String, FileStamp>,
+    stats: &WalkStats,
+    cancel: &AtomicBool,
+    mut on_file: impl FnMut(FileInfo, bool),
+    mut on_tick: impl FnMut(),
+) -> HashMap<String, FileStamp> {
+    let known = Mutex::new(known);
+    let seen = Mutex::new(HashSet::new());
+    let ctx = WalkContext {
+        rules,
+        known: &known,
+        seen: &seen,
+        stats,
+        cancel,
+    };
+
+    // RUST SCOPED THREADS:
+    // thread::scope() lets threads borrow local variables (ctx) because Rust
+    // knows they all finish before the scope ends.
+    let (tx, rx) = mpsc::sync_channel::<(FileInfo, bool)>(BATCH_SIZE * 2);
+    std::thread::scope(|scope| {
+        let ctx = &ctx;
+        scope.spawn(move || walk_parallel(roots, ctx, tx));
+        // `tx` moved into the walker - when the walk ends it's dropped,
+        // which "disconnects" rx and ends the loop below
+
+        loop {
+            match rx.recv_timeout(PROGRESS_INTERVAL) {
+                Ok((file_info, was_known)) => on_file(file_info, was_known),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            on_tick();
+        }
+    });
+
+    known.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Rows we didn't see during a walk: either gone from disk, now ignored,
+/// or no longer allowed by the policy. Double-check each one - a folder we
+/// couldn't read (permissions) isn't a deleted folder.
+pub(crate) fn missing_paths(leftover: HashMap<String, FileStamp>, rules: &IndexRules) -> Vec<String> {
+    leftover
+        .into_keys()
+        .filter(|path| {
+            let path = Path::new(path);
+            !path.exists() || !rules.wants(path)
+        })
+        .collect()
+}
+
+/// Index of the most specific root containing `path`
+fn root_index(roots: &[(PathBuf, RootStats)], path: &Path) -> Option<usize> {
+    roots
+        .iter()
+        .enumerate()
+        .filter(|(_, (root, _))| path.starts_with(root))
+        .max_by_key(|(_, (root, _))| root.as_os_str().len())
+        .map(|(i, _)| i)
+}
+
+/// Walk all roots on the worker pool, sending new/changed files to `tx`.
+/// Returns when every thread is done (or the scan was cancelled).
+fn walk_parallel(roots: &[String], ctx: &WalkContext, tx: mpsc::SyncSender<(FileInfo, bool)>) {
+    // "Follow symlinks" is a walker-wide setting, so roots are walked in up
+    // to two groups. Everything else in a policy is checked per entry.
+    let (follow, no_follow): (Vec<&String>, Vec<&String>) = roots
+        .iter()
+        .partition(|root| ctx.rules.policies.for_path(Path::new(root)).1.follow_links);
+
+    for (group, follow_links) in [(follow, true), (no_follow, false)] {
+        let Some((first, rest)) = group.split_first() else {
+            continue;
+        };
+        if ctx.cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let mut builder = WalkBuilder::new(first);
+        for root in rest {
+            builder.add(root);
+        }
+        builder
+            .standard_filters(false)     // No built-in hidden/.gitignore filters - our rules decide
+            .follow_links(follow_links)  // The walker detects symlink loops (a -> ..) and reports them
+            .threads(walker_threads());
+
+        // run() calls the outer closure once per thread to build that thread's
+        // visitor; the visitor is then called for every entry the thread finds
+        builder.build_parallel().run(|| {
+            let tx = tx.clone();
+            Box::new(move |result| match result {
+                Ok(entry) => visit_entry(entry, ctx, &tx),
+                Err(e) => {
+                    if is_symlink_loop(&e) {
+                        eprintln!("🔁 Skipping symlink loop: {}", e);
+                    } else {
+                        eprintln!("⚠️  Skipping unreadable entry: {}", e);
+                        ctx.stats.record_error(error_path(&e), e.to_string());
+                    }
+                    WalkState::Continue  // Unreadable entry - keep going
+                }
+            })
+        });
+    }
+}
+
+/// Is this walk error a symlink pointing back at one of its own parents?
+fn is_symlink_loop(error: &ignore::Error) -> bool {
+    match error {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => is_symlink_loop(err),
+        _ => false,
+    }
+}
+
+/// The path a walk error is about, if it says
+fn error_path(error: &ignore::Error) -> Option<&Path> {
+    match error {
+        ignore::Error::WithPath { path, .. } => Some(path.as_path()),
+        ignore::Error::WithDepth { err, .. } => error_path(err),
+        _ => None,
+    }
+}
+
+/// Handle one file or folder found by a walker thread
+fn visit_entry(entry: DirEntry, ctx: &WalkContext, tx: &mpsc::SyncSender<(FileInfo, bool)>) -> WalkState {
+    // Checked on every entry so cancelling feels instant
+    if ctx.cancel.load(Ordering::Relaxed) {
+        ctx.stats.cancelled.store(true, Ordering::SeqCst);
+        return WalkState::Quit;
+    }
+
+    let path = entry.path();
+    let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+    // What to do with something we don't want: never look inside a folder
+    let skip = if is_dir { WalkState::Skip } else { WalkState::Continue };
+
+    // The folder the user picked (depth 0) is always walked
+    if entry.depth() > 0 {
+        let (policy_root, policy) = ctx.rules.policies.for_path(path);
+        if !policy_allows_entry(&entry, policy_root, policy) {
+            return skip;
+        }
+        if ctx.rules.is_ignored(path, is_dir) {
+            return skip;
+        }
+    }
+
+    if is_dir {
+        ctx.stats.directories_visited.fetch_add(1, Ordering::Relaxed);
+        // try_lock: progress text is best-effort, never worth waiting for
+        if let Ok(mut current) = ctx.stats.current_dir.try_lock() {
+            *current = path.to_string_lossy().to_string();
+        }
+
+        // Folders only get their own row if the policy asks for it
+        let index_directory = entry.depth() > 0 && ctx.rules.policies.for_path(path).1.index_directories;
+        if !index_directory {
+            return WalkState::Continue;
+        }
+    } else if !entry.file_type().is_some_and(|t| t.is_file()) {
+        return WalkState::Continue;
+    }
+
+    let path_str = path.to_string_lossy().to_string();
+    let first_visit = ctx.seen.lock().map(|mut seen| seen.insert(path_str.clone())).unwrap_or(true);
+    if !first_visit {
+        return WalkState::Continue;  // Already handled via another (overlapping) folder
+    }
+    ctx.stats.files_found.fetch_add(1, Ordering::Relaxed);
+
+    let metadata = match entry.metadata() {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            // Skip files we can't read (permissions, etc.)
+            eprintln!("⚠️  Skipping file {}: {}", path.display(), e);
+            ctx.stats.record_error(Some(path), format!("{}: {}", path.display(), e));
+            return WalkState::Continue;
+        }
+    };
+    if !is_dir && !ctx.rules.policies.for_path(path).1.allows_file(path, &metadata) {
+        return WalkState::Continue;  // Too big/small or a filtered extension
+    }
+
+    // Compare with the stored stamp before doing any expensive work
+    // (tag xattrs, building a FileInfo, writing to SQLite). The ctime is
+    // part of it: a tag edit changes nothing else.
+    let modified_at = metadata.modified().ok().map(system_time_to_unix).unwrap_or(0);
+    let previous = ctx.known.lock().ok().and_then(|mut known| known.remove(&path_str));
+    if previous == Some((modified_at, metadata.len(), changed_at_of(&metadata))) {
+        ctx.stats.count(path, |root| &root.unchanged);
+        return WalkState::Continue;
+    }
+
+    if previous.is_some() {
+        ctx.stats.count(path, |root| &root.updated);
+    } else {
+        ctx.stats.count(path, |root| &root.added);
+    }
+
+    // Blocks while the channel is full (saving is behind) - that's the point
+    let file_info = file_info_for(path, &metadata, &ctx.rules.tag_backends);
+    if tx.send((file_info, previous.is_some())).is_err() {
+        return WalkState::Quit;  // The saving side is gone
+    }
+    WalkState::Continue
+}
+
+/// Depth, hidden and symlink checks for one walked entry
+fn policy_allows_entry(entry: &DirEntry, policy_root: Option<&Path>, policy: &ScanPolicy) -> bool {
+    // Depth counts from the source the policy belongs to (or the walk root)
+    let depth = policy_root
+        .and_then(|root| entry.path().strip_prefix(root).ok())
+        .map(|rel| rel.components().count())
+        .unwrap_or(entry.depth());
+    if depth > policy.max_depth {
+        return false;
+    }
+    if !policy.include_hidden && scan_policy::is_hidden(entry.file_name()) {
+        return false;
+    }
+    // A nested source may not want links even when its parent follows them
+    policy.follow_links || !entry.path_is_symlink()
+}
+
+/// Save one batch of new/changed files and empty the batch
+fn save_batch(pool: &DbPool, batch: &mut Vec<FileInfo>) {
+    if batch.is_empty() {
+        return;
+    }
+    match crate::db::upsert_files(&mut pool.writer(), batch) {
+        Ok(saved_count) => println!("💾 Saved {} new or changed files to database", saved_count),
+        Err(e) => eprintln!("❌ Failed to save scan results: {}", e),
+    }
+    batch.clear();
+}
+
+/// Build a FileInfo from a path and metadata that was already read
+/// (also used by the file watcher)
+pub(crate) fn file_info_for(path: &Path, metadata: &std::fs::Metadata, tag_backends: &TagBackends) -> FileInfo {
+    let path_str = path.to_string_lossy().to_string();
+    // to_string_lossy() converts Path to String
+    // "lossy" means: replace invalid UTF-8 with � character
+
+    // Get file name
+    let name = path
+        .file_name()  // Get the file name part
+        .and_then(|n| n.to_str())  // Convert to &str
+        .unwrap_or("Unknown")  // If None, use "Unknown"
+        .to_string();
+
+    // Get file extension (folders indexed by their policy are "folder")
+    let file_type = if metadata.is_dir() {
+        scan_policy::FOLDER_FILE_TYPE.to_string()
+    } else {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    // Get timestamps
+    let created_at = metadata.created()
+        .ok()
+        .map(system_time_to_unix)
+        .unwrap_or(0);
+
+    let modified_at = metadata.modified()
+        .ok()
+        .map(system_time_to_unix)
+        .unwrap_or(0);
+
+    // Tags live in extended attributes on the file itself
+    let (finder_tags, finder_colors) = tag_backends.tag_lists(path);
+
+    FileInfo {
+        id: None,  // Will be set by database
+        path: path_str,
+        name,
+        file_type,
+        size: metadata.len(),
+        created_at,
+        modified_at,
+        last_opened_at: None,
+        thumbnail_path: None,
+        finder_tags,  // Saved into finder_tags/file_tags by db::upsert_files
+        finder_colors,
+        metadata: None,  // Emotional metadata lives in the database, not on disk
+        inode: inode_of(metadata),
+        changed_at: changed_at_of(metadata),
+    }
+}
+
+/// The ctime, in nanoseconds: when the file's content OR its attributes
+/// (tags, permissions) last changed. Nanoseconds so a tag edit right after
+/// a scan still shows. Windows has no ctime: None, so only the mtime counts.
+pub(crate) fn changed_at_of(metadata: &std::fs::Metadata) -> Option<i64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(metadata.ctime() * 1_000_000_000 + metadata.ctime_nsec())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// (device, inode) - the file system's own ID for a file, which survives
+/// renames and moves on the same disk. Windows has no inodes: None.
+pub(crate) fn inode_of(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some((metadata.dev(), metadata.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+// ============================================================================
+// PROGRESS EVENTS
+// ============================================================================
+
+/// Rate-limited "scan-progress" emitting for one job
+struct ProgressReporter<'a> {
+    app_handle: &'a AppHandle,
+    job_id: u64,
+    started: Instant,
+    last_emit: Instant,
+}
+
+impl<'a> ProgressReporter<'a> {
+    fn new(app_handle: &'a AppHandle, job_id: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            app_handle,
+            job_id,
+            started: now,
+            last_emit: now,
+        }
+    }
+
+    /// Emit if enough time has passed since the last event
+    fn tick(&mut self, stats: &WalkStats) {
+        if self.last_emit.elapsed() >= PROGRESS_INTERVAL {
+            self.emit(stats, false);
+        }
+    }
+
+    fn emit(&mut self, stats: &WalkStats, done: bool) {
+        self.last_emit = Instant::now();
+        let elapsed = self.started.elapsed();
+        let files_found = stats.files_found.load(Ordering::Relaxed);
+        let files_per_second = if elapsed.as_secs_f64() > 0.0 {
+            files_found as f64 / elapsed.as_secs_f64()
+        } else {
+            0.0
+        };
+        let current_path = stats
+            .current_dir
+            .lock()
+            .map(|current| current.clone())
+            .unwrap_or_default();
+
+        self.app_handle
+            .emit(
+                "scan-progress",
+                ScanProgress {
+                    job_id: self.job_id,
+                    directories_visited: stats.directories_visited.load(Ordering::Relaxed),
+                    files_found,
+                    current_path,
+                    files_per_second,
+                    elapsed_ms: elapsed.as_millis() as u64,
+                    done,
+                },
+            )
+            .ok();
+    }
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. ATOMICS (AtomicBool, AtomicU64):
+//    - Numbers/flags that several threads can read and write safely
+//      without a Mutex - perfect for "please stop" flags and ID counters
+//
+// 2. Arc<T>:
+//    - "Atomically Reference Counted" pointer: lets the command and the
+//      walking thread share the same cancel flag
+//
+// 3. LIFETIMES ON STRUCTS:
+//    - ProgressReporter<'a> borrows the AppHandle; 'a says the reporter
+//      can't outlive the handle it borrowed
+//
+// 4. SCOPED THREADS + CHANNELS:
+//    - thread::scope() threads may borrow locals; sync_channel(n) is a
+//      queue that makes senders wait once n items are waiting
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS + BENCHMARK
+// ============================================================================
+//
+// The benchmark is an ignored test, so `cargo test` stays fast. Run it with:
+//
+//   cargo test --release bench_scan_generated_tree -- --ignored --nocapture
+//
+// AURORA_BENCH_FILES changes the tree size (default 500k files).
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_database, scan, TempDir};
+
+    /// Files per generated folder (and folders per parent folder)
+    const FAN_OUT: usize = 100;
+
+    /// A scratch folder under the system temp dir (and its database),
+    /// removed on drop
+    struct TempTree(PathBuf);
+
+    impl TempTree {
+        /// `files` empty files, FAN_OUT per folder, in folders nested two deep
+        /// (a/b/file) so the walkers have subtrees to share out
+        fn generate(name: &str, files: usize) -> Self {
+            let root = std::env::temp_dir().join(format!("aurora-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            for i in 0..files {
+                let folder = i / FAN_OUT;
+                let dir = root
+                    .join(format!("d{:03}", folder / FAN_OUT))
+                    .join(format!("s{:03}", folder % FAN_OUT));
+                if i % FAN_OUT == 0 {
+                    std::fs::create_dir_all(&dir).unwrap();
+                }
+                std::fs::write(dir.join(format!("file-{}.txt", i)), b"").unwrap();
+            }
+            Self(root)
+        }
+
+        fn root(&self) -> String {
+            self.0.to_string_lossy().to_string()
+        }
+    }
+
+    impl Drop for TempTree {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+            let _ = std::fs::remove_file(self.0.with_extension("db"));
+        }
+    }
+
+    /// An aurora.db with the current schema, next to the tree
+    fn open_database(tree: &TempTree) -> rusqlite::Connection {
+        let path = tree.0.with_extension("db");
+        let _ = std::fs::remove_file(&path);
+        let mut conn = rusqlite::Connection::open(&path).unwrap();
+        crate::migrations::run_migrations(&mut conn).unwrap();
+        conn
+    }
+
+    fn file_count(conn: &rusqlite::Connection) -> usize {
+        conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get::<_, i64>(0)).unwrap() as usize
+    }
+
+    #[test]
+    fn scans_a_generated_tree_then_finds_nothing_new() {
+        let tree = TempTree::generate("scan-test", 2_500);
+        let mut conn = open_database(&tree);
+
+        assert_eq!(scan(&mut conn, &tree.root()), (2_500, 0));
+        assert_eq!(file_count(&conn), 2_500);
+
+        // Nothing changed on disk: a rescan only compares stamps
+        assert_eq!(scan(&mut conn, &tree.root()), (0, 2_500));
+        assert_eq!(file_count(&conn), 2_500);
+    }
+
+    #[test]
+    fn single_paths_follow_the_same_policy_as_the_walk() {
+        let dir = TempDir::new("scan-wants");
+        let source = dir.path().join("source");
+        let deep: String = (1..=20).map(|i| format!("d{}/", i)).collect();
+        let too_deep = dir.write(&format!("source/{}file.txt", deep), b"");
+        let deep_enough = dir.write(&format!("source/{}file.txt", &deep[..deep.len() - 4]), b"");
+        let in_hidden = dir.write("source/.hidden/a/file.txt", b"");
+
+        // A source without a policy of its own: the default policy, with
+        // depth counted from the source
+        let mut conn = memory_database();
+        crate::db::add_scan_source(&conn, &source.to_string_lossy()).unwrap();
+        let rules = IndexRules::load(&conn);
+        assert!(!rules.wants(Path::new(&too_deep)));
+        assert!(rules.wants(Path::new(&deep_enough)));
+        assert!(rules.wants(Path::new(&in_hidden)));
+
+        // A policy saved on the folder above counts from there instead
+        // (one level more to go), hidden folders included
+        let policy = ScanPolicy { include_hidden: false, max_depth: 21, ..ScanPolicy::default() };
+        crate::db::set_scan_policy(&conn, &dir.path().to_string_lossy(), &policy).unwrap();
+        let rules = IndexRules::load(&conn);
+        assert!(!rules.wants(Path::new(&in_hidden)));
+        assert!(!rules.wants(Path::new(&too_deep)));
+        assert!(rules.wants(Path::new(&deep_enough)));
+
+        // ...and the walk agrees
+        scan(&mut conn, &source.to_string_lossy());
+        let indexed: Vec<String> = crate::db::get_all_files(&conn).unwrap().into_iter().map(|f| f.path).collect();
+        assert_eq!(indexed, vec![deep_enough]);
+    }
+
+    #[test]
+    #[ignore = "benchmark: generates a 500k-file tree"]
+    fn bench_scan_generated_tree() {
+        let files = std::env::var("AURORA_BENCH_FILES")
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(500_000);
+
+        let started = Instant::now();
+        let tree = TempTree::generate("scan-bench", files);
+        println!("🌳 Generated {} files in {:.1?}", files, started.elapsed());
+        let mut conn = open_database(&tree);
+
+        let started = Instant::now();
+        let (added, _) = scan(&mut conn, &tree.root());
+        let elapsed = started.elapsed();
+        println!(
+            "⏱️  First scan: {} files in {:.1?} ({:.0} files/s, {} walker threads)",
+            added,
+            elapsed,
+            added as f64 / elapsed.as_secs_f64(),
+            walker_threads()
+        );
+        assert_eq!(added, files);
+
+        let started = Instant::now();
+        let (_, unchanged) = scan(&mut conn, &tree.root());
+        let elapsed = started.elapsed();
+        println!(
+            "⏱️  Rescan (nothing changed): {} files in {:.1?} ({:.0} files/s)",
+            unchanged,
+            elapsed,
+            unchanged as f64 / elapsed.as_secs_f64()
+        );
+        assert_eq!(unchanged, files);
+    }
+}
@@ -0,0 +1,854 @@
+// ============================================================================
+// SEARCH QUERY MODULE - Turn what the user typed into safe SQL
+// ============================================================================
+//
+// The search box used to hand its text straight to FTS5's MATCH, which has
+// its own syntax: "report-2024" or a lone " was a syntax error, and there
+// was no way to ask for "PDFs bigger than 10 MB". Now the text is parsed
+// into WORDS and FILTERS first:
+//
+//   budget "q3 plan"          words (prefix match) and exact phrases
+//   name:invoice path:work    words that must be in the name / the path
+//   -draft                    leave out files matching a word or filter
+//
+//   type:pdf  type:jpg,png  type:image     extension, or a group of them
+//   tag:urgent                Finder tag (tag:none = untagged)
+//   size:>10mb  size:<1kb  size:1mb..1gb
+//   modified:<7d  modified:>1y  created:2024-03-01  opened:today
+//   opened:never
+//   cluster:"In Progress"     (cluster:none = not in a cluster)
+//   mood:calm  season:winter  energy:high  location:paris  notes:idea
+//   in:~/Documents            only files inside this folder
+//
+// Words become an FTS5 expression where every word is a quoted string, so
+// nothing the user types can be read as FTS5 syntax. Filters become SQL
+// conditions with ? placeholders - their values travel as parameters,
+// never inside the SQL text.
+//
+// Mistakes get an error that says what's wrong and how to write it
+// ("unknown size unit 'qb' (use b, kb, mb, gb or tb)"). A filter with no
+// value yet ("type:") is skipped: the user is probably still typing.
+//
+// ============================================================================
+
+use chrono::{DateTime, Days, Local, NaiveDate};
+use rusqlite::types::Value;
+
+/// Filters the parser understands. Any other "word:" is plain text
+/// ("re:meeting", "todo:", "http://...")
+const FILTERS: &[&str] = &[
+    "type", "ext", "tag", "size", "modified", "created", "opened", "cluster",
+    "mood", "season", "energy", "location", "notes", "in", "name", "path",
+];
+
+/// type: values that stand for several extensions
+const TYPE_GROUPS: &[(&str, &[&str])] = &[
+    ("image", &["png", "jpg", "jpeg", "gif", "webp", "heic", "bmp", "tiff", "svg"]),
+    ("video", &["mp4", "mov", "m4v", "mkv", "avi", "webm"]),
+    ("audio", &["mp3", "m4a", "wav", "flac", "aac", "ogg"]),
+    ("document", &["pdf", "doc", "docx", "pages", "rtf", "odt", "txt", "md"]),
+    ("spreadsheet", &["xls", "xlsx", "numbers", "csv", "ods"]),
+    ("presentation", &["ppt", "pptx", "key", "odp"]),
+    ("archive", &["zip", "tar", "gz", "rar", "7z", "dmg"]),
+];
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A parsed search, ready to be put into a query over `files f`
+/// (with `file_metadata m` LEFT JOINed, as in db::FILE_SELECT)
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// FTS5 MATCH expression for files_fts, if any words were typed
+    pub fts: Option<String>,
+    /// The words and phrases as typed (for fuzzy matching)
+    pub words: Vec<String>,
+    /// The words and phrases part of `fts` on its own (for file contents)
+    pub words_fts: Option<String>,
+    /// The name:/path: part of `fts` on its own
+    pub column_fts: Option<String>,
+    /// The FTS5 terms of the words and of name:/path: one by one
+    /// (highlight.rs marks any of them, not only full matches)
+    pub word_terms: Vec<String>,
+    pub column_terms: Vec<String>,
+    /// notes: values (notes aren't in FTS - highlight.rs bolds these itself)
+    pub notes: Vec<String>,
+    /// SQL conditions to AND together, using ? placeholders...
+    pub conditions: Vec<String>,
+    /// ...filled in by these values, in order
+    pub params: Vec<Value>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        Self::parse_at(input, Local::now())
+    }
+
+    /// Parse with relative times ("<7d", "today") counted from `now`
+    pub fn parse_at(input: &str, now: DateTime<Local>) -> Result<Self, String> {
+        let mut query = SearchQuery::default();
+        let mut fts_terms = Vec::new();
+        let mut word_terms = Vec::new();
+        let mut column_terms = Vec::new();
+
+        for token in tokenize(input) {
+            match token {
+                Token::Text { text, phrase, negated } => {
+                    let Some(term) = fts_term(&text, phrase) else {
+                        continue;  // Only punctuation: nothing to look for
+                    };
+                    if negated {
+                        query.push(
+                            "f.id NOT IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?)".to_string(),
+                            vec![Value::Text(term)],
+                        );
+                    } else {
+                        fts_terms.push(term.clone());
+                        word_terms.push(term);
+                        query.words.push(text);
+                    }
+                }
+                Token::Filter { key, value, negated } => {
+                    if value.trim().is_empty() {
+                        continue;  // "type:" - still typing
+                    }
+                    let key = key.to_lowercase();
+                    let value = value.trim();
+                    match key.as_str() {
+                        // Column filters are FTS5 too ("name : ...")
+                        "name" | "path" => {
+                            let Some(term) = fts_term(value, false) else { continue };
+                            let term = format!("{} : {}", key, term);
+                            if negated {
+                                query.push(
+                                    "f.id NOT IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?)".to_string(),
+                                    vec![Value::Text(term)],
+                                );
+                            } else {
+                                fts_terms.push(term.clone());
+                                column_terms.push(term);
+                            }
+                        }
+                        _ => {
+                            let (sql, params) = compile_filter(&key, value, now)
+                                .map_err(|e| format!("{}:{} - {}", key, value, e))?;
+                            if negated {
+                                // IFNULL: "-opened:<7d" should keep never-opened files
+                                query.push(format!("NOT IFNULL(({}), 0)", sql), params);
+                            } else {
+                                if key == "notes" {
+                                    query.notes.push(value.to_string());
+                                }
+                                query.push(sql, params);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if !word_terms.is_empty() {
+            query.words_fts = Some(word_terms.join(" "));
+        }
+        if !column_terms.is_empty() {
+            query.column_fts = Some(column_terms.join(" "));
+        }
+        if !fts_terms.is_empty() {
+            query.fts = Some(fts_terms.join(" "));
+        }
+        query.word_terms = word_terms;
+        query.column_terms = column_terms;
+        Ok(query)
+    }
+
+    /// Nothing to search for (an empty box, or only punctuation)
+    pub fn is_empty(&self) -> bool {
+        self.fts.is_none() && self.conditions.is_empty()
+    }
+
+    fn push(&mut self, condition: String, params: Vec<Value>) {
+        self.conditions.push(condition);
+        self.params.extend(params);
+    }
+}
+
+// ============================================================================
+// TOKENIZER
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text { text: String, phrase: bool, negated: bool },
+    Filter { key: String, value: String, negated: bool },
+}
+
+/// Split the input into words, "quoted phrases" and key:value filters.
+/// Never fails: a quote that's never closed ends at the end of the input.
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else { break };
+
+        let negated = first == '-';
+        if negated {
+            chars.next();
+        }
+
+        if chars.next_if_eq(&'"').is_some() {
+            let text = read_quoted(&mut chars);
+            tokens.push(Token::Text { text, phrase: true, negated });
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut filter = None;
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            // "type:pdf" is a filter; "10:30", "re:meeting" or "http://..."
+            // is just text
+            if c == ':' && filter.is_none() && FILTERS.contains(&word.to_lowercase().as_str()) {
+                let value = if chars.next_if_eq(&'"').is_some() {
+                    read_quoted(&mut chars)
+                } else {
+                    read_word(&mut chars)
+                };
+                filter = Some(value);
+                break;
+            }
+            word.push(c);
+        }
+
+        match filter {
+            Some(value) => tokens.push(Token::Filter { key: word, value, negated }),
+            None if !word.is_empty() => tokens.push(Token::Text { text: word, phrase: false, negated }),
+            None => {}  // A lone "-"
+        }
+    }
+
+    tokens
+}
+
+/// Everything up to the closing quote (which is consumed)
+fn read_quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut text = String::new();
+    for c in chars.by_ref() {
+        if c == '"' {
+            break;
+        }
+        text.push(c);
+    }
+    text
+}
+
+/// Everything up to the next whitespace
+fn read_word(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut text = String::new();
+    while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+        text.push(c);
+    }
+    text
+}
+
+/// A word or phrase as an FTS5 string. Quoting it means FTS5 treats
+/// -, *, :, AND, NOT... as plain text; words also match as a prefix
+/// ("repo" finds "report").
+fn fts_term(text: &str, phrase: bool) -> Option<String> {
+    if !text.chars().any(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    let quoted = format!("\"{}\"", text.replace('"', "\"\""));
+    Some(if phrase { quoted } else { quoted + "*" })
+}
+
+// ============================================================================
+// FILTERS
+// ============================================================================
+
+/// SQL condition (and its parameters) for one key:value filter
+fn compile_filter(key: &str, value: &str, now: DateTime<Local>) -> Result<(String, Vec<Value>), String> {
+    match key {
+        "type" | "ext" => {
+            let mut extensions = Vec::new();
+            for item in list(value) {
+                let item = item.trim_start_matches('.').to_lowercase();
+                match TYPE_GROUPS.iter().find(|(group, _)| *group == item) {
+                    Some((_, group)) => extensions.extend(group.iter().map(|ext| ext.to_string())),
+                    None => extensions.push(item),
+                }
+            }
+            Ok(one_of("f.file_type", extensions))
+        }
+        "tag" => {
+            if value.eq_ignore_ascii_case("none") {
+                return Ok(("NOT EXISTS (SELECT 1 FROM file_tags ft WHERE ft.file_id = f.id)".to_string(), Vec::new()));
+            }
+            let (names, params) = one_of("t.name", list(value));
+            Ok((
+                format!(
+                    "EXISTS (SELECT 1 FROM file_tags ft JOIN finder_tags t ON t.id = ft.tag_id
+                             WHERE ft.file_id = f.id AND {})",
+                    names
+                ),
+                params,
+            ))
+        }
+        "size" => compile_size(value),
+        "modified" => compile_time("f.modified_at", value, now, false),
+        "created" => compile_time("f.created_at", value, now, false),
+        "opened" => compile_time("f.last_opened_at", value, now, true),
+        "cluster" => Ok(equals_or_none("f.tile_cluster", value)),
+        "mood" => Ok(equals_or_none("m.mood", value)),
+        "season" => Ok(equals_or_none("m.season", value)),
+        "energy" => Ok(equals_or_none("m.energy_level", value)),
+        "location" => Ok(contains("m.location", value)),
+        "notes" => Ok(contains("m.notes", value)),
+        "in" => {
+            let folder = match value.strip_prefix('~') {
+                Some(rest) => match std::env::var("HOME") {
+                    Ok(home) => format!("{}{}", home, rest),
+                    Err(_) => value.to_string(),
+                },
+                None => value.to_string(),
+            };
+            // Trailing slash: in:~/Doc shouldn't match ~/Documents
+            let prefix = format!("{}/", crate::scan_policy::normalize_root(&folder).trim_end_matches('/'));
+            Ok((
+                "substr(f.path, 1, ?) = ?".to_string(),
+                vec![Value::Integer(prefix.chars().count() as i64), Value::Text(prefix)],
+            ))
+        }
+        // Only keys in FILTERS get here, and every one of them is above
+        _ => Err(format!(
+            "unknown filter. Try one of: {}",
+            FILTERS.iter().map(|f| format!("{}:", f)).collect::<Vec<_>>().join(" ")
+        )),
+    }
+}
+
+/// "a, b,c" -> ["a", "b", "c"]
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// `column` is one of `values` (ignoring case)
+fn one_of(column: &str, values: Vec<String>) -> (String, Vec<Value>) {
+    let placeholders = vec!["?"; values.len()].join(", ");
+    (
+        format!("{} COLLATE NOCASE IN ({})", column, placeholders),
+        values.into_iter().map(Value::Text).collect(),
+    )
+}
+
+/// `column` is one of the listed values, or empty for "none"
+fn equals_or_none(column: &str, value: &str) -> (String, Vec<Value>) {
+    if value.eq_ignore_ascii_case("none") {
+        (format!("{} IS NULL", column), Vec::new())
+    } else {
+        one_of(column, list(value))
+    }
+}
+
+/// `column` contains `value` anywhere (ignoring case)
+fn contains(column: &str, value: &str) -> (String, Vec<Value>) {
+    // Escape LIKE's wildcards so "100%" means the text "100%"
+    let escaped = value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    (
+        format!("{} LIKE ? ESCAPE '\\'", column),
+        vec![Value::Text(format!("%{}%", escaped))],
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+/// ">=10mb" -> (GreaterOrEqual, "10mb")
+fn split_comparison(value: &str) -> (Comparison, &str) {
+    for (prefix, comparison) in [
+        (">=", Comparison::GreaterOrEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+        ("=", Comparison::Equal),
+    ] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (comparison, rest.trim());
+        }
+    }
+    (Comparison::Equal, value)
+}
+
+fn compile_size(value: &str) -> Result<(String, Vec<Value>), String> {
+    if let Some((low, high)) = value.split_once("..") {
+        let (low, high) = (parse_size(low)?, parse_size(high)?);
+        if low > high {
+            return Err("the smaller size goes first (size:1mb..10mb)".to_string());
+        }
+        return Ok((
+            "f.size BETWEEN ? AND ?".to_string(),
+            vec![Value::Integer(low), Value::Integer(high)],
+        ));
+    }
+
+    let (comparison, size) = split_comparison(value);
+    let operator = match comparison {
+        Comparison::Less => "<",
+        Comparison::LessOrEqual => "<=",
+        Comparison::Greater => ">",
+        Comparison::GreaterOrEqual => ">=",
+        Comparison::Equal => "=",
+    };
+    Ok((format!("f.size {} ?", operator), vec![Value::Integer(parse_size(size)?)]))
+}
+
+/// "10mb" -> 10485760 (sizes are 1024-based, like Finder's "Get Info")
+fn parse_size(text: &str) -> Result<i64, String> {
+    let text = text.trim().to_lowercase();
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' isn't a size (try 500kb or 1.5gb)", text))?;
+    let multiplier: f64 = match unit.trim() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1024.0,
+        "m" | "mb" => 1024.0 * 1024.0,
+        "g" | "gb" => 1024.0 * 1024.0 * 1024.0,
+        "t" | "tb" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        other => return Err(format!("unknown size unit '{}' (use b, kb, mb, gb or tb)", other)),
+    };
+    Ok((number * multiplier) as i64)
+}
+
+/// A point or span in time the user typed
+enum When {
+    /// "7d": the moment 7 days ago
+    Ago(i64),
+    /// "2024-03-01", "today": [start, end) of a day, in local time
+    Day(i64, i64),
+}
+
+fn compile_time(
+    column: &str,
+    value: &str,
+    now: DateTime<Local>,
+    allow_never: bool,
+) -> Result<(String, Vec<Value>), String> {
+    if value.eq_ignore_ascii_case("never") {
+        return if allow_never {
+            Ok((format!("{} IS NULL", column), Vec::new()))
+        } else {
+            Err("only opened: can be 'never'".to_string())
+        };
+    }
+
+    if let Some((from, to)) = value.split_once("..") {
+        let (When::Day(start, _), When::Day(_, end)) = (parse_when(from, now)?, parse_when(to, now)?) else {
+            return Err("ranges need two dates (2024-01-01..2024-03-31)".to_string());
+        };
+        if start >= end {
+            return Err("the earlier date goes first (2024-01-01..2024-03-31)".to_string());
+        }
+        return Ok((
+            format!("{} >= ? AND {} < ?", column, column),
+            vec![Value::Integer(start), Value::Integer(end)],
+        ));
+    }
+
+    let (comparison, when) = split_comparison(value);
+    let (sql, params) = match (parse_when(when, now)?, comparison) {
+        // For ages, "<7d" means LESS than 7 days old: newer than 7 days ago
+        (When::Ago(t), Comparison::Less | Comparison::LessOrEqual | Comparison::Equal) => {
+            (format!("{} >= ?", column), vec![t])
+        }
+        (When::Ago(t), Comparison::Greater | Comparison::GreaterOrEqual) => (format!("{} < ?", column), vec![t]),
+        // For dates, "<" means before and ">" means after
+        (When::Day(start, _), Comparison::Less) => (format!("{} < ?", column), vec![start]),
+        (When::Day(_, end), Comparison::LessOrEqual) => (format!("{} < ?", column), vec![end]),
+        (When::Day(_, end), Comparison::Greater) => (format!("{} >= ?", column), vec![end]),
+        (When::Day(start, _), Comparison::GreaterOrEqual) => (format!("{} >= ?", column), vec![start]),
+        (When::Day(start, end), Comparison::Equal) => {
+            (format!("{} >= ? AND {} < ?", column, column), vec![start, end])
+        }
+    };
+    Ok((sql, params.into_iter().map(Value::Integer).collect()))
+}
+
+/// "7d" / "2024-03-01" / "today" / "yesterday"
+fn parse_when(text: &str, now: DateTime<Local>) -> Result<When, String> {
+    let text = text.trim().to_lowercase();
+    let today = now.date_naive();
+
+    let day = match text.as_str() {
+        "today" => Some(today),
+        "yesterday" => today.checked_sub_days(Days::new(1)),
+        _ => NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok(),
+    };
+    if let Some(day) = day {
+        // The last date chrono knows has no next day to end at
+        let next_day = day
+            .checked_add_days(Days::new(1))
+            .ok_or_else(|| format!("'{}' is too far in the future", text))?;
+        return Ok(When::Day(start_of_day(day)?, start_of_day(next_day)?));
+    }
+    if text.contains('-') {
+        return Err(format!("'{}' isn't a date (use YYYY-MM-DD, like 2024-03-01)", text));
+    }
+
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: i64 = number.parse().map_err(|_| {
+        format!("'{}' isn't a time (try 7d, 3w, 2024-03-01, today or yesterday)", text)
+    })?;
+    let seconds = match unit {
+        "h" => 60 * 60,
+        "d" => SECONDS_PER_DAY,
+        "w" => 7 * SECONDS_PER_DAY,
+        "mo" => 30 * SECONDS_PER_DAY,
+        "y" => 365 * SECONDS_PER_DAY,
+        "m" => return Err("'m' could be minutes or months - use mo for months".to_string()),
+        other => return Err(format!("unknown time unit '{}' (use h, d, w, mo or y)", other)),
+    };
+    Ok(When::Ago(now.timestamp() - number.saturating_mul(seconds)))
+}
+
+/// Unix time of local midnight at the start of `day`
+fn start_of_day(day: NaiveDate) -> Result<i64, String> {
+    day.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map(|midnight| midnight.timestamp())
+        .ok_or_else(|| format!("{} doesn't exist in this time zone", day))
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. Peekable iterators:
+//    - chars.next_if(|c| ...) takes the next character ONLY if it passes
+//      the test, so the tokenizer can stop right before a space or quote
+//
+// 2. let-else:
+//    - `let Some(term) = ... else { continue };` unpacks a value or leaves
+//      the loop iteration early - no extra nesting
+//
+// 3. Matching on tuples:
+//    - match (parse_when(..)?, comparison) handles every combination of
+//      "age vs date" and "<, >, =" in one table-like match
+//
+// 4. rusqlite::types::Value:
+//    - An owned "any SQL value", so conditions with different parameter
+//      types (text, integers) can share one Vec
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Friday 2024-03-15, noon local time
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).single().unwrap()
+    }
+
+    fn parse(input: &str) -> SearchQuery {
+        SearchQuery::parse_at(input, now()).unwrap()
+    }
+
+    fn error(input: &str) -> String {
+        SearchQuery::parse_at(input, now()).unwrap_err()
+    }
+
+    fn text(text: &str, phrase: bool, negated: bool) -> Token {
+        Token::Text { text: text.to_string(), phrase, negated }
+    }
+
+    fn filter(key: &str, value: &str, negated: bool) -> Token {
+        Token::Filter { key: key.to_string(), value: value.to_string(), negated }
+    }
+
+    /// The one condition a single filter compiles to, with its parameters
+    fn condition(input: &str) -> (String, Vec<Value>) {
+        let query = parse(input);
+        assert_eq!(query.conditions.len(), 1, "{}", input);
+        (query.conditions[0].split_whitespace().collect::<Vec<_>>().join(" "), query.params)
+    }
+
+    fn texts(values: &[&str]) -> Vec<Value> {
+        values.iter().map(|v| Value::Text(v.to_string())).collect()
+    }
+
+    fn midnight(year: i32, month: u32, day: u32) -> i64 {
+        Local.with_ymd_and_hms(year, month, day, 0, 0, 0).earliest().unwrap().timestamp()
+    }
+
+    // ---- tokenizer ----------------------------------------------------------
+
+    #[test]
+    fn tokenizes_words_phrases_and_filters() {
+        assert_eq!(
+            tokenize(r#"  budget "q3 plan" -draft type:pdf -tag:"In Progress" "#),
+            vec![
+                text("budget", false, false),
+                text("q3 plan", true, false),
+                text("draft", false, true),
+                filter("type", "pdf", false),
+                filter("tag", "In Progress", true),
+            ]
+        );
+        // A quote that's never closed runs to the end; a lone "-" is nothing
+        assert_eq!(tokenize(r#"- "half a phrase"#), vec![text("half a phrase", true, false)]);
+        assert_eq!(tokenize(""), vec![]);
+        // Keys are matched ignoring case (parse lowercases them)
+        assert_eq!(tokenize("Type:PDF"), vec![filter("Type", "PDF", false)]);
+        assert_eq!(tokenize("size:"), vec![filter("size", "", false)]);
+    }
+
+    #[test]
+    fn only_known_keys_are_filters() {
+        for input in ["http://example.com", "re:meeting", "todo:", "10:30", "c:\\temp", "a:b:c"] {
+            assert_eq!(tokenize(input), vec![text(input, false, false)], "{}", input);
+        }
+        let query = parse("re:meeting todo: notes");
+        assert_eq!(query.words, vec!["re:meeting", "todo:", "notes"]);
+        assert_eq!(query.fts.as_deref(), Some(r#""re:meeting"* "todo:"* "notes"*"#));
+        assert!(query.conditions.is_empty());
+    }
+
+    // ---- words --------------------------------------------------------------
+
+    #[test]
+    fn fts_terms_are_always_quoted() {
+        assert_eq!(fts_term("report-2024", false).as_deref(), Some(r#""report-2024"*"#));
+        assert_eq!(fts_term("q3 plan", true).as_deref(), Some(r#""q3 plan""#));
+        assert_eq!(fts_term(r#"5" floppy"#, true).as_deref(), Some(r#""5"" floppy""#));
+        assert_eq!(fts_term("NOT", false).as_deref(), Some(r#""NOT"*"#));
+        assert_eq!(fts_term("-*:", false), None);
+        assert_eq!(fts_term("", true), None);
+    }
+
+    #[test]
+    fn words_become_one_fts_expression() {
+        let query = parse(r#"report-2024 "q3 plan name:x" name:invoice -draft ..."#);
+        assert_eq!(query.words, vec!["report-2024", "q3 plan name:x"]);
+        assert_eq!(query.words_fts.as_deref(), Some(r#""report-2024"* "q3 plan name:x""#));
+        assert_eq!(query.column_fts.as_deref(), Some(r#"name : "invoice"*"#));
+        assert_eq!(query.fts.as_deref(), Some(r#""report-2024"* "q3 plan name:x" name : "invoice"*"#));
+        assert_eq!(query.word_terms, vec![r#""report-2024"*"#, r#""q3 plan name:x""#]);
+        assert_eq!(query.column_terms, vec![r#"name : "invoice"*"#]);
+        // -draft is a condition, not part of the MATCH
+        assert_eq!(
+            query.conditions,
+            vec!["f.id NOT IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?)"]
+        );
+        assert_eq!(query.params, texts(&[r#""draft"*"#]));
+
+        // An unbalanced quote is still a phrase
+        assert_eq!(parse(r#"say "hello"#).fts.as_deref(), Some(r#""say"* "hello""#));
+        assert!(parse("  ").is_empty());
+        assert!(parse("-- ... type:").is_empty());
+    }
+
+    // ---- filters ------------------------------------------------------------
+
+    #[test]
+    fn type_and_ext_filters() {
+        assert_eq!(condition("type:pdf"), ("f.file_type COLLATE NOCASE IN (?)".to_string(), texts(&["pdf"])));
+        assert_eq!(
+            condition("ext:.JPG,png"),
+            ("f.file_type COLLATE NOCASE IN (?, ?)".to_string(), texts(&["jpg", "png"]))
+        );
+        let (sql, params) = condition("type:audio,pdf");
+        assert_eq!(sql, "f.file_type COLLATE NOCASE IN (?, ?, ?, ?, ?, ?, ?)");
+        assert_eq!(params, texts(&["mp3", "m4a", "wav", "flac", "aac", "ogg", "pdf"]));
+    }
+
+    #[test]
+    fn tag_and_none_filters() {
+        let (sql, params) = condition(r#"tag:"In Progress, urgent""#);
+        assert_eq!(
+            sql,
+            "EXISTS (SELECT 1 FROM file_tags ft JOIN finder_tags t ON t.id = ft.tag_id \
+             WHERE ft.file_id = f.id AND t.name COLLATE NOCASE IN (?, ?))"
+        );
+        assert_eq!(params, texts(&["In Progress", "urgent"]));
+        assert_eq!(
+            condition("tag:none"),
+            ("NOT EXISTS (SELECT 1 FROM file_tags ft WHERE ft.file_id = f.id)".to_string(), vec![])
+        );
+
+        assert_eq!(condition("cluster:none"), ("f.tile_cluster IS NULL".to_string(), vec![]));
+        assert_eq!(
+            condition(r#"cluster:"In Progress""#),
+            ("f.tile_cluster COLLATE NOCASE IN (?)".to_string(), texts(&["In Progress"]))
+        );
+        assert_eq!(condition("mood:calm"), ("m.mood COLLATE NOCASE IN (?)".to_string(), texts(&["calm"])));
+        assert_eq!(condition("season:NONE"), ("m.season IS NULL".to_string(), vec![]));
+        assert_eq!(
+            condition("energy:high,low"),
+            ("m.energy_level COLLATE NOCASE IN (?, ?)".to_string(), texts(&["high", "low"]))
+        );
+    }
+
+    #[test]
+    fn contains_filters_escape_like_wildcards() {
+        assert_eq!(
+            condition("location:paris"),
+            ("m.location LIKE ? ESCAPE '\\'".to_string(), texts(&["%paris%"]))
+        );
+        let query = parse(r#"notes:"100% done_ok""#);
+        assert_eq!(query.params, texts(&["%100\\% done\\_ok%"]));
+        assert_eq!(query.notes, vec!["100% done_ok"]);
+        // A negated notes: filter isn't something to highlight
+        assert!(parse("-notes:idea").notes.is_empty());
+    }
+
+    #[test]
+    fn in_filter_matches_whole_folders() {
+        let (sql, params) = condition("in:/Users/me/Docs/");
+        assert_eq!(sql, "substr(f.path, 1, ?) = ?");
+        assert_eq!(params, vec![Value::Integer(15), Value::Text("/Users/me/Docs/".to_string())]);
+
+        if let Ok(home) = std::env::var("HOME") {
+            let prefix = format!("{}/Documents/", home.trim_end_matches('/'));
+            let (_, params) = condition("in:~/Documents");
+            assert_eq!(params, vec![Value::Integer(prefix.chars().count() as i64), Value::Text(prefix)]);
+        }
+    }
+
+    #[test]
+    fn negated_filters_keep_rows_without_a_value() {
+        assert_eq!(condition("-type:pdf").0, "NOT IFNULL((f.file_type COLLATE NOCASE IN (?)), 0)");
+        assert_eq!(
+            condition("-name:draft"),
+            (
+                "f.id NOT IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?)".to_string(),
+                texts(&[r#"name : "draft"*"#])
+            )
+        );
+        // path: is a column filter like name:
+        assert_eq!(parse("path:work").column_fts.as_deref(), Some(r#"path : "work"*"#));
+    }
+
+    // ---- sizes --------------------------------------------------------------
+
+    #[test]
+    fn size_units_and_comparisons() {
+        let size = |input: &str| condition(input);
+        assert_eq!(size("size:500"), ("f.size = ?".to_string(), vec![Value::Integer(500)]));
+        assert_eq!(size("size:>10mb"), ("f.size > ?".to_string(), vec![Value::Integer(10 * 1024 * 1024)]));
+        assert_eq!(size("size:<1.5KB"), ("f.size < ?".to_string(), vec![Value::Integer(1536)]));
+        assert_eq!(size("size:>=2g"), ("f.size >= ?".to_string(), vec![Value::Integer(2 << 30)]));
+        assert_eq!(size("size:<=1t"), ("f.size <= ?".to_string(), vec![Value::Integer(1 << 40)]));
+        assert_eq!(size("size:=3b"), ("f.size = ?".to_string(), vec![Value::Integer(3)]));
+        assert_eq!(
+            size("size:1mb..1gb"),
+            ("f.size BETWEEN ? AND ?".to_string(), vec![Value::Integer(1 << 20), Value::Integer(1 << 30)])
+        );
+        assert_eq!(
+            size("size:1k..1k"),
+            ("f.size BETWEEN ? AND ?".to_string(), vec![Value::Integer(1024), Value::Integer(1024)])
+        );
+    }
+
+    #[test]
+    fn size_errors_say_how_to_write_it() {
+        assert_eq!(error("size:10qb"), "size:10qb - unknown size unit 'qb' (use b, kb, mb, gb or tb)");
+        assert_eq!(error("size:big"), "size:big - 'big' isn't a size (try 500kb or 1.5gb)");
+        assert_eq!(error("size:10mb..1mb"), "size:10mb..1mb - the smaller size goes first (size:1mb..10mb)");
+        assert!(error("size:..1mb").contains("isn't a size"));
+    }
+
+    // ---- times --------------------------------------------------------------
+
+    #[test]
+    fn ages_count_back_from_now() {
+        let now = now().timestamp();
+        let ago = |input: &str| condition(input);
+        let days = |n: i64| vec![Value::Integer(now - n * SECONDS_PER_DAY)];
+        assert_eq!(ago("modified:<7d"), ("f.modified_at >= ?".to_string(), days(7)));
+        assert_eq!(ago("modified:>1y"), ("f.modified_at < ?".to_string(), days(365)));
+        assert_eq!(ago("created:3h"), ("f.created_at >= ?".to_string(), vec![Value::Integer(now - 3 * 60 * 60)]));
+        assert_eq!(ago("opened:<=2w").1, days(14));
+        assert_eq!(ago("opened:>=6mo").1, days(180));
+    }
+
+    #[test]
+    fn days_are_local_midnight_to_midnight() {
+        let (march_1, march_2) = (midnight(2024, 3, 1), midnight(2024, 3, 2));
+        let day = |input: &str| condition(input);
+        assert_eq!(
+            day("created:2024-03-01"),
+            (
+                "f.created_at >= ? AND f.created_at < ?".to_string(),
+                vec![Value::Integer(march_1), Value::Integer(march_2)]
+            )
+        );
+        assert_eq!(day("modified:<2024-03-01"), ("f.modified_at < ?".to_string(), vec![Value::Integer(march_1)]));
+        assert_eq!(day("modified:<=2024-03-01"), ("f.modified_at < ?".to_string(), vec![Value::Integer(march_2)]));
+        assert_eq!(day("modified:>2024-03-01"), ("f.modified_at >= ?".to_string(), vec![Value::Integer(march_2)]));
+        assert_eq!(day("modified:>=2024-03-01"), ("f.modified_at >= ?".to_string(), vec![Value::Integer(march_1)]));
+
+        let (today, tomorrow) = (midnight(2024, 3, 15), midnight(2024, 3, 16));
+        assert_eq!(day("opened:today").1, vec![Value::Integer(today), Value::Integer(tomorrow)]);
+        assert_eq!(day("opened:Yesterday").1, vec![Value::Integer(midnight(2024, 3, 14)), Value::Integer(today)]);
+        assert_eq!(
+            day("modified:2024-01-01..2024-03-31"),
+            (
+                "f.modified_at >= ? AND f.modified_at < ?".to_string(),
+                vec![Value::Integer(midnight(2024, 1, 1)), Value::Integer(midnight(2024, 4, 1))]
+            )
+        );
+        assert_eq!(day("modified:today..today").1, vec![Value::Integer(today), Value::Integer(tomorrow)]);
+        assert_eq!(day("opened:never"), ("f.last_opened_at IS NULL".to_string(), vec![]));
+    }
+
+    #[test]
+    fn time_errors_say_how_to_write_it() {
+        assert_eq!(error("modified:<3m"), "modified:<3m - 'm' could be minutes or months - use mo for months");
+        assert_eq!(error("modified:7x"), "modified:7x - unknown time unit 'x' (use h, d, w, mo or y)");
+        assert_eq!(
+            error("created:soon"),
+            "created:soon - 'soon' isn't a time (try 7d, 3w, 2024-03-01, today or yesterday)"
+        );
+        assert_eq!(error("modified:never"), "modified:never - only opened: can be 'never'");
+        assert_eq!(
+            error("modified:7d..2024-01-01"),
+            "modified:7d..2024-01-01 - ranges need two dates (2024-01-01..2024-03-31)"
+        );
+        assert_eq!(
+            error("modified:2024-03-31..2024-01-01"),
+            "modified:2024-03-31..2024-01-01 - the earlier date goes first (2024-01-01..2024-03-31)"
+        );
+        assert_eq!(
+            error("created:2024-02-30"),
+            "created:2024-02-30 - '2024-02-30' isn't a date (use YYYY-MM-DD, like 2024-03-01)"
+        );
+    }
+
+    #[test]
+    fn the_last_representable_date_is_an_error_not_a_panic() {
+        let last = NaiveDate::MAX.format("%Y-%m-%d").to_string();
+        assert_eq!(NaiveDate::parse_from_str(&last, "%Y-%m-%d").ok(), Some(NaiveDate::MAX));
+
+        let message = error(&format!("modified:{}", last));
+        assert!(message.ends_with(&format!("'{}' is too far in the future", last)), "{}", message);
+        assert!(SearchQuery::parse_at(&format!("created:2024-01-01..{}", last), now()).is_err());
+    }
+}
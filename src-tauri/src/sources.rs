@@ -0,0 +1,258 @@
+// ============================================================================
+// SOURCES MODULE - The folders Aurora indexes and watches
+// ============================================================================
+//
+// A "scan source" is a folder the user added in Settings (or onboarding).
+// Sources live in the scan_sources table, so the backend knows them by
+// itself: at startup main.rs asks the file watcher to watch every enabled
+// source, without waiting for the frontend to send paths again.
+//
+// Each source also remembers how its last scan went (counts, errors),
+// which scanner.rs saves at the end of every scan.
+//
+// A disabled source stays in the list (with its policy and stats) but
+// isn't watched. Removing a source keeps its already-indexed files.
+//
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, State};
+use crate::db::DbPool;
+use crate::scan_policy::normalize_root;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSource {
+    pub id: i64,
+    pub path: String,
+    pub enabled: bool,
+    pub added_at: i64,
+    pub last_scan_at: Option<i64>,         // None = never scanned
+    pub last_scan_status: Option<String>,  // "done", "cancelled" or "missing"
+    pub last_scan_added: usize,
+    pub last_scan_updated: usize,
+    pub last_scan_removed: usize,
+    pub last_scan_unchanged: usize,
+    pub last_scan_errors: usize,
+    pub last_error: Option<String>,
+}
+
+/// A path the user picked -> the key stored in scan_sources
+fn validate_source_path(path: &str) -> Result<String, String> {
+    let path = normalize_root(path);
+    let path_obj = Path::new(&path);
+    if !path_obj.is_absolute() {
+        return Err(format!("Folder path must be absolute: {}", path));
+    }
+    if !path_obj.is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+    Ok(path)
+}
+
+/// After the list of enabled sources changed, watch the new list
+fn rewatch(app_handle: &AppHandle) {
+    if let Err(e) = crate::file_watcher::restore_from_sources(app_handle) {
+        eprintln!("⚠️  Failed to update file watcher: {}", e);
+    }
+}
+
+fn list(pool: &DbPool) -> Result<Vec<ScanSource>, String> {
+    crate::db::get_scan_sources(&pool.reader()).map_err(|e| e.to_string())
+}
+
+/// Register the valid folders among `paths`. Returns how many were new.
+fn add_sources(conn: &rusqlite::Connection, paths: &[String]) -> Result<usize, String> {
+    let mut added = 0usize;
+    for path in paths.iter().filter(|p| !p.trim().is_empty()) {
+        let path = match validate_source_path(path) {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("⚠️  Not adding scan source: {}", e);
+                continue;
+            }
+        };
+        if crate::db::add_scan_source(conn, &path).map_err(|e| e.to_string())? {
+            println!("📂 Added scan source: {}", path);
+            added += 1;
+        }
+    }
+    Ok(added)
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn sources_list(pool: State<'_, DbPool>) -> Result<Vec<ScanSource>, String> {
+    list(&pool)
+}
+
+/// Register folders (already-registered ones are left alone).
+/// Returns the full list of sources.
+#[tauri::command]
+pub async fn sources_add(
+    app_handle: AppHandle,
+    pool: State<'_, DbPool>,
+    paths: Vec<String>,
+) -> Result<Vec<ScanSource>, String> {
+    let added = add_sources(&pool.writer(), &paths)?;
+
+    // Re-adding known folders (the frontend does that on every launch)
+    // shouldn't restart the watcher
+    if added > 0 {
+        rewatch(&app_handle);
+    }
+    list(&pool)
+}
+
+/// Forget a folder (files already indexed from it are kept)
+#[tauri::command]
+pub async fn sources_remove(
+    app_handle: AppHandle,
+    pool: State<'_, DbPool>,
+    path: String,
+) -> Result<Vec<ScanSource>, String> {
+    let path = normalize_root(&path);
+    let removed = crate::db::delete_scan_source(&pool.writer(), &path).map_err(|e| e.to_string())?;
+    if removed {
+        println!("📂 Removed scan source: {}", path);
+        rewatch(&app_handle);
+    }
+    list(&pool)
+}
+
+/// Pause (or resume) watching a folder without forgetting it
+#[tauri::command]
+pub async fn sources_set_enabled(
+    app_handle: AppHandle,
+    pool: State<'_, DbPool>,
+    path: String,
+    enabled: bool,
+) -> Result<Vec<ScanSource>, String> {
+    let path = normalize_root(&path);
+    let found = crate::db::set_scan_source_enabled(&pool.writer(), &path, enabled).map_err(|e| e.to_string())?;
+    if !found {
+        return Err(format!("Not a scan source: {}", path));
+    }
+    rewatch(&app_handle);
+    list(&pool)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::SourceScanStats;
+    use crate::test_support::{file_info, memory_database, pool, TempDir};
+
+    fn paths(sources: &[ScanSource]) -> Vec<(String, bool)> {
+        sources.iter().map(|s| (s.path.clone(), s.enabled)).collect()
+    }
+
+    #[test]
+    fn only_existing_absolute_folders_are_sources() {
+        let dir = TempDir::new("sources-validate");
+        let file = dir.write("notes.txt", b"");
+        let root = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(validate_source_path(&format!(" {}/ ", root)).unwrap(), root);
+        assert!(validate_source_path("relative/folder").unwrap_err().contains("absolute"));
+        assert!(validate_source_path(&file).unwrap_err().contains("Not a folder"));
+        assert!(validate_source_path(&format!("{}/gone", root)).unwrap_err().contains("Not a folder"));
+    }
+
+    #[test]
+    fn sources_are_added_toggled_and_removed() {
+        let dir = TempDir::new("sources-crud");
+        std::fs::create_dir_all(dir.path().join("b")).unwrap();
+        std::fs::create_dir_all(dir.path().join("a")).unwrap();
+        let a = dir.path().join("a").to_string_lossy().to_string();
+        let b = dir.path().join("b").to_string_lossy().to_string();
+        let mut conn = memory_database();
+
+        // Bad and blank paths are skipped, the rest added once
+        let picked = vec![b.clone(), format!("{}/", a), "  ".to_string(), "nope".to_string()];
+        assert_eq!(add_sources(&conn, &picked).unwrap(), 2);
+        assert_eq!(add_sources(&conn, &picked).unwrap(), 0);
+        let sources = crate::db::get_scan_sources(&conn).unwrap();
+        assert_eq!(paths(&sources), vec![(a.clone(), true), (b.clone(), true)]);
+        assert!(sources.iter().all(|s| s.last_scan_at.is_none() && s.last_scan_status.is_none()));
+
+        assert!(crate::db::set_scan_source_enabled(&conn, &a, false).unwrap());
+        assert!(!crate::db::set_scan_source_enabled(&conn, "/not/a/source", false).unwrap());
+        assert_eq!(crate::db::get_enabled_source_paths(&conn).unwrap(), vec![b.clone()]);
+
+        // Removing a source keeps the files indexed from it
+        crate::db::upsert_files(&mut conn, &[file_info(&format!("{}/x.txt", a), 1, 1)]).unwrap();
+        assert!(crate::db::delete_scan_source(&conn, &a).unwrap());
+        assert!(!crate::db::delete_scan_source(&conn, &a).unwrap());
+        assert_eq!(paths(&crate::db::get_scan_sources(&conn).unwrap()), vec![(b, true)]);
+        assert_eq!(crate::db::get_all_files(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn scan_stats_are_recorded_for_sources_only() {
+        let dir = TempDir::new("sources-stats");
+        let root = dir.path().to_string_lossy().to_string();
+        let conn = memory_database();
+        add_sources(&conn, std::slice::from_ref(&root)).unwrap();
+
+        let stats = SourceScanStats {
+            path: root.clone(),
+            status: "done".to_string(),
+            added: 3,
+            updated: 2,
+            removed: 1,
+            unchanged: 7,
+            errors: 1,
+            last_error: Some("Permission denied".to_string()),
+        };
+        crate::db::record_source_scan(&conn, &stats).unwrap();
+        // A folder that isn't a source has no row to update
+        let elsewhere = SourceScanStats { path: "/elsewhere".to_string(), ..stats.clone() };
+        crate::db::record_source_scan(&conn, &elsewhere).unwrap();
+
+        let sources = crate::db::get_scan_sources(&conn).unwrap();
+        assert_eq!(sources.len(), 1);
+        let source = &sources[0];
+        assert!(source.last_scan_at.is_some());
+        assert_eq!(source.last_scan_status.as_deref(), Some("done"));
+        assert_eq!(
+            (source.last_scan_added, source.last_scan_updated, source.last_scan_removed, source.last_scan_unchanged),
+            (3, 2, 1, 7)
+        );
+        assert_eq!((source.last_scan_errors, source.last_error.as_deref()), (1, Some("Permission denied")));
+    }
+
+    #[test]
+    fn sources_come_back_after_a_restart() {
+        let dir = TempDir::new("sources-restart");
+        let folders: Vec<String> = ["photos", "work", "paused"]
+            .iter()
+            .map(|name| {
+                std::fs::create_dir_all(dir.path().join(name)).unwrap();
+                dir.path().join(name).to_string_lossy().to_string()
+            })
+            .collect();
+        {
+            let pool = pool(&dir);
+            add_sources(&pool.writer(), &folders).unwrap();
+            crate::db::set_scan_source_enabled(&pool.writer(), &folders[2], false).unwrap();
+        }
+
+        // What main.rs hands the file watcher on the next launch:
+        // the enabled sources, without the frontend sending anything
+        let pool = pool(&dir);
+        let restored = crate::db::get_enabled_source_paths(&pool.reader()).unwrap();
+        assert_eq!(restored, vec![folders[0].clone(), folders[1].clone()]);
+        // The paused one is still listed, just not watched
+        let listed = list(&pool).unwrap();
+        assert_eq!(listed.len(), 3);
+        assert!(listed.iter().any(|s| s.path == folders[2] && !s.enabled));
+    }
+}
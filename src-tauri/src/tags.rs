@@ -0,0 +1,787 @@
+// ============================================================================
+// TAGS MODULE - Reading and writing file tags (macOS Finder + freedesktop)
+// ============================================================================
+//
+// WHERE DO TAGS LIVE?
+// Not in a database! Tags are stored right on the file, in an "extended
+// attribute" (xattr). Different systems use different attributes:
+//
+// 1. macOS Finder: com.apple.metadata:_kMDItemUserTags
+//    A plist (Apple's property list format) holding strings like:
+//    ["Urgent\n6", "Work\n4", "Someday"]
+//      name ^  ^ color code (missing = no color)
+//
+// 2. Linux file managers (freedesktop spec): user.xdg.tags
+//    Plain comma-separated text: "Urgent,Work,Someday"
+//    There's no color in there, so Aurora keeps a name -> color mapping
+//    in its settings table (see TagColorMap).
+//
+// Each format is a "backend" implementing the TagBackend trait. Everything
+// else in Aurora just sees FinderTag { name, color } and doesn't care which
+// attribute it came from.
+//
+// Aurora keeps a copy in SQLite (finder_tags + file_tags) so it can show and
+// search tags quickly. When Aurora CHANGES a tag it writes both places:
+// the xattr (so Finder / the file manager sees it) and the database.
+//
+// ============================================================================
+
+use plist::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+use crate::commands::FinderTag;
+use crate::db::DbPool;
+
+/// Extended attribute key for Finder tags
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub const FINDER_TAGS_KEY: &str = "com.apple.metadata:_kMDItemUserTags";
+
+/// Extended attribute key for freedesktop tags
+pub const XDG_TAGS_KEY: &str = "user.xdg.tags";
+
+/// Settings key for the freedesktop tag color mapping
+const COLOR_MAP_SETTING: &str = "xdg_tag_colors";
+
+/// Finder color codes go from 0 (none) to 7
+const MAX_COLOR: i32 = 7;
+
+// ============================================================================
+// BACKENDS
+// ============================================================================
+//
+// RUST TRAITS:
+// A trait is like a TypeScript interface: a list of methods a type promises
+// to have. `Box<dyn TagBackend>` means "some type that implements
+// TagBackend" - we can keep different backends in one Vec.
+
+pub trait TagBackend: Send + Sync {
+    /// The extended attribute this backend reads and writes
+    fn xattr_key(&self) -> &'static str;
+
+    /// Parse the raw attribute bytes into tags
+    fn decode(&self, data: &[u8]) -> Result<Vec<FinderTag>, String>;
+
+    /// Turn tags back into raw attribute bytes
+    fn encode(&self, tags: &[FinderTag]) -> Result<Vec<u8>, String>;
+
+    /// Can this format store colors? (If not, colors come from TagColorMap)
+    fn stores_colors(&self) -> bool;
+}
+
+/// macOS Finder tags (binary plist)
+// Only built into the backend list on macOS (see TagBackends::for_platform)
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub struct FinderBackend;
+
+impl TagBackend for FinderBackend {
+    fn xattr_key(&self) -> &'static str {
+        FINDER_TAGS_KEY
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<FinderTag>, String> {
+        parse_finder_tags(data.to_vec())
+    }
+
+    fn encode(&self, tags: &[FinderTag]) -> Result<Vec<u8>, String> {
+        encode_finder_tags(tags)
+    }
+
+    fn stores_colors(&self) -> bool {
+        true
+    }
+}
+
+/// Freedesktop tags (comma-separated text, colors from a mapping)
+pub struct XdgBackend {
+    colors: TagColorMap,
+}
+
+impl TagBackend for XdgBackend {
+    fn xattr_key(&self) -> &'static str {
+        XDG_TAGS_KEY
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<FinderTag>, String> {
+        let text = String::from_utf8_lossy(data);
+        let mut tags: Vec<FinderTag> = Vec::new();
+        for name in text.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if tags.iter().any(|t| t.name == name) {
+                continue;
+            }
+            tags.push(FinderTag {
+                name: name.to_string(),
+                color: self.colors.color_for(name),
+            });
+        }
+        Ok(tags)
+    }
+
+    fn encode(&self, tags: &[FinderTag]) -> Result<Vec<u8>, String> {
+        // A comma inside a name would split it into two tags
+        if let Some(tag) = tags.iter().find(|t| t.name.contains(',')) {
+            return Err(format!("Tag names can't contain commas here: {:?}", tag.name));
+        }
+        let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+        Ok(names.join(",").into_bytes())
+    }
+
+    fn stores_colors(&self) -> bool {
+        false
+    }
+}
+
+// ============================================================================
+// COLOR MAPPING (for backends without colors)
+// ============================================================================
+
+/// Tag name (lowercase) -> Finder color code.
+/// Saved in the settings table; defaults map color-named tags to their color.
+#[derive(Debug, Clone)]
+pub struct TagColorMap(HashMap<String, i32>);
+
+impl Default for TagColorMap {
+    fn default() -> Self {
+        // Finder's own color tags, so "Red" on Linux shows up red in Aurora
+        let defaults = [
+            ("gray", 1),
+            ("grey", 1),
+            ("green", 2),
+            ("purple", 3),
+            ("blue", 4),
+            ("yellow", 5),
+            ("red", 6),
+            ("orange", 7),
+        ];
+        Self(defaults.iter().map(|(name, color)| (name.to_string(), *color)).collect())
+    }
+}
+
+impl TagColorMap {
+    /// Load the saved mapping (or the defaults if none was saved)
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        match crate::db::get_setting::<HashMap<String, i32>>(conn, COLOR_MAP_SETTING) {
+            Ok(Some(map)) => Self(map),
+            Ok(None) => Self::default(),
+            Err(e) => {
+                eprintln!("⚠️  Failed to load tag colors, using defaults: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, conn: &rusqlite::Connection) -> Result<(), String> {
+        crate::db::set_setting(conn, COLOR_MAP_SETTING, &self.0).map_err(|e| e.to_string())
+    }
+
+    pub fn color_for(&self, name: &str) -> i32 {
+        self.0.get(&name.to_lowercase()).copied().unwrap_or(0)
+    }
+
+    fn remember(&mut self, tags: &[FinderTag]) {
+        for tag in tags {
+            self.0.insert(tag.name.to_lowercase(), tag.color);
+        }
+    }
+}
+
+// ============================================================================
+// BACKEND SET - which backends this platform uses
+// ============================================================================
+//
+// Every backend is READ and the results are merged (first backend wins if a
+// tag shows up twice). On WRITE we update the platform's main backend, plus
+// any other backend whose attribute is already on the file - so a tag removed
+// in Aurora doesn't linger in the other format.
+
+pub struct TagBackends {
+    backends: Vec<Box<dyn TagBackend>>,
+}
+
+impl TagBackends {
+    /// Build the backends for this platform, reading the color mapping from SQLite
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        Self::for_platform(TagColorMap::load(conn))
+    }
+
+    pub fn for_platform(colors: TagColorMap) -> Self {
+        // On macOS, Finder is the main backend. Other systems don't allow
+        // the "com.apple." attribute namespace at all, so only XDG is used.
+        #[cfg(target_os = "macos")]
+        let backends: Vec<Box<dyn TagBackend>> =
+            vec![Box::new(FinderBackend), Box::new(XdgBackend { colors })];
+
+        #[cfg(not(target_os = "macos"))]
+        let backends: Vec<Box<dyn TagBackend>> = vec![Box::new(XdgBackend { colors })];
+
+        Self { backends }
+    }
+
+    /// Read and merge tags from every backend
+    pub fn read(&self, path: &Path) -> Result<Vec<FinderTag>, String> {
+        let mut merged: Vec<FinderTag> = Vec::new();
+        for backend in &self.backends {
+            if let Some(data) = read_raw(path, backend.xattr_key())? {
+                for tag in backend.decode(&data)? {
+                    if !merged.iter().any(|t| t.name == tag.name) {
+                        merged.push(tag);
+                    }
+                }
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Split tags into the parallel name/color lists FileInfo uses.
+    /// Unreadable tags are treated as "no tags" - they shouldn't stop indexing.
+    pub fn tag_lists(&self, path: &Path) -> (Vec<String>, Vec<i32>) {
+        self.read(path)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tag| (tag.name, tag.color))
+            .unzip()
+    }
+
+    /// Does any backend in use need colors remembered in TagColorMap?
+    fn needs_color_map(&self) -> bool {
+        self.backends.iter().any(|b| !b.stores_colors())
+    }
+}
+
+/// Read one attribute's raw bytes (None = the file doesn't have it)
+fn read_raw(path: &Path, key: &str) -> Result<Option<Vec<u8>>, String> {
+    xattr::get(path, key).map_err(|e| format!("Failed to read tags from {}: {}", path.display(), e))
+}
+
+/// Write one attribute's raw bytes back exactly (None = remove it)
+fn write_raw(path: &Path, key: &str, raw: Option<&[u8]>) -> Result<(), String> {
+    match raw {
+        Some(data) => xattr::set(path, key, data)
+            .map_err(|e| format!("Failed to write tags: {}", e)),
+        None => match xattr::remove(path, key) {
+            Ok(()) => Ok(()),
+            // Nothing to remove is fine - the file already has no tags
+            Err(_) if matches!(xattr::get(path, key), Ok(None)) => Ok(()),
+            Err(e) => Err(format!("Failed to remove tags: {}", e)),
+        },
+    }
+}
+
+// ============================================================================
+// FINDER PLIST FORMAT
+// ============================================================================
+
+/// Parse Finder tags from plist data
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_finder_tags(data: Vec<u8>) -> Result<Vec<FinderTag>, String> {
+    // Finder tags are stored as a plist (XML or binary format)
+    // For MVP, we'll use plist crate to parse it
+
+    match plist::from_bytes::<Value>(&data) {
+        Ok(Value::Array(items)) => {
+            // Tags are stored as an array of strings with color info
+            let tags: Vec<FinderTag> = items
+                .iter()
+                .filter_map(|item| {
+                    if let Value::String(tag_str) = item {
+                        // Tag format: "Name\n6" where 6 is the color code
+                        let parts: Vec<&str> = tag_str.split('\n').collect();
+                        if let Some(name) = parts.first() {
+                            let color = parts.get(1)
+                                .and_then(|c| c.parse::<i32>().ok())
+                                .unwrap_or(0);
+
+                            return Some(FinderTag {
+                                name: name.to_string(),
+                                color,
+                            });
+                        }
+                    }
+                    None
+                })
+                .collect();
+
+            Ok(tags)
+        }
+        Ok(_) => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to parse tags: {}", e)),
+    }
+}
+
+/// Turn tags back into the binary plist Finder expects
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn encode_finder_tags(tags: &[FinderTag]) -> Result<Vec<u8>, String> {
+    let items = tags
+        .iter()
+        .map(|tag| {
+            // Finder leaves the color off for uncolored tags
+            if tag.color == 0 {
+                Value::String(tag.name.clone())
+            } else {
+                Value::String(format!("{}\n{}", tag.name, tag.color))
+            }
+        })
+        .collect();
+
+    let mut data = Vec::new();
+    plist::to_writer_binary(&mut data, &Value::Array(items))
+        .map_err(|e| format!("Failed to encode tags: {}", e))?;
+    Ok(data)
+}
+
+// ============================================================================
+// WRITING
+// ============================================================================
+
+/// Apply `change` to the tags of every file in `paths`, all-or-nothing.
+///
+/// HOW "ALL-OR-NOTHING" WORKS HERE:
+/// 1. Read every file's current tags first (any failure = nothing written)
+/// 2. Open ONE database transaction
+/// 3. For each file: write the xattr(s), then update file_tags
+/// 4. If anything fails, put the original xattr bytes back on the files we
+///    already touched and let the transaction roll back
+fn update_tags<F>(pool: &DbPool, paths: &[String], mut change: F) -> Result<(), String>
+where
+    F: FnMut(&mut Vec<FinderTag>),
+{
+    let backends = TagBackends::load(&pool.reader());
+
+    // Step 1: snapshot (raw bytes per backend for rollback + merged tags to edit)
+    struct Plan<'a> {
+        path: &'a Path,
+        raw: Vec<Option<Vec<u8>>>,  // Same order as backends.backends
+        tags: Vec<FinderTag>,
+    }
+    let mut plans = Vec::with_capacity(paths.len());
+    for path in paths {
+        let path = Path::new(path);
+        let raw = backends
+            .backends
+            .iter()
+            .map(|b| read_raw(path, b.xattr_key()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut tags = backends.read(path)?;
+        change(&mut tags);
+        plans.push(Plan { path, raw, tags });
+    }
+
+    // Step 2: one transaction for every database change
+    let mut conn = pool.writer();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    // Step 3: write files + database, remembering what we touched
+    let mut written: Vec<(&Path, &'static str, Option<&[u8]>)> = Vec::new();
+    let mut failure: Option<String> = None;
+    'files: for plan in &plans {
+        for (idx, backend) in backends.backends.iter().enumerate() {
+            // The main backend (index 0) is always written; others only if present
+            let original = plan.raw[idx].as_deref();
+            if idx > 0 && original.is_none() {
+                continue;
+            }
+
+            let result = if plan.tags.is_empty() {
+                write_raw(plan.path, backend.xattr_key(), None)
+            } else {
+                backend
+                    .encode(&plan.tags)
+                    .and_then(|data| write_raw(plan.path, backend.xattr_key(), Some(&data)))
+            };
+            if let Err(e) = result {
+                failure = Some(format!("{}: {}", plan.path.display(), e));
+                break 'files;
+            }
+            written.push((plan.path, backend.xattr_key(), original));
+        }
+
+        let (names, colors): (Vec<String>, Vec<i32>) =
+            plan.tags.iter().map(|t| (t.name.clone(), t.color)).unzip();
+        if let Err(e) = crate::db::set_file_tags(&tx, &plan.path.to_string_lossy(), &names, &colors) {
+            failure = Some(format!("{}: {}", plan.path.display(), e));
+            break;
+        }
+    }
+
+    // Formats without colors (XDG) can't remember them - keep them in the mapping
+    if failure.is_none() && backends.needs_color_map() {
+        let mut colors = TagColorMap::load(&tx);
+        for plan in &plans {
+            colors.remember(&plan.tags);
+        }
+        if let Err(e) = colors.save(&tx) {
+            failure = Some(e);
+        }
+    }
+
+    let result = match failure {
+        Some(e) => Err(e),
+        None => tx.commit().map_err(|e| e.to_string()),
+    };
+
+    // Step 4: undo the xattr writes if the batch didn't make it
+    if let Err(e) = result {
+        for (path, key, raw) in written.into_iter().rev() {
+            if let Err(undo) = write_raw(path, key, raw) {
+                eprintln!("⚠️  Could not restore tags on {}: {}", path.display(), undo);
+            }
+        }
+        return Err(format!("Failed to update tags: {}", e));
+    }
+
+    Ok(())
+}
+
+/// tags_add's edit: new tags are appended, existing ones get the new color
+fn add_tags(current: &mut Vec<FinderTag>, tags: &[FinderTag]) {
+    for tag in tags {
+        let name = tag.name.trim();
+        match current.iter_mut().find(|t| t.name == name) {
+            Some(existing) => existing.color = tag.color,
+            None => current.push(FinderTag { name: name.to_string(), color: tag.color }),
+        }
+    }
+}
+
+/// tags_remove's edit
+fn remove_tags(current: &mut Vec<FinderTag>, names: &[String]) {
+    current.retain(|t| !names.contains(&t.name));
+}
+
+/// tags_set_color's edit (files without the tag don't get it)
+fn set_tag_color(current: &mut [FinderTag], name: &str, color: i32) {
+    for tag in current.iter_mut().filter(|t| t.name == name) {
+        tag.color = color;
+    }
+}
+
+fn validate_color(color: i32) -> Result<(), String> {
+    if (0..=MAX_COLOR).contains(&color) {
+        Ok(())
+    } else {
+        Err(format!("Invalid tag color {} (expected 0-{})", color, MAX_COLOR))
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+//
+// Every command takes a list of paths, so "tag one file" is just a list of one.
+
+/// Add tags to files. A tag the file already has gets the new color.
+#[tauri::command]
+pub async fn tags_add(pool: State<'_, DbPool>, paths: Vec<String>, tags: Vec<FinderTag>) -> Result<(), String> {
+    for tag in &tags {
+        validate_color(tag.color)?;
+        if tag.name.trim().is_empty() || tag.name.contains('\n') {
+            return Err(format!("Invalid tag name: {:?}", tag.name));
+        }
+    }
+
+    update_tags(&pool, &paths, |current| add_tags(current, &tags))
+}
+
+/// Remove tags (by name) from files
+#[tauri::command]
+pub async fn tags_remove(pool: State<'_, DbPool>, paths: Vec<String>, names: Vec<String>) -> Result<(), String> {
+    update_tags(&pool, &paths, |current| remove_tags(current, &names))
+}
+
+/// Change the color of a tag on files that have it
+#[tauri::command]
+pub async fn tags_set_color(
+    pool: State<'_, DbPool>,
+    paths: Vec<String>,
+    name: String,
+    color: i32,
+) -> Result<(), String> {
+    validate_color(color)?;
+    update_tags(&pool, &paths, |current| set_tag_color(current, &name, color))
+}
+
+/// The tag name -> color mapping used for tags that don't store a color
+#[tauri::command]
+pub async fn tags_get_color_map(pool: State<'_, DbPool>) -> Result<HashMap<String, i32>, String> {
+    Ok(TagColorMap::load(&pool.reader()).0)
+}
+
+/// Replace the tag name -> color mapping
+#[tauri::command]
+pub async fn tags_set_color_map(pool: State<'_, DbPool>, colors: HashMap<String, i32>) -> Result<(), String> {
+    let mut map = HashMap::with_capacity(colors.len());
+    for (name, color) in colors {
+        validate_color(color)?;
+        map.insert(name.trim().to_lowercase(), color);
+    }
+    TagColorMap(map).save(&pool.writer())
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. TRAITS + TRAIT OBJECTS:
+//    - `trait TagBackend` declares the methods every backend has
+//    - `Box<dyn TagBackend>` stores any backend behind a pointer
+//
+// 2. #[cfg(...)]:
+//    - Compile-time "if" - code for other platforms isn't even built
+//
+// 3. LABELED BREAK:
+//    - `'files: for ...` names a loop; `break 'files` exits it from inside
+//      an inner loop
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{memory_database, pool, scan, TempDir};
+
+    fn tag(name: &str, color: i32) -> FinderTag {
+        FinderTag { name: name.to_string(), color }
+    }
+
+    fn pairs(tags: &[FinderTag]) -> Vec<(String, i32)> {
+        tags.iter().map(|t| (t.name.clone(), t.color)).collect()
+    }
+
+    /// A plist array of strings, the way Finder writes it
+    fn finder_plist(items: &[&str]) -> Vec<u8> {
+        let items = items.iter().map(|item| Value::String(item.to_string())).collect();
+        let mut data = Vec::new();
+        plist::to_writer_binary(&mut data, &Value::Array(items)).unwrap();
+        data
+    }
+
+    #[test]
+    fn finder_color_suffixes() {
+        let tags = parse_finder_tags(finder_plist(&["Red\n6", "Work\n4"])).unwrap();
+        assert_eq!(pairs(&tags), vec![("Red".to_string(), 6), ("Work".to_string(), 4)]);
+    }
+
+    #[test]
+    fn finder_tags_without_a_color() {
+        let tags = parse_finder_tags(finder_plist(&["Someday", "Odd\nnot-a-number"])).unwrap();
+        assert_eq!(pairs(&tags), vec![("Someday".to_string(), 0), ("Odd".to_string(), 0)]);
+    }
+
+    #[test]
+    fn finder_xml_plist() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0"><array><string>Green
+2</string><string>Home</string></array></plist>"#;
+        let tags = parse_finder_tags(xml.to_vec()).unwrap();
+        assert_eq!(pairs(&tags), vec![("Green".to_string(), 2), ("Home".to_string(), 0)]);
+    }
+
+    #[test]
+    fn finder_round_trip() {
+        let tags = vec![tag("Red", 6), tag("Project X", 0), tag("Café ☕", 3)];
+        let data = encode_finder_tags(&tags).unwrap();
+        assert_eq!(pairs(&parse_finder_tags(data).unwrap()), pairs(&tags));
+
+        // Uncolored tags are written without a suffix, like Finder does
+        let Value::Array(items) = plist::from_bytes::<Value>(&encode_finder_tags(&tags).unwrap()).unwrap() else {
+            panic!("not an array");
+        };
+        assert_eq!(items[0], Value::String("Red\n6".to_string()));
+        assert_eq!(items[1], Value::String("Project X".to_string()));
+    }
+
+    #[test]
+    fn finder_empty_and_garbage_payloads() {
+        assert!(parse_finder_tags(finder_plist(&[])).unwrap().is_empty());
+        assert!(parse_finder_tags(Vec::new()).is_err());
+        assert!(parse_finder_tags(b"definitely not a plist".to_vec()).is_err());
+
+        // A valid plist that isn't a list of tags has no tags
+        let mut dict = Vec::new();
+        plist::to_writer_binary(&mut dict, &Value::Dictionary(plist::Dictionary::new())).unwrap();
+        assert!(parse_finder_tags(dict).unwrap().is_empty());
+        assert!(encode_finder_tags(&[]).is_ok_and(|data| parse_finder_tags(data).unwrap().is_empty()));
+    }
+
+    #[test]
+    fn xdg_comma_format() {
+        let backend = XdgBackend { colors: TagColorMap::default() };
+
+        // Spaces trimmed, empties and repeats dropped, colors from the map
+        let tags = backend.decode(b" red, Work ,,Work,Blue ").unwrap();
+        assert_eq!(
+            pairs(&tags),
+            vec![("red".to_string(), 6), ("Work".to_string(), 0), ("Blue".to_string(), 4)]
+        );
+        assert!(backend.decode(b"").unwrap().is_empty());
+
+        let data = backend.encode(&[tag("Red", 6), tag("Work", 0)]).unwrap();
+        assert_eq!(data, b"Red,Work");
+        assert_eq!(pairs(&backend.decode(&data).unwrap()), vec![("Red".to_string(), 6), ("Work".to_string(), 0)]);
+
+        assert!(backend.encode(&[tag("a,b", 0)]).is_err());
+        assert!(backend.encode(&[]).unwrap().is_empty());
+    }
+
+    /// Put `data` in the `key` xattr, or false if this filesystem can't
+    /// store user xattrs (the scan tests are skipped then)
+    fn set_xattr(path: &str, key: &str, data: &[u8]) -> bool {
+        match xattr::set(path, key, data) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("⚠️  Skipping: no xattrs on {}: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// (name, color) pairs of the row at `path`, as get_all_files returns it
+    fn indexed_tags(conn: &rusqlite::Connection, path: &str) -> Vec<(String, i32)> {
+        let files = crate::db::get_all_files(conn).unwrap();
+        let file = files.iter().find(|f| f.path == path).unwrap();
+        file.finder_tags.iter().cloned().zip(file.finder_colors.iter().copied()).collect()
+    }
+
+    #[test]
+    fn scanning_stores_xdg_tags_from_xattrs() {
+        let dir = TempDir::new("tags-scan");
+        let tagged = dir.write("tagged.txt", b"trip notes");
+        let untagged = dir.write("untagged.txt", b"other notes");
+        if !set_xattr(&tagged, XDG_TAGS_KEY, b"Red,Lisbon") {
+            return;
+        }
+
+        let mut conn = memory_database();
+        scan(&mut conn, &dir.path().to_string_lossy());
+
+        // Colors for xdg tags come from the color map ("Red" is red).
+        // File rows list their tags by name.
+        assert_eq!(indexed_tags(&conn, &tagged), vec![("Lisbon".to_string(), 0), ("Red".to_string(), 6)]);
+        assert_eq!(indexed_tags(&conn, &untagged), vec![]);
+
+        // ...and they're in file_tags, which search and filters use
+        let tag_rows: Vec<String> = conn
+            .prepare(
+                "SELECT t.name FROM file_tags ft
+                 JOIN finder_tags t ON t.id = ft.tag_id
+                 JOIN files f ON f.id = ft.file_id
+                 WHERE f.path = ?1 ORDER BY t.name",
+            )
+            .unwrap()
+            .query_map([&tagged], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tag_rows, vec!["Lisbon".to_string(), "Red".to_string()]);
+    }
+
+    #[test]
+    fn rescanning_picks_up_tags_edited_outside_the_app() {
+        let dir = TempDir::new("tags-rescan");
+        let path = dir.write("notes.txt", b"trip notes");
+        let mut conn = memory_database();
+        let root = dir.path().to_string_lossy().to_string();
+        scan(&mut conn, &root);
+        assert_eq!(scan(&mut conn, &root), (0, 1));
+
+        // Tagging only touches an xattr: size and mtime stay the same
+        if !set_xattr(&path, XDG_TAGS_KEY, b"Lisbon") {
+            return;
+        }
+        assert_eq!(scan(&mut conn, &root), (0, 0), "the tag edit should count as a change");
+        assert_eq!(indexed_tags(&conn, &path), vec![("Lisbon".to_string(), 0)]);
+
+        xattr::remove(&path, XDG_TAGS_KEY).unwrap();
+        scan(&mut conn, &root);
+        assert_eq!(indexed_tags(&conn, &path), vec![]);
+        assert_eq!(scan(&mut conn, &root), (0, 1));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn scanning_stores_finder_tags_from_xattrs() {
+        let dir = TempDir::new("tags-scan");
+        let tagged = dir.write("tagged.txt", b"trip notes");
+        if !set_xattr(&tagged, FINDER_TAGS_KEY, &finder_plist(&["Urgent\n6", "Work"])) {
+            return;
+        }
+
+        let mut conn = memory_database();
+        scan(&mut conn, &dir.path().to_string_lossy());
+
+        assert_eq!(indexed_tags(&conn, &tagged), vec![("Urgent".to_string(), 6), ("Work".to_string(), 0)]);
+    }
+
+    /// Two scanned files under `dir`, the first tagged "Work" (None if
+    /// this filesystem has no user xattrs)
+    fn tagged_pair(dir: &TempDir, pool: &DbPool) -> Option<(String, String)> {
+        let first = dir.write("files/first.txt", b"one");
+        let second = dir.write("files/second.txt", b"two");
+        if !set_xattr(&first, XDG_TAGS_KEY, b"Work") {
+            return None;
+        }
+        scan(&mut pool.writer(), &dir.path().join("files").to_string_lossy());
+        Some((first, second))
+    }
+
+    #[test]
+    fn update_tags_writes_xattrs_database_and_colors() {
+        let dir = TempDir::new("tags-update");
+        let pool = pool(&dir);
+        let Some((first, second)) = tagged_pair(&dir, &pool) else {
+            return;
+        };
+        let paths = vec![first.clone(), second.clone()];
+
+        update_tags(&pool, &paths, |current| add_tags(current, &[tag(" Lisbon ", 4)])).unwrap();
+        assert_eq!(read_raw(Path::new(&first), XDG_TAGS_KEY).unwrap(), Some(b"Work,Lisbon".to_vec()));
+        assert_eq!(read_raw(Path::new(&second), XDG_TAGS_KEY).unwrap(), Some(b"Lisbon".to_vec()));
+        assert_eq!(indexed_tags(&pool.reader(), &second), vec![("Lisbon".to_string(), 4)]);
+        // xdg can't store the color, so the mapping remembers it
+        assert_eq!(TagColorMap::load(&pool.reader()).color_for("lisbon"), 4);
+
+        // Recoloring only touches files that have the tag
+        update_tags(&pool, &paths, |current| set_tag_color(current, "Work", 2)).unwrap();
+        assert_eq!(indexed_tags(&pool.reader(), &first), vec![("Lisbon".to_string(), 4), ("Work".to_string(), 2)]);
+        assert_eq!(indexed_tags(&pool.reader(), &second), vec![("Lisbon".to_string(), 4)]);
+
+        // Removing the last tag removes the attribute
+        update_tags(&pool, &paths, |current| remove_tags(current, &["Lisbon".to_string()])).unwrap();
+        assert_eq!(read_raw(Path::new(&first), XDG_TAGS_KEY).unwrap(), Some(b"Work".to_vec()));
+        assert_eq!(read_raw(Path::new(&second), XDG_TAGS_KEY).unwrap(), None);
+        assert_eq!(indexed_tags(&pool.reader(), &first), vec![("Work".to_string(), 2)]);
+        assert_eq!(indexed_tags(&pool.reader(), &second), vec![]);
+    }
+
+    #[test]
+    fn failed_update_puts_the_old_xattrs_back() {
+        let dir = TempDir::new("tags-update");
+        let pool = pool(&dir);
+        let Some((first, second)) = tagged_pair(&dir, &pool) else {
+            return;
+        };
+
+        // The first file gets its new tags written, then the second one
+        // fails (xdg tags can't hold a comma)
+        let mut calls = 0;
+        let error = update_tags(&pool, &[first.clone(), second.clone()], |current| {
+            calls += 1;
+            let name = if calls == 1 { "Lisbon" } else { "Lisbon, 2024" };
+            current.push(tag(name, 0));
+        })
+        .unwrap_err();
+        assert!(error.contains("can't contain commas"), "{}", error);
+
+        assert_eq!(read_raw(Path::new(&first), XDG_TAGS_KEY).unwrap(), Some(b"Work".to_vec()));
+        assert_eq!(read_raw(Path::new(&second), XDG_TAGS_KEY).unwrap(), None);
+        // ...and the database transaction rolled back too
+        assert_eq!(indexed_tags(&pool.reader(), &first), vec![("Work".to_string(), 0)]);
+        assert_eq!(indexed_tags(&pool.reader(), &second), vec![]);
+        assert_eq!(TagColorMap::load(&pool.reader()).color_for("lisbon"), 0);
+    }
+}
@@ -0,0 +1,132 @@
+// ============================================================================
+// TEST SUPPORT - Scratch folders, databases and rows for unit tests
+// ============================================================================
+//
+// Only compiled for `cargo test` (see the #[cfg(test)] in main.rs). Tests
+// that touch the disk get their own folder under the system temp dir, so
+// they can run in parallel without seeing each other's files.
+//
+// ============================================================================
+
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use crate::commands::FileInfo;
+use crate::db::DbPool;
+use crate::scanner::{walk_changes, IndexRules, WalkStats, BATCH_SIZE};
+
+/// Makes every TempDir name unique within one test run
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// A scratch folder under the system temp dir, removed on drop
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let id = NEXT_DIR.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("aurora-{}-{}-{}", name, std::process::id(), id));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Write a file (creating its folders) and return its path
+    pub fn write(&self, relative: &str, contents: &[u8]) -> String {
+        let path = self.0.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().to_string()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// An in-memory database with the current schema, set up like the
+/// pooled connections (foreign keys on, so deletes cascade)
+pub fn memory_database() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+    crate::migrations::run_migrations(&mut conn).unwrap();
+    conn
+}
+
+/// A full connection pool on an aurora.db inside `dir`
+pub fn pool(dir: &TempDir) -> DbPool {
+    crate::db::open_database(&dir.path().join("aurora.db")).unwrap()
+}
+
+/// What run_scan does, minus the app: walk, and save in batches.
+/// Returns (added, unchanged).
+pub fn scan(conn: &mut Connection, root: &str) -> (usize, usize) {
+    let rules = IndexRules::load(conn).with_roots(&[root.to_string()]);
+    let known = crate::db::get_file_stamps_under(conn, root).unwrap();
+    let known_count = known.len();
+    let stats = WalkStats::new(&[root.to_string()]);
+    let cancel = AtomicBool::new(false);
+
+    let (mut added, mut updated) = (0, 0);
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let missing = walk_changes(
+        &[root.to_string()],
+        &rules,
+        known,
+        &stats,
+        &cancel,
+        |file_info, was_known| {
+            if was_known {
+                updated += 1;
+            } else {
+                added += 1;
+            }
+            batch.push(file_info);
+            if batch.len() >= BATCH_SIZE {
+                crate::db::upsert_files(conn, &batch).unwrap();
+                batch.clear();
+            }
+        },
+        || {},
+    );
+    crate::db::upsert_files(conn, &batch).unwrap();
+
+    // Whatever was known and neither changed nor missing is unchanged
+    (added, known_count - updated - missing.len())
+}
+
+/// A row as a scan would build it for a file that isn't on disk
+pub fn file_info(path: &str, size: u64, modified_at: i64) -> FileInfo {
+    let name = Path::new(path).file_name().unwrap().to_string_lossy().to_string();
+    let file_type = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    FileInfo {
+        id: None,
+        path: path.to_string(),
+        name,
+        file_type,
+        size,
+        created_at: modified_at,
+        modified_at,
+        last_opened_at: None,
+        thumbnail_path: None,
+        finder_tags: Vec::new(),
+        finder_colors: Vec::new(),
+        metadata: None,
+        inode: None,
+        changed_at: None,
+    }
+}
+
+/// The id of the row at `path`
+pub fn file_id(conn: &Connection, path: &str) -> i64 {
+    conn.query_row("SELECT id FROM files WHERE path = ?1", [path], |row| row.get(0))
+        .unwrap()
+}
@@ -0,0 +1,425 @@
+// ============================================================================
+// THUMBNAILS MODULE - Image previews for file tiles
+// ============================================================================
+//
+// HOW IT WORKS:
+// 1. Read the image file and hash its bytes with BLAKE3
+// 2. The hash becomes the thumbnail's file name ("content-addressed cache")
+//    - Two copies of the same photo share one thumbnail
+//    - Editing a photo changes its hash, so the old thumbnail is never reused
+// 3. Decode the image, shrink it, and save it as PNG under:
+//    <app data dir>/thumbnails/<first 2 hash chars>/<hash>-<size>.png
+// 4. Remember the path in files.thumbnail_path so the UI can show it
+//
+// Decoding and resizing is slow-ish (tens of milliseconds per photo), so it
+// happens on a small pool of background threads. Each finished file emits a
+// "thumbnail-progress" event to the frontend.
+//
+// ============================================================================
+
+use image::ImageFormat;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::db::DbPool;
+
+/// Sizes (longest edge, in pixels) generated when the caller doesn't ask for any.
+/// The FIRST size is the one stored in files.thumbnail_path.
+pub const DEFAULT_SIZES: &[u32] = &[256];
+
+/// Smallest and largest size we'll agree to generate
+const MIN_SIZE: u32 = 16;
+const MAX_SIZE: u32 = 2048;
+
+/// Skip enormous files - decoding a 200MB TIFF-in-disguise would eat all RAM
+const MAX_SOURCE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// How many background threads decode images at once
+const WORKER_COUNT: usize = 2;
+
+/// File extensions we know how to decode
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp"];
+
+/// Can we make a thumbnail for this file?
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SUPPORTED_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// ============================================================================
+// GENERATION (runs on a background thread)
+// ============================================================================
+
+/// Create (or reuse) thumbnails for one file.
+/// Returns the cache path for each requested size, in the same order.
+pub fn generate(source: &Path, cache_dir: &Path, sizes: &[u32]) -> Result<Vec<PathBuf>, String> {
+    if !is_supported(source) {
+        return Err(format!("Unsupported image type: {}", source.display()));
+    }
+
+    let len = std::fs::metadata(source)
+        .map_err(|e| format!("Failed to read metadata: {}", e))?
+        .len();
+    if len > MAX_SOURCE_BYTES {
+        return Err(format!("Image is too large for a thumbnail ({} bytes)", len));
+    }
+
+    let bytes = std::fs::read(source).map_err(|e| format!("Failed to read image: {}", e))?;
+    let hash = blake3::hash(&bytes).to_hex().to_string();
+    let bucket = cache_dir.join(&hash[..2]);
+
+    // Decode lazily: if every size is already cached we never decode at all
+    let mut decoded: Option<image::DynamicImage> = None;
+    let mut outputs = Vec::with_capacity(sizes.len());
+
+    for &size in sizes {
+        let output = bucket.join(format!("{}-{}.png", hash, size));
+
+        if !output.exists() {
+            if decoded.is_none() {
+                decoded = Some(
+                    image::load_from_memory(&bytes)
+                        .map_err(|e| format!("Failed to decode image: {}", e))?,
+                );
+            }
+            let image = decoded.as_ref().expect("decoded above");
+
+            std::fs::create_dir_all(&bucket)
+                .map_err(|e| format!("Failed to create thumbnail cache: {}", e))?;
+
+            // thumbnail() keeps the aspect ratio and fits inside size x size.
+            // We write to a temp file and rename it, so a crash mid-write
+            // never leaves a half-written PNG in the cache.
+            let tmp = output.with_extension("png.tmp");
+            image
+                .thumbnail(size, size)
+                .save_with_format(&tmp, ImageFormat::Png)
+                .map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+            std::fs::rename(&tmp, &output)
+                .map_err(|e| format!("Failed to store thumbnail: {}", e))?;
+        }
+
+        outputs.push(output);
+    }
+
+    Ok(outputs)
+}
+
+/// Keep only sane sizes, falling back to DEFAULT_SIZES
+fn normalize_sizes(sizes: Option<Vec<u32>>) -> Vec<u32> {
+    let mut sizes: Vec<u32> = sizes
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| s.clamp(MIN_SIZE, MAX_SIZE))
+        .collect();
+    // Remove duplicates but keep the caller's order (the first size matters)
+    let mut seen = HashSet::new();
+    sizes.retain(|s| seen.insert(*s));
+
+    if sizes.is_empty() {
+        DEFAULT_SIZES.to_vec()
+    } else {
+        sizes
+    }
+}
+
+// ============================================================================
+// BACKGROUND WORKER POOL (Tauri managed state)
+// ============================================================================
+
+struct ThumbnailJob {
+    path: String,
+    sizes: Vec<u32>,
+}
+
+/// Payload for the "thumbnail-progress" event
+#[derive(Debug, Clone, Serialize)]
+pub struct ThumbnailProgress {
+    pub path: String,
+    pub thumbnail_path: Option<String>,
+    pub error: Option<String>,
+    pub completed: usize,  // Jobs finished in the current batch
+    pub total: usize,      // Jobs queued in the current batch
+}
+
+pub struct ThumbnailQueue {
+    tx: Mutex<mpsc::Sender<ThumbnailJob>>,
+    cache_dir: PathBuf,
+    // Paths waiting in the queue, so a rescan doesn't queue the same file twice
+    pending: Arc<Mutex<HashSet<String>>>,
+    // Jobs queued in the current batch (the workers own the "completed" count)
+    total: Arc<AtomicUsize>,
+}
+
+impl ThumbnailQueue {
+    /// Spawn the worker threads. Call once from main.rs setup().
+    pub fn start(app_handle: AppHandle, cache_dir: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel::<ThumbnailJob>();
+
+        // RUST CONCURRENCY:
+        // mpsc = "multi-producer, single-consumer". To let several workers
+        // pull from ONE queue we share the receiver behind Arc<Mutex<...>>.
+        let rx = Arc::new(Mutex::new(rx));
+        let pending = Arc::new(Mutex::new(HashSet::new()));
+        let completed = Arc::new(AtomicUsize::new(0));
+        let total = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..WORKER_COUNT {
+            let rx = Arc::clone(&rx);
+            let app_handle = app_handle.clone();
+            let cache_dir = cache_dir.clone();
+            let pending = Arc::clone(&pending);
+            let completed = Arc::clone(&completed);
+            let total = Arc::clone(&total);
+
+            std::thread::spawn(move || loop {
+                // Hold the lock only while waiting for the next job
+                let job = match rx.lock() {
+                    Ok(guard) => guard.recv(),
+                    Err(_) => break,
+                };
+                let Ok(job) = job else {
+                    break;  // Sender dropped: the app is shutting down
+                };
+
+                let result = generate(Path::new(&job.path), &cache_dir, &job.sizes)
+                    .and_then(|outputs| record_thumbnail(&app_handle, &job.path, &outputs));
+
+                if let Ok(mut set) = pending.lock() {
+                    set.remove(&job.path);
+                }
+
+                let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                let queued = total.load(Ordering::SeqCst);
+                if let Err(e) = &result {
+                    eprintln!("⚠️  Thumbnail failed for {}: {}", job.path, e);
+                }
+
+                app_handle
+                    .emit(
+                        "thumbnail-progress",
+                        ThumbnailProgress {
+                            path: job.path,
+                            thumbnail_path: result.as_ref().ok().cloned(),
+                            error: result.err(),
+                            completed: done,
+                            total: queued,
+                        },
+                    )
+                    .ok();
+
+                // Batch finished: start counting from zero next time
+                if done >= queued {
+                    let _ = completed.compare_exchange(done, 0, Ordering::SeqCst, Ordering::SeqCst);
+                    let _ = total.compare_exchange(queued, 0, Ordering::SeqCst, Ordering::SeqCst);
+                }
+            });
+        }
+
+        println!("🖼️  Thumbnail workers ready ({} threads)", WORKER_COUNT);
+
+        Self {
+            tx: Mutex::new(tx),
+            cache_dir,
+            pending,
+            total,
+        }
+    }
+
+    /// Queue files for background thumbnail generation.
+    /// Unsupported and already-queued files are skipped. Returns how many were queued.
+    pub fn enqueue<I: IntoIterator<Item = String>>(&self, paths: I, sizes: &[u32]) -> usize {
+        let Ok(tx) = self.tx.lock() else {
+            return 0;
+        };
+        let Ok(mut pending) = self.pending.lock() else {
+            return 0;
+        };
+
+        let mut queued = 0usize;
+        for path in paths {
+            if !is_supported(Path::new(&path)) || !pending.insert(path.clone()) {
+                continue;
+            }
+            self.total.fetch_add(1, Ordering::SeqCst);
+            if tx.send(ThumbnailJob { path, sizes: sizes.to_vec() }).is_ok() {
+                queued += 1;
+            }
+        }
+
+        queued
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+}
+
+/// Save the primary thumbnail path in the files table.
+/// Returns that path as a String (what the frontend receives).
+fn record_thumbnail(app_handle: &AppHandle, source: &str, outputs: &[PathBuf]) -> Result<String, String> {
+    let primary = outputs
+        .first()
+        .map(|p| p.to_string_lossy().to_string())
+        .ok_or_else(|| "No thumbnail sizes requested".to_string())?;
+
+    let pool = app_handle.state::<DbPool>();
+    crate::db::set_thumbnail_path(&pool.writer(), source, &primary).map_err(|e| e.to_string())?;
+
+    Ok(primary)
+}
+
+/// Queue thumbnails for every indexed image that doesn't have one yet.
+/// Called after scans so new photos get previews without the UI asking.
+pub fn enqueue_missing(app_handle: &AppHandle) {
+    let pool = app_handle.state::<DbPool>();
+    let queue = app_handle.state::<ThumbnailQueue>();
+
+    let missing = crate::db::files_missing_thumbnails(&pool.reader(), SUPPORTED_EXTENSIONS);
+    match missing {
+        Ok(paths) => {
+            let queued = queue.enqueue(paths, DEFAULT_SIZES);
+            if queued > 0 {
+                println!("🖼️  Queued {} thumbnails", queued);
+            }
+        }
+        Err(e) => eprintln!("⚠️  Failed to look up missing thumbnails: {}", e),
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Generate one thumbnail right now and return its path.
+/// `size` defaults to the first of DEFAULT_SIZES.
+#[tauri::command]
+pub async fn generate_thumbnail(
+    app_handle: AppHandle,
+    queue: State<'_, ThumbnailQueue>,
+    file_path: String,
+    size: Option<u32>,
+) -> Result<String, String> {
+    let sizes = normalize_sizes(size.map(|s| vec![s]));
+    let cache_dir = queue.cache_dir().to_path_buf();
+
+    // Decoding is CPU work - keep it off the async runtime's threads
+    tauri::async_runtime::spawn_blocking(move || {
+        let outputs = generate(Path::new(&file_path), &cache_dir, &sizes)?;
+        record_thumbnail(&app_handle, &file_path, &outputs)
+    })
+    .await
+    .map_err(|e| format!("Thumbnail task failed: {}", e))?
+}
+
+/// Queue thumbnails for many files; progress arrives as "thumbnail-progress" events.
+/// Returns how many files were queued.
+#[tauri::command]
+pub async fn thumbnails_enqueue(
+    queue: State<'_, ThumbnailQueue>,
+    paths: Vec<String>,
+    sizes: Option<Vec<u32>>,
+) -> Result<usize, String> {
+    let sizes = normalize_sizes(sizes);
+    Ok(queue.enqueue(paths, &sizes))
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. WORKER POOL:
+//    - Several std::thread workers share one job queue (Arc<Mutex<Receiver>>)
+//    - Whoever grabs the lock first gets the next job
+//
+// 2. ATOMICS:
+//    - AtomicUsize is a number many threads can update without a Mutex
+//    - fetch_add() = "add 1 and tell me the old value", all in one step
+//
+// 3. let-else:
+//    - `let Ok(x) = result else { return; };`
+//    - Unwraps the happy path or runs the else block (which must exit)
+//
+// ============================================================================
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    /// A width x height PNG, coloured by `shade` so images can differ
+    fn png(width: u32, height: u32, shade: u8) -> Vec<u8> {
+        let image = image::RgbImage::from_pixel(width, height, image::Rgb([shade, 128, 64]));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut bytes, ImageFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+
+    fn dimensions(path: &Path) -> (u32, u32) {
+        image::image_dimensions(path).unwrap()
+    }
+
+    #[test]
+    fn generates_every_size_keeping_the_aspect_ratio() {
+        let dir = TempDir::new("thumbs");
+        let cache = dir.path().join("cache");
+        let photo = dir.write("photo.png", &png(400, 200, 10));
+
+        let outputs = generate(Path::new(&photo), &cache, &[128, 32]).unwrap();
+
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(dimensions(&outputs[0]), (128, 64));
+        assert_eq!(dimensions(&outputs[1]), (32, 16));
+        // <cache>/<first 2 hash chars>/<hash>-<size>.png
+        let hash = blake3::hash(&std::fs::read(&photo).unwrap()).to_hex().to_string();
+        assert_eq!(outputs[0], cache.join(&hash[..2]).join(format!("{}-128.png", hash)));
+    }
+
+    #[test]
+    fn same_content_reuses_the_cached_thumbnail() {
+        let dir = TempDir::new("thumbs");
+        let cache = dir.path().join("cache");
+        let original = dir.write("photo.png", &png(64, 64, 10));
+        let copy = dir.write("backup/photo copy.png", &png(64, 64, 10));
+        let edited = dir.write("edited.png", &png(64, 64, 200));
+
+        let first = generate(Path::new(&original), &cache, &[32]).unwrap();
+        // Mark the cached file: if it's decoded and written again, this goes
+        std::fs::write(&first[0], b"cached").unwrap();
+
+        let again = generate(Path::new(&copy), &cache, &[32]).unwrap();
+        assert_eq!(again, first);
+        assert_eq!(std::fs::read(&again[0]).unwrap(), b"cached");
+
+        // Different bytes, different thumbnail
+        let other = generate(Path::new(&edited), &cache, &[32]).unwrap();
+        assert_ne!(other, first);
+        assert_eq!(dimensions(&other[0]), (32, 32));
+    }
+
+    #[test]
+    fn odd_files_are_refused() {
+        let dir = TempDir::new("thumbs");
+        let cache = dir.path().join("cache");
+        let notes = dir.write("notes.txt", b"not an image");
+        assert!(generate(Path::new(&notes), &cache, &[256]).unwrap_err().contains("Unsupported"));
+        let broken = dir.write("broken.jpg", b"not a jpeg either");
+        assert!(generate(Path::new(&broken), &cache, &[256]).unwrap_err().contains("decode"));
+    }
+
+    #[test]
+    fn sizes_are_clamped_and_deduplicated() {
+        assert_eq!(normalize_sizes(None), DEFAULT_SIZES.to_vec());
+        assert_eq!(normalize_sizes(Some(vec![])), DEFAULT_SIZES.to_vec());
+        assert_eq!(normalize_sizes(Some(vec![512, 1, 512, 100_000, 64])), vec![512, MIN_SIZE, MAX_SIZE, 64]);
+    }
+}
@@ -0,0 +1,349 @@
+// ============================================================================
+// WATCH FALLBACK MODULE - Polling for folders native events can't cover
+// ============================================================================
+//
+// The native watcher (FSEvents on macOS, inotify on Linux) only hears about
+// changes the local kernel makes. That misses a lot:
+//
+// 1. Network shares (NFS, SMB...): another computer edits the file, our
+//    kernel never sees it happen
+// 2. FUSE mounts (sshfs, rclone...): same story, the changes happen
+//    somewhere else
+// 3. inotify's watch limit: one watch per folder, and a big photo library
+//    can use them all up - then watching fails outright
+//
+// For those folders file_watcher.rs uses notify's PollWatcher instead: it
+// re-reads the folder every few seconds and compares modified times. Slower
+// and heavier, but it sees everything. Its events go through the very same
+// debouncer -> handle_file_events pipeline, so the frontend can't tell the
+// difference (except in watch_status, which says which folders are polled).
+//
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, State};
+use crate::db::DbPool;
+use crate::scan_policy::normalize_root;
+
+/// Settings key for WatcherSettings
+const WATCHER_SETTING: &str = "watcher";
+
+/// Polling interval bounds (seconds)
+const MIN_POLL_INTERVAL: u64 = 2;
+const MAX_POLL_INTERVAL: u64 = 3600;
+
+/// File systems whose changes can happen on another machine
+const REMOTE_FILE_SYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "afpfs", "webdav", "davfs",
+    "9p", "afs", "ceph", "glusterfs", "lustre", "ncpfs", "sshfs",
+];
+
+/// What the user can change about the file watcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherSettings {
+    /// How often polled folders are re-read
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Poll network/FUSE folders and folders the native watcher can't watch
+    #[serde(default = "default_true")]
+    pub auto_fallback: bool,
+    /// Folders that are always polled
+    #[serde(default)]
+    pub force_polling: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_poll_interval_secs() -> u64 {
+    30
+}
+
+impl Default for WatcherSettings {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: default_poll_interval_secs(),
+            auto_fallback: true,
+            force_polling: Vec::new(),
+        }
+    }
+}
+
+impl WatcherSettings {
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        match crate::db::get_setting::<WatcherSettings>(conn, WATCHER_SETTING) {
+            Ok(Some(settings)) => settings,
+            Ok(None) => Self::default(),
+            Err(e) => {
+                eprintln!("⚠️  Failed to load watcher settings, using defaults: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs.clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL))
+    }
+
+    /// Why `path` should be polled right away, or None to try native events
+    pub fn polling_reason(&self, path: &Path) -> Option<String> {
+        if self.force_polling.iter().any(|forced| path.starts_with(forced)) {
+            return Some("Polling is turned on for this folder".to_string());
+        }
+        if !self.auto_fallback {
+            return None;
+        }
+        match file_system_type(path) {
+            Some(fs_type) if is_remote_or_fuse(&fs_type) => Some(format!(
+                "On a {} file system - changes made elsewhere don't send events",
+                fs_type
+            )),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// MOUNT DETECTION
+// ============================================================================
+
+fn is_remote_or_fuse(fs_type: &str) -> bool {
+    // fuseblk is a local disk behind FUSE (NTFS, exFAT) - native events work
+    REMOTE_FILE_SYSTEMS.contains(&fs_type)
+        || (fs_type.starts_with("fuse") && fs_type != "fuseblk" && fs_type != "fusectl")
+        || fs_type.starts_with("macfuse")
+        || fs_type.starts_with("osxfuse")
+}
+
+/// Type of the file system `path` lives on ("ext4", "nfs4", "fuse.sshfs"...)
+fn file_system_type(path: &Path) -> Option<String> {
+    // Resolve symlinks first: ~/nas -> /mnt/nas is still a network share
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    // The deepest mount point containing the path is the one it's on
+    mounts()
+        .into_iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// (mount point, file system type) of every mounted file system
+#[cfg(target_os = "linux")]
+fn mounts() -> Vec<(PathBuf, String)> {
+    match std::fs::read_to_string("/proc/self/mounts") {
+        Ok(text) => parse_proc_mounts(&text),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Lines look like: "server:/photos /mnt/photos nfs4 rw,relatime 0 0"
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_mounts(text: &str) -> Vec<(PathBuf, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = fields.next()?;
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(unescape_mount_point(mount_point)), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// /proc/mounts writes spaces etc. as octal escapes ("My\040Share")
+#[cfg(any(target_os = "linux", test))]
+fn unescape_mount_point(raw: &str) -> String {
+    raw.replace("\\040", " ")
+        .replace("\\011", "\t")
+        .replace("\\012", "\n")
+        .replace("\\134", "\\")
+}
+
+/// (mount point, file system type) of every mounted file system
+#[cfg(target_os = "macos")]
+fn mounts() -> Vec<(PathBuf, String)> {
+    match std::process::Command::new("mount").output() {
+        Ok(output) if output.status.success() => parse_mount_output(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// Lines look like: "//me@nas/Photos on /Volumes/Photos (smbfs, nodev, nosuid)"
+#[cfg(any(target_os = "macos", test))]
+fn parse_mount_output(text: &str) -> Vec<(PathBuf, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (device_and_mount, options) = line.rsplit_once(" (")?;
+            let (_, mount_point) = device_and_mount.rsplit_once(" on ")?;
+            let fs_type = options.split(',').next()?.trim_end_matches(')').trim();
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn mounts() -> Vec<(PathBuf, String)> {
+    Vec::new()
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn watch_get_settings(pool: State<'_, DbPool>) -> Result<WatcherSettings, String> {
+    Ok(WatcherSettings::load(&pool.reader()))
+}
+
+/// Save watcher settings and restart the watcher with them.
+/// Returns the new watcher status.
+#[tauri::command]
+pub async fn watch_set_settings(
+    app_handle: AppHandle,
+    pool: State<'_, DbPool>,
+    settings: WatcherSettings,
+) -> Result<crate::file_watcher::WatcherStatus, String> {
+    if !(MIN_POLL_INTERVAL..=MAX_POLL_INTERVAL).contains(&settings.poll_interval_secs) {
+        return Err(format!(
+            "Polling interval must be between {} and {} seconds",
+            MIN_POLL_INTERVAL, MAX_POLL_INTERVAL
+        ));
+    }
+
+    let mut force_polling: Vec<String> = settings
+        .force_polling
+        .iter()
+        .map(|p| normalize_root(p))
+        .filter(|p| !p.is_empty())
+        .collect();
+    force_polling.sort();
+    force_polling.dedup();
+
+    let settings = WatcherSettings { force_polling, ..settings };
+    crate::db::set_setting(&pool.writer(), WATCHER_SETTING, &settings).map_err(|e| e.to_string())?;
+
+    crate::file_watcher::rewatch(&app_handle)
+}
+
+// ============================================================================
+// RUST CONCEPTS IN THIS FILE:
+// ============================================================================
+//
+// 1. #[cfg(target_os = "...")]:
+//    - Only ONE of the three mounts() functions is compiled, depending on
+//      the platform - callers don't need to know which
+//
+// 2. #[serde(default = "default_true")]:
+//    - Settings saved by an older Aurora (without the field) still load,
+//      and the missing field gets the function's value
+//
+// 3. Struct update syntax (`..settings`):
+//    - "Everything else from `settings`" - like { ...settings } in JS
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_and_fuse_file_systems() {
+        let table = [
+            ("nfs", true),
+            ("nfs4", true),
+            ("cifs", true),
+            ("smbfs", true),
+            ("fuse.sshfs", true),
+            ("fuse.rclone", true),
+            ("fuse", true),
+            ("macfuse", true),
+            ("osxfuse", true),
+            // Local disks behind FUSE, and FUSE's own control file system
+            ("fuseblk", false),
+            ("fusectl", false),
+            ("ext4", false),
+            ("apfs", false),
+            ("tmpfs", false),
+            ("nfsd", false),
+        ];
+        for (fs_type, remote) in table {
+            assert_eq!(is_remote_or_fuse(fs_type), remote, "{}", fs_type);
+        }
+    }
+
+    #[test]
+    fn mount_point_escapes() {
+        let table = [
+            ("/mnt/photos", "/mnt/photos"),
+            ("/media/me/My\\040Share", "/media/me/My Share"),
+            ("/mnt/a\\011b\\012c", "/mnt/a\tb\nc"),
+            ("/mnt/back\\134slash", "/mnt/back\\slash"),
+        ];
+        for (raw, unescaped) in table {
+            assert_eq!(unescape_mount_point(raw), unescaped, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn proc_mounts_lines() {
+        let text = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+server:/photos /mnt/photos nfs4 rw,relatime,vers=4.2 0 0
+me@host:/home/me /home/me/remote\\040box fuse.sshfs rw,nosuid,nodev 0 0
+/dev/sdb1 /media/usb fuseblk rw,nosuid 0 0
+fusectl /sys/fs/fuse/connections fusectl rw 0 0
+broken-line
+";
+        let mounts = parse_proc_mounts(text);
+        let expected = [
+            ("/", "ext4"),
+            ("/mnt/photos", "nfs4"),
+            ("/home/me/remote box", "fuse.sshfs"),
+            ("/media/usb", "fuseblk"),
+            ("/sys/fs/fuse/connections", "fusectl"),
+        ];
+        let expected: Vec<(PathBuf, String)> =
+            expected.iter().map(|(path, fs)| (PathBuf::from(path), fs.to_string())).collect();
+        assert_eq!(mounts, expected);
+    }
+
+    #[test]
+    fn macos_mount_lines() {
+        let text = "\
+/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)
+//me@nas/Photos on /Volumes/Photos (smbfs, nodev, nosuid, mounted by me)
+me@host:/data on /Users/me/My Mount (macfuse, nodev, nosuid, synchronous)
+map auto_home on /System/Volumes/Data/home (autofs, automounted, nobrowse)
+not a mount line
+";
+        let mounts = parse_mount_output(text);
+        let expected = [
+            ("/", "apfs"),
+            ("/Volumes/Photos", "smbfs"),
+            ("/Users/me/My Mount", "macfuse"),
+            ("/System/Volumes/Data/home", "autofs"),
+        ];
+        let expected: Vec<(PathBuf, String)> =
+            expected.iter().map(|(path, fs)| (PathBuf::from(path), fs.to_string())).collect();
+        assert_eq!(mounts, expected);
+    }
+
+    #[test]
+    fn settings_saved_without_a_field_still_load() {
+        let settings: WatcherSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.poll_interval_secs, 30);
+        assert!(settings.auto_fallback);
+        assert!(settings.force_polling.is_empty());
+
+        let settings: WatcherSettings = serde_json::from_str(r#"{"force_polling": ["/mnt/nas"]}"#).unwrap();
+        assert_eq!(settings.poll_interval(), Duration::from_secs(30));
+        let reason = settings.polling_reason(Path::new("/mnt/nas/photos"));
+        assert_eq!(reason.as_deref(), Some("Polling is turned on for this folder"));
+    }
+}
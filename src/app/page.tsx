@@ -3,7 +3,7 @@
 import { useEffect, useMemo, useRef, useState } from 'react'
 import { motion, AnimatePresence } from 'framer-motion'
 import { listen } from '@tauri-apps/api/event'
-import { greet, scanDirectories, dbGetAllFiles, dbGetFileCount, watchSetPaths, onFileCreated, onFileModified, onFileRemoved, type FileInfo } from '@/lib/tauri'
+import { greet, scanDirectories, dbGetAllFiles, dbGetFileCount, sourcesAdd, onFileChanges, applyFileChanges, FILE_CHANGES_VERSION, onReconcileComplete, type FileInfo } from '@/lib/tauri'
 import { RecentActivitySidebar } from '@/components/RecentActivitySidebar'
 import { QuickAccessPanel } from '@/components/QuickAccessPanel'
 import { ValuesIntegration } from '@/components/ValuesIntegration'
@@ -242,9 +242,11 @@ export default function HomePage() {
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [fileCount, loading, scanning, settings.scanSources])
 
-  // Configure Rust file watcher based on settings
+  // Register chosen folders with the backend. It keeps its own list of
+  // sources (and restores the file watcher from it at startup), so this
+  // only matters for newly added folders - known ones are left alone.
   useEffect(() => {
-    watchSetPaths(settings.scanSources ?? [])
+    sourcesAdd(settings.scanSources ?? [])
   }, [settings.scanSources])
 
   // Live updates from Rust file watcher
@@ -259,15 +261,26 @@ export default function HomePage() {
       }, 400)
     }
 
-    let unlistenCreated: (() => void) | null = null
-    let unlistenModified: (() => void) | null = null
-    let unlistenRemoved: (() => void) | null = null
+    let unlistenChanges: (() => void) | null = null
+    let unlistenReconciled: (() => void) | null = null
 
     const start = async () => {
       try {
-        unlistenCreated = await onFileCreated(() => scheduleReload())
-        unlistenModified = await onFileModified(() => scheduleReload())
-        unlistenRemoved = await onFileRemoved(() => scheduleReload())
+        unlistenChanges = await onFileChanges(({ version, changes }) => {
+          // A payload shape we don't know yet: fall back to reloading
+          if (version !== FILE_CHANGES_VERSION) {
+            scheduleReload()
+            return
+          }
+          setFiles((prev) => applyFileChanges(prev, changes))
+          const added = changes.filter((c) => c.kind === 'created').length
+          const removed = changes.filter((c) => c.kind === 'removed').length
+          setFileCount((count) => Math.max(0, count + added - removed))
+        })
+        // Startup catch-up for changes made while Aurora was closed
+        unlistenReconciled = await onReconcileComplete((summary) => {
+          if (summary.created + summary.modified + summary.removed > 0) scheduleReload()
+        })
       } catch {
         // Not running inside Tauri (or events not available)
       }
@@ -277,9 +290,8 @@ export default function HomePage() {
 
     return () => {
       if (timerRef.id) clearTimeout(timerRef.id)
-      if (unlistenCreated) unlistenCreated()
-      if (unlistenModified) unlistenModified()
-      if (unlistenRemoved) unlistenRemoved()
+      if (unlistenChanges) unlistenChanges()
+      if (unlistenReconciled) unlistenReconciled()
     }
   }, [])
 
@@ -328,9 +340,11 @@ export default function HomePage() {
 
     setScanning(true)
     try {
-      const scannedFiles = await scanDirectories(sources)
+      const summary = await scanDirectories(sources)
 
-      console.log(`📁 Scanned ${scannedFiles.length} files`)
+      console.log(
+        `📁 Scan: ${summary.added} added, ${summary.updated} updated, ${summary.removed} removed, ${summary.unchanged} unchanged`
+      )
       await loadFilesFromDatabase()
     } catch (error) {
       console.error('Failed to scan files:', error)
@@ -36,7 +36,7 @@ export function GlobalSearch({ isOpen, onClose, theme }: GlobalSearchProps) {
         const timer = setTimeout(async () => {
             try {
                 const hits = await dbSearchFiles(query)
-                setResults(hits)
+                setResults(hits.map(hit => hit.file))
                 setSelectedIndex(0)
             } catch (e) {
                 console.error('Search failed:', e)
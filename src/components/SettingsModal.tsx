@@ -9,6 +9,7 @@ import { Archive, GripVertical, Settings as SettingsIcon, X, Palette, LayoutGrid
 import { type ThemePreference } from '@/lib/useThemeMode'
 import { type AuroraSettings } from '@/lib/settings'
 import { open } from '@tauri-apps/plugin-dialog'
+import { sourcesRemove } from '@/lib/tauri'
 import { VALUE_ICON_OPTIONS, type ValueIconId } from '@/lib/value-icons'
 import { CoreValuesEditor, type CoreValue, type CoreValueDraft } from '@/components/settings/CoreValuesEditor'
 import { QuickStartSection } from '@/components/settings/QuickStartSection'
@@ -93,6 +94,8 @@ export function SettingsModal({
 
   const removeScanSource = (path: string) => {
     updateSettings({ scanSources: (settings.scanSources ?? []).filter((p) => p !== path) })
+    // Also stop watching it (adding is handled by the page's sourcesAdd effect)
+    sourcesRemove(path)
   }
 
   const createId = () => {
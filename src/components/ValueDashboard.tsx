@@ -1058,7 +1058,7 @@ function RelevantFilesWidget({
       try {
         const results = await dbSearchFiles(query)
         if (active && results.length > 0) {
-          setMatches(results.slice(0, 6))
+          setMatches(results.slice(0, 6).map((r) => r.file))
         }
       } catch (e) {
         console.error('Smart widget search failed:', e)
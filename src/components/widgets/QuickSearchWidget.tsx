@@ -57,7 +57,7 @@ export function QuickSearchWidget({
     setIsSearching(true)
     try {
       const searchResults = await dbSearchFiles(searchQuery)
-      setResults(searchResults.slice(0, 5))
+      setResults(searchResults.slice(0, 5).map((r) => r.file))
       
       // Save to history
       const newHistory: SearchHistoryItem[] = [
@@ -32,6 +32,7 @@ export interface FileInfo {
   thumbnail_path?: string
   finder_tags: string[]
   finder_colors: number[]
+  metadata?: FileMetadata  // Emotional metadata, if the user added any
 }
 
 export interface FinderTag {
@@ -46,6 +47,7 @@ export interface FileMetadata {
   vibe_color?: string
   location?: string
   energy_level?: string
+  notes?: string
 }
 
 export interface AppleCalendarEvent {
@@ -105,14 +107,133 @@ export async function appleCalendarListEvents(startMs: number, endMs: number): P
 }
 
 /**
- * Scan directories for files
+ * What a scan changed. Files themselves are read back with dbGetAllFiles().
+ */
+export interface ScanSummary {
+  job_id: number
+  added: number
+  updated: number
+  removed: number
+  unchanged: number
+  cancelled: boolean
+  sources: SourceScanStats[]  // The same counts, per scanned folder
+}
+
+/**
+ * How the scan of one folder went
+ */
+export interface SourceScanStats {
+  path: string
+  status: 'done' | 'cancelled' | 'missing'
+  added: number
+  updated: number
+  removed: number
+  unchanged: number
+  errors: number             // Entries that couldn't be read
+  last_error: string | null
+}
+
+/**
+ * Scan directories for files (only new or changed files are re-indexed,
+ * and files that disappeared from disk are removed from the index).
+ * Resolves when the scan finishes; use onScanProgress() for live updates.
  * @param directories - Array of directory paths to scan
- * @returns Array of FileInfo objects
+ * @returns Counts of added/updated/removed/unchanged files
  */
 export async function scanDirectories(
   directories: string[]
-): Promise<FileInfo[]> {
-  return await invoke<FileInfo[]>('scan_directories', { directories })
+): Promise<ScanSummary> {
+  return await invoke<ScanSummary>('scan_directories', { directories })
+}
+
+/**
+ * Stop a running scan (files found so far are kept)
+ * @param jobId - From ScanProgress.job_id
+ * @returns false if the scan had already finished
+ */
+export async function scanCancel(jobId: number): Promise<boolean> {
+  return await invoke<boolean>('scan_cancel', { jobId })
+}
+
+/**
+ * How one source folder is scanned (and watched)
+ */
+export interface ScanPolicy {
+  max_depth: number
+  follow_links: boolean
+  include_hidden: boolean
+  min_size?: number | null
+  max_size?: number | null
+  allowed_extensions: string[]  // Empty = every extension
+  denied_extensions: string[]
+  index_directories: boolean    // Folders get their own rows (file_type "folder")
+}
+
+/**
+ * Get a source folder's scan policy (the defaults if none was saved)
+ */
+export async function scanPolicyGet(root: string): Promise<ScanPolicy> {
+  return await invoke<ScanPolicy>('scan_policy_get', { root })
+}
+
+/**
+ * Save a source folder's scan policy
+ * @returns The policy as saved (extensions lowercased, etc.)
+ */
+export async function scanPolicySet(root: string, policy: Partial<ScanPolicy>): Promise<ScanPolicy> {
+  return await invoke<ScanPolicy>('scan_policy_set', { root, policy })
+}
+
+/**
+ * Go back to the default scan policy for a source folder
+ */
+export async function scanPolicyReset(root: string): Promise<void> {
+  return await invoke('scan_policy_reset', { root })
+}
+
+/**
+ * A folder Aurora indexes and watches (stored in the backend, so the
+ * watcher is restored at startup without the frontend re-sending paths)
+ */
+export interface ScanSource {
+  id: number
+  path: string
+  enabled: boolean
+  added_at: number
+  last_scan_at: number | null  // null = never scanned
+  last_scan_status: 'done' | 'cancelled' | 'missing' | null
+  last_scan_added: number
+  last_scan_updated: number
+  last_scan_removed: number
+  last_scan_unchanged: number
+  last_scan_errors: number
+  last_error: string | null
+}
+
+export async function sourcesList(): Promise<ScanSource[]> {
+  return (await safeInvoke<ScanSource[]>('sources_list')) ?? []
+}
+
+/**
+ * Register folders (already-registered ones are left alone)
+ * @returns Every source
+ */
+export async function sourcesAdd(paths: string[]): Promise<ScanSource[]> {
+  return (await safeInvoke<ScanSource[]>('sources_add', { paths })) ?? []
+}
+
+/**
+ * Forget a folder (its already-indexed files are kept)
+ */
+export async function sourcesRemove(path: string): Promise<ScanSource[]> {
+  return (await safeInvoke<ScanSource[]>('sources_remove', { path })) ?? []
+}
+
+/**
+ * Pause or resume watching a folder without forgetting it
+ */
+export async function sourcesSetEnabled(path: string, enabled: boolean): Promise<ScanSource[]> {
+  return (await safeInvoke<ScanSource[]>('sources_set_enabled', { path, enabled })) ?? []
 }
 
 /**
@@ -131,16 +252,88 @@ export async function getFinderTags(filePath: string): Promise<FinderTag[]> {
 }
 
 /**
- * Generate a thumbnail for a file using macOS Quick Look
+ * Add Finder tags to one or more files (a tag they already have gets the new color)
+ */
+export async function tagsAdd(paths: string[], tags: FinderTag[]): Promise<void> {
+  return await invoke('tags_add', { paths, tags })
+}
+
+/**
+ * Remove Finder tags (by name) from one or more files
+ */
+export async function tagsRemove(paths: string[], names: string[]): Promise<void> {
+  return await invoke('tags_remove', { paths, names })
+}
+
+/**
+ * Change a tag's color on one or more files
+ */
+export async function tagsSetColor(
+  paths: string[],
+  name: string,
+  color: number
+): Promise<void> {
+  return await invoke('tags_set_color', { paths, name, color })
+}
+
+/**
+ * Tag name -> color code used for tags stored without a color
+ * (freedesktop user.xdg.tags on Linux). Names are lowercase.
+ */
+export async function tagsGetColorMap(): Promise<Record<string, number>> {
+  return await invoke<Record<string, number>>('tags_get_color_map')
+}
+
+/**
+ * Replace the tag name -> color mapping
+ */
+export async function tagsSetColorMap(colors: Record<string, number>): Promise<void> {
+  return await invoke('tags_set_color_map', { colors })
+}
+
+/**
+ * Global ignore rules (gitignore syntax). Folders can add their own
+ * rules in a .auroraignore file.
+ */
+export interface IgnoreSettings {
+  patterns: string[]
+  honor_gitignore: boolean
+}
+
+export async function ignoreGetSettings(): Promise<IgnoreSettings> {
+  return await invoke<IgnoreSettings>('ignore_get_settings')
+}
+
+/**
+ * Save ignore rules (applies to the next scan and to watcher events)
+ */
+export async function ignoreSetSettings(settings: IgnoreSettings): Promise<void> {
+  return await invoke('ignore_set_settings', { settings })
+}
+
+/**
+ * Generate a thumbnail for an image (PNG, JPEG, GIF, WebP) right now
  * @param filePath - Full path to the file
- * @param outputDir - Directory to save thumbnail
- * @returns Path to generated thumbnail
+ * @param size - Longest edge in pixels (defaults to 256)
+ * @returns Path to the cached thumbnail (also saved as file.thumbnail_path)
  */
 export async function generateThumbnail(
   filePath: string,
-  outputDir: string
+  size?: number
 ): Promise<string> {
-  return await invoke<string>('generate_thumbnail', { filePath, outputDir })
+  return await invoke<string>('generate_thumbnail', { filePath, size })
+}
+
+/**
+ * Queue thumbnails for many files on the background workers
+ * Listen with onThumbnailProgress() to hear about each finished file
+ * @returns How many files were queued
+ */
+export async function thumbnailsEnqueue(
+  paths: string[],
+  sizes?: number[]
+): Promise<number> {
+  return await invoke<number>('thumbnails_enqueue', { paths, sizes })
 }
 
 /**
@@ -152,9 +345,25 @@ export async function updateFileMetadata(
   return await invoke('update_file_metadata', { metadata })
 }
 
+/**
+ * Load emotional metadata for a file (null if none saved yet)
+ */
+export async function getFileMetadata(
+  fileId: number
+): Promise<FileMetadata | null> {
+  return await invoke<FileMetadata | null>('get_file_metadata', { fileId })
+}
+
+/**
+ * Remove all emotional metadata from a file
+ */
+export async function clearFileMetadata(fileId: number): Promise<void> {
+  return await invoke('clear_file_metadata', { fileId })
+}
+
 /**
  * Search files using full-text search
- * @param query - Search query
+ * @param query - Search query (same syntax as dbSearchFiles)
  */
 export async function searchFiles(query: string): Promise<FileInfo[]> {
   return await invoke<FileInfo[]>('search_files', { query })
@@ -173,11 +382,84 @@ export async function dbGetAllFiles(): Promise<FileInfo[]> {
 }
 
 /**
- * Search files in database using FTS5
- * @param query - Search query (supports FTS5 syntax)
+ * Search files in database
+ * @param query - Words, "exact phrases" and filters, e.g.
+ *   `budget type:pdf size:>1mb modified:<7d`, `tag:urgent -type:png`,
+ *   `cluster:"In Progress" opened:never`, `mood:calm in:~/Documents`.
+ *   Rejects with a readable message when a filter can't be understood.
+ * @returns Best results first, each with what matched (name/path ranges,
+ *   content and notes snippets)
  */
-export async function dbSearchFiles(query: string): Promise<FileInfo[]> {
-  return await invoke<FileInfo[]>('db_search_files', { query })
+export async function dbSearchFiles(query: string, mode: SearchMode = 'auto'): Promise<SearchResult[]> {
+  return await invoke<SearchResult[]>('db_search_files', { query, mode })
+}
+
+/**
+ * How search matches words:
+ * - 'auto': whole words first, fuzzy if that finds nothing (default)
+ * - 'text': whole words and word prefixes only
+ * - 'fuzzy': letters in order with gaps allowed ("resme" finds "resume")
+ */
+export type SearchMode = 'auto' | 'text' | 'fuzzy'
+
+/**
+ * Bytes [start, end) of a name or path to highlight.
+ * UTF-8 bytes, not JS string indices - use highlightSegments() to split.
+ */
+export interface MatchRange {
+  start: number
+  end: number
+}
+
+/**
+ * Some text (a bit of a file's contents or notes) with the parts that matched
+ */
+export interface Snippet {
+  text: string
+  ranges: MatchRange[]
+}
+
+/**
+ * One dbSearchFiles result and why it matched
+ */
+export interface SearchResult {
+  file: FileInfo
+  name_ranges: MatchRange[]   // Parts of file.name to bold
+  path_ranges: MatchRange[]   // Parts of file.path to bold
+  content: Snippet | null     // Text inside the file around the match
+  notes: Snippet | null       // The file's notes around the match
+}
+
+/**
+ * Split text into plain and matched pieces, ready to render
+ * (e.g. `<mark>` around the ones with match: true)
+ */
+export function highlightSegments(text: string, ranges: MatchRange[]): { text: string; match: boolean }[] {
+  const bytes = new TextEncoder().encode(text)
+  const decoder = new TextDecoder()
+  const segments: { text: string; match: boolean }[] = []
+  let last = 0
+  for (const { start, end } of ranges) {
+    if (start > last) segments.push({ text: decoder.decode(bytes.slice(last, start)), match: false })
+    segments.push({ text: decoder.decode(bytes.slice(start, end)), match: true })
+    last = end
+  }
+  if (last < bytes.length) segments.push({ text: decoder.decode(bytes.slice(last)), match: false })
+  return segments
+}
+
+export interface FuzzyMatch {
+  file: FileInfo
+  score: number
+  name_ranges: MatchRange[]
+  path_ranges: MatchRange[]
+}
+
+/**
+ * Fuzzy filename search with highlight ranges (same filters as dbSearchFiles)
+ */
+export async function dbFuzzySearch(query: string, limit = 50): Promise<FuzzyMatch[]> {
+  return await invoke<FuzzyMatch[]>('db_fuzzy_search', { query, limit })
 }
 
 /**
@@ -203,41 +485,161 @@ export async function dbRecordOpen(path: string): Promise<void> {
   await invoke('db_record_open', { path })
 }
 
+/**
+ * How search results are ordered: text match, how often/recently a file
+ * was opened, how recently it changed, plus bonuses for tags and clusters
+ */
+export interface SearchRanking {
+  text_weight: number
+  frecency_weight: number
+  recency_weight: number
+  tag_boost: number
+  cluster_boost: number
+  opened_half_life_days: number    // An open this old counts half
+  modified_half_life_days: number  // A change this old counts half
+}
+
+export async function searchGetRanking(): Promise<SearchRanking | null> {
+  return await safeInvoke<SearchRanking>('search_get_ranking')
+}
+
+/**
+ * Save ranking weights (weights must be 0 or more, half-lives more than 0)
+ */
+export async function searchSetRanking(ranking: SearchRanking): Promise<SearchRanking | null> {
+  return await safeInvoke<SearchRanking>('search_set_ranking', { ranking })
+}
+
 // ============================================================================
 // EVENT LISTENERS (Real-time updates from Rust)
 // ============================================================================
 
 /**
- * Listen for file created events
- * @param callback - Function to call when a file is created
+ * Version of the "file-changes" payload this frontend understands
+ */
+export const FILE_CHANGES_VERSION = 1
+
+export type FileChangeKind = 'created' | 'modified' | 'removed' | 'renamed'
+
+/**
+ * One change from the file watcher
+ */
+export interface FileChange {
+  kind: FileChangeKind
+  path: string
+  file: FileInfo | null          // The row with its id (for 'removed': the row before deletion)
+  previous_path: string | null   // 'renamed' only - row, tags and metadata were kept
+}
+
+/**
+ * Everything that changed during one watcher debounce window
+ * (at most one change per path)
+ */
+export interface FileChanges {
+  version: number
+  changes: FileChange[]
+}
+
+/**
+ * Listen for file watcher changes
+ * @param callback - Called once per batch of changes
  * @returns Unlisten function to stop listening
  */
-export async function onFileCreated(
-  callback: (paths: string[]) => void
+export async function onFileChanges(
+  callback: (batch: FileChanges) => void
+): Promise<UnlistenFn> {
+  return await listen<FileChanges>('file-changes', (event) => {
+    callback(event.payload)
+  })
+}
+
+/**
+ * Apply a batch of changes to a list of files (matched by database id),
+ * newest first - so the UI can update without asking the database again
+ */
+export function applyFileChanges(files: FileInfo[], changes: FileChange[]): FileInfo[] {
+  const touched = new Set<number>()
+  const updated: FileInfo[] = []
+  for (const change of changes) {
+    const id = change.file?.id
+    if (id == null) continue
+    touched.add(id)
+    if (change.kind !== 'removed' && change.file) updated.push(change.file)
+  }
+
+  return [...updated, ...files.filter((f) => f.id == null || !touched.has(f.id))]
+    .sort((a, b) => b.modified_at - a.modified_at)
+}
+
+export interface ThumbnailProgress {
+  path: string
+  thumbnail_path?: string
+  error?: string
+  completed: number
+  total: number
+}
+
+/**
+ * Listen for background thumbnail progress
+ */
+export async function onThumbnailProgress(
+  callback: (progress: ThumbnailProgress) => void
 ): Promise<UnlistenFn> {
-  return await listen<string[]>('file-created', (event) => {
+  return await listen<ThumbnailProgress>('thumbnail-progress', (event) => {
     callback(event.payload)
   })
 }
 
+export interface ScanProgress {
+  job_id: number
+  directories_visited: number
+  files_found: number
+  current_path: string
+  files_per_second: number
+  elapsed_ms: number
+  done: boolean
+}
+
 /**
- * Listen for file modified events
+ * Listen for scan progress (a few events per second while a scan runs)
  */
-export async function onFileModified(
-  callback: (paths: string[]) => void
+export async function onScanProgress(
+  callback: (progress: ScanProgress) => void
 ): Promise<UnlistenFn> {
-  return await listen<string[]>('file-modified', (event) => {
+  return await listen<ScanProgress>('scan-progress', (event) => {
     callback(event.payload)
   })
 }
 
 /**
- * Listen for file removed events
+ * What reconciliation changed (files created/edited/deleted while
+ * Aurora was closed, or since the last reconcile)
+ */
+export interface ReconcileSummary {
+  job_id: number
+  sources: number
+  created: number
+  modified: number
+  removed: number
+  cancelled: boolean
+  elapsed_ms: number
+}
+
+/**
+ * Compare every enabled source with the index and apply the differences
+ * (also runs automatically at startup). Cancel with scanCancel(job_id).
+ */
+export async function reconcileSources(): Promise<ReconcileSummary> {
+  return await invoke<ReconcileSummary>('reconcile_sources')
+}
+
+/**
+ * Listen for the one event sent after each reconciliation
  */
-export async function onFileRemoved(
-  callback: (paths: string[]) => void
+export async function onReconcileComplete(
+  callback: (summary: ReconcileSummary) => void
 ): Promise<UnlistenFn> {
-  return await listen<string[]>('file-removed', (event) => {
+  return await listen<ReconcileSummary>('reconcile-complete', (event) => {
     callback(event.payload)
   })
 }
@@ -364,33 +766,176 @@ export async function safeInvoke<T>(
   }
 }
 
+// ============================================================================
+// DUPLICATE FILES (content hashes, computed in the background)
+// ============================================================================
+
+/**
+ * Files with identical content
+ */
+export interface DuplicateGroup {
+  hash: string
+  size: number
+  wasted_bytes: number   // Freed by deleting all but one copy
+  files: FileInfo[]      // Oldest first
+}
+
+export interface DuplicateReport {
+  groups: DuplicateGroup[]
+  total_wasted_bytes: number
+  pending: number        // Files not hashed yet - more groups may appear
+}
+
+/**
+ * Groups of identical files, biggest waste first
+ * @param limit - Max groups (default 100)
+ */
+export async function findDuplicates(limit?: number): Promise<DuplicateReport | null> {
+  return await safeInvoke<DuplicateReport>('find_duplicates', { limit })
+}
+
+export interface HashingSummary {
+  partial_hashed: number
+  fully_hashed: number
+}
+
+/**
+ * Fires after a background hashing pass - a good time to refresh duplicates
+ */
+export async function onHashingComplete(
+  callback: (summary: HashingSummary) => void
+): Promise<UnlistenFn> {
+  return await listen<HashingSummary>('hashing-complete', (event) => {
+    callback(event.payload)
+  })
+}
+
+// ============================================================================
+// CONTENT SEARCH (text read out of documents in the background)
+// ============================================================================
+
+export interface ContentIndexStatus {
+  indexed: number        // Files whose text is searchable
+  pending: number        // Files still waiting to be read
+}
+
+/**
+ * How far content indexing has got
+ */
+export async function contentIndexStatus(): Promise<ContentIndexStatus | null> {
+  return await safeInvoke<ContentIndexStatus>('content_index_status')
+}
+
+export interface ContentIndexSummary {
+  files_read: number
+}
+
+/**
+ * Fires after a background pass read new text - searches may find more now
+ */
+export async function onContentIndexed(
+  callback: (summary: ContentIndexSummary) => void
+): Promise<UnlistenFn> {
+  return await listen<ContentIndexSummary>('content-indexed', (event) => {
+    callback(event.payload)
+  })
+}
+
 // ============================================================================
 // FILE WATCHER (Rust -> JS events + control)
 // ============================================================================
 
-export async function watchSetPaths(paths: string[]): Promise<void> {
-  await safeInvoke<void>('watch_set_paths', { paths })
+/**
+ * One folder the watcher was asked to watch
+ */
+export interface WatchedPath {
+  path: string
+  watching: boolean      // Events are actually coming in for it
+  polling: boolean       // Watched by the polling fallback
+  poll_reason: string | null  // Network/FUSE mount, forced, or the native watcher failed
+  error: string | null   // Why it isn't watched (e.g. the inotify watch limit)
+  starting: boolean      // Still being set up - see onWatcherStatus for how it went
+}
+
+/**
+ * What the file watcher is actually doing
+ */
+export interface WatcherStatus {
+  active: boolean
+  paths: WatchedPath[]
+  events_received: number
+  changes_emitted: number
+  errors: number
+  last_event_at: number | null  // Unix timestamp
+  last_error: string | null
+  polling_fallback: boolean
+  starting: boolean      // Folders are still being set up
+}
+
+/**
+ * Watch these folders (replacing the current ones). Resolves right away
+ * with the folders `starting`; a "watcher-status" event says how each
+ * one went. Returns null if none of them exist.
+ */
+export async function watchSetPaths(paths: string[]): Promise<WatcherStatus | null> {
+  return await safeInvoke<WatcherStatus>('watch_set_paths', { paths })
 }
 
 export async function watchStop(): Promise<void> {
   await safeInvoke<void>('watch_stop')
 }
 
+export async function watchStatus(): Promise<WatcherStatus | null> {
+  return await safeInvoke<WatcherStatus>('watch_status')
+}
+
+/**
+ * When folders are polled instead of using native change events
+ */
+export interface WatcherSettings {
+  poll_interval_secs: number  // 2 - 3600
+  auto_fallback: boolean      // Poll network/FUSE folders and ones that can't be watched
+  force_polling: string[]     // Folders that are always polled
+}
+
+export async function watchGetSettings(): Promise<WatcherSettings | null> {
+  return await safeInvoke<WatcherSettings>('watch_get_settings')
+}
+
+/**
+ * Save watcher settings (restarts the watcher with them)
+ */
+export async function watchSetSettings(settings: WatcherSettings): Promise<WatcherStatus | null> {
+  return await safeInvoke<WatcherStatus>('watch_set_settings', { settings })
+}
+
+/**
+ * Listen for watcher status changes (paths started/failed, new events)
+ * @returns Unlisten function to stop listening
+ */
+export async function onWatcherStatus(
+  callback: (status: WatcherStatus) => void
+): Promise<UnlistenFn> {
+  return await listen<WatcherStatus>('watcher-status', (event) => {
+    callback(event.payload)
+  })
+}
+
 // ============================================================================
 // USAGE EXAMPLES:
 // ============================================================================
 //
 // In your React components:
 //
-// import { scanDirectories, onFileCreated } from '@/lib/tauri'
+// import { scanDirectories, onFileChanges } from '@/lib/tauri'
 //
 // // Call Rust function
-// const files = await scanDirectories(['/Users/you/Documents'])
+// const summary = await scanDirectories(['/Users/you/Documents'])
 //
 // // Listen for real-time events
 // useEffect(() => {
-//   const unlisten = onFileCreated((paths) => {
-//     console.log('New files:', paths)
+//   const unlisten = onFileChanges(({ changes }) => {
+//     console.log('Changed files:', changes)
 //     // Update your state!
 //   })
 //   return () => unlisten.then(fn => fn())